@@ -64,6 +64,7 @@ fn generate_items(count: usize) -> Vec<StreamItem> {
                 duration_ms: Some((i * 10) as u64),
                 progress: if i % 4 == 1 { Some((i % 100) as u8) } else { None },
                 expanded: false,
+                output: String::new(),
             }),
             4 => StreamContent::Plan(
                 PlanBlock::new(format!("Plan #{}", i))
@@ -1,5 +1,8 @@
 //! Main application structure and state management
 
+use crate::protocol::NegotiatedProtocol;
+use crate::resilience::ConnectionState;
+
 #[derive(Clone)]
 pub enum Message {
     /// Frame update event
@@ -8,6 +11,10 @@ pub enum Message {
     Connected,
     /// Connection lost
     Disconnected(String),
+    /// Connection state reported by the orchestrator client
+    ConnectionStateChanged(ConnectionState),
+    /// Protocol handshake completed
+    Negotiated(NegotiatedProtocol),
     /// Error occurred
     Error(String),
 }
@@ -16,6 +23,10 @@ pub enum Message {
 pub struct AguiApp {
     /// Is the app connected to the orchestrator?
     pub is_connected: bool,
+    /// Detailed connection state (for status display)
+    pub connection_state: ConnectionState,
+    /// Protocol version and capabilities agreed with the orchestrator
+    pub protocol: Option<NegotiatedProtocol>,
     /// Current error message, if any
     pub error: Option<String>,
     /// Frame counter for performance monitoring
@@ -28,6 +39,8 @@ impl AguiApp {
         tracing::debug!("Creating new AguiApp instance");
         Self {
             is_connected: false,
+            connection_state: ConnectionState::Disconnected,
+            protocol: None,
             error: None,
             frame_count: 0,
         }
@@ -47,15 +60,40 @@ impl AguiApp {
             Message::Connected => {
                 tracing::info!("Connected to orchestrator");
                 self.is_connected = true;
+                self.connection_state = ConnectionState::Connected;
                 self.error = None;
                 crate::metrics::record_connection_attempt(true);
             }
             Message::Disconnected(reason) => {
                 tracing::warn!("Disconnected from orchestrator: {}", reason);
                 self.is_connected = false;
+                self.connection_state = ConnectionState::Disconnected;
+                self.protocol = None;
                 self.error = Some(reason);
                 crate::metrics::record_connection_attempt(false);
             }
+            Message::ConnectionStateChanged(state) => {
+                if state == ConnectionState::Connected {
+                    self.handle_message(Message::Connected);
+                } else {
+                    if self.is_connected {
+                        self.handle_message(Message::Disconnected(state.label().to_string()));
+                    }
+                    if state == ConnectionState::Failed && self.error.is_none() {
+                        // Keep a more specific error (e.g. handshake rejection) if present
+                        self.error = Some(state.label().to_string());
+                    }
+                    self.connection_state = state;
+                }
+            }
+            Message::Negotiated(protocol) => {
+                tracing::info!(
+                    "Negotiated protocol {} with capabilities {:?}",
+                    protocol.version,
+                    protocol.capabilities
+                );
+                self.protocol = Some(protocol);
+            }
             Message::Error(msg) => {
                 tracing::error!("Application error: {}", msg);
                 self.error = Some(msg);
@@ -68,13 +106,16 @@ impl AguiApp {
         self.error.is_none()
     }
 
+    /// Check whether the orchestrator negotiated a capability
+    pub fn supports(&self, capability: &str) -> bool {
+        self.protocol
+            .as_ref()
+            .is_some_and(|protocol| protocol.supports(capability))
+    }
+
     /// Get current connection status
     pub fn connection_status(&self) -> &'static str {
-        if self.is_connected {
-            "Connected"
-        } else {
-            "Disconnected"
-        }
+        self.connection_state.label()
     }
 }
 
@@ -114,6 +155,28 @@ mod tests {
         assert_eq!(app.error.as_deref(), Some(error_msg));
     }
 
+    #[test]
+    fn test_connection_state_changes() {
+        let mut app = AguiApp::new();
+
+        app.handle_message(Message::ConnectionStateChanged(ConnectionState::Connecting));
+        assert!(!app.is_connected);
+        assert_eq!(app.connection_status(), "Connecting...");
+
+        app.handle_message(Message::ConnectionStateChanged(ConnectionState::Connected));
+        assert!(app.is_connected);
+        assert!(app.is_healthy());
+
+        let reconnecting = ConnectionState::Reconnecting {
+            attempt: 1,
+            next_retry_secs: 2,
+        };
+        app.handle_message(Message::ConnectionStateChanged(reconnecting));
+        assert!(!app.is_connected);
+        assert!(!app.is_healthy());
+        assert_eq!(app.connection_state, reconnecting);
+    }
+
     #[test]
     fn test_frame_counting() {
         let mut app = AguiApp::new();
@@ -122,4 +185,21 @@ mod tests {
         }
         assert_eq!(app.frame_count, 100);
     }
+
+    #[test]
+    fn test_negotiated_protocol() {
+        let mut app = AguiApp::new();
+        assert!(!app.supports("user_action"));
+
+        let mut protocol = NegotiatedProtocol::legacy();
+        protocol.capabilities.insert("user_action".to_string());
+        app.handle_message(Message::Negotiated(protocol));
+        app.handle_message(Message::ConnectionStateChanged(ConnectionState::Connected));
+        assert!(app.supports("user_action"));
+
+        // Capabilities are cleared when the connection drops
+        app.handle_message(Message::ConnectionStateChanged(ConnectionState::Disconnected));
+        assert!(app.protocol.is_none());
+        assert!(!app.supports("user_action"));
+    }
 }
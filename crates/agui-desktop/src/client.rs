@@ -0,0 +1,641 @@
+//! Orchestrator WebSocket client
+//!
+//! Dials the orchestrator at `AppConfig::orchestrator_url`, decodes
+//! `EventEnvelope` frames and reports connection state changes. Lost
+//! connections are retried with exponential backoff via `ReconnectStrategy`.
+//!
+//! Every connection starts with a HELLO/WELCOME handshake. A server that
+//! selects an incompatible major version is rejected without retrying;
+//! servers that never answer HELLO are treated as speaking the base protocol.
+//!
+//! Outbound events are queued while the connection is offline and flushed
+//! in order once it is re-established.
+
+use crate::protocol::{Event, EventEnvelope, Hello, NegotiatedProtocol, ProtocolError};
+use crate::resilience::{ConnectionState, ReconnectStrategy};
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message as WsMessage};
+
+/// How long to wait for WELCOME before assuming a pre-handshake server
+pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Events reported by the client to the UI
+#[derive(Debug, Clone)]
+pub enum ClientEvent {
+    /// Connection state changed
+    StateChanged(ConnectionState),
+    /// Protocol event received from the orchestrator
+    Event(Box<EventEnvelope>),
+    /// A frame could not be decoded
+    DecodeError(String),
+    /// Handshake completed (sent before `StateChanged(Connected)`)
+    Negotiated(NegotiatedProtocol),
+    /// Server selected an incompatible protocol version; the client gives up
+    HandshakeRejected(String),
+}
+
+/// WebSocket client for the orchestrator connection
+#[derive(Debug, Clone)]
+pub struct OrchestratorClient {
+    /// WebSocket URL to dial
+    url: String,
+    /// Backoff strategy for reconnects
+    strategy: ReconnectStrategy,
+    /// How long to wait for WELCOME
+    handshake_timeout: Duration,
+}
+
+impl OrchestratorClient {
+    /// Create a new client for the given URL
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            strategy: ReconnectStrategy::new(),
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
+        }
+    }
+
+    /// Use a custom reconnect strategy
+    pub fn with_strategy(mut self, strategy: ReconnectStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set how long to wait for WELCOME before assuming a pre-handshake server
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    /// Get the orchestrator URL
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Spawn the connection task on the given runtime
+    pub fn spawn(self, runtime: &tokio::runtime::Handle) -> ClientHandle {
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let (outbound_tx, outbound_rx) = mpsc::unbounded_channel();
+        let task = runtime.spawn(run_connection(self, event_tx, outbound_rx));
+
+        ClientHandle {
+            events: event_rx,
+            outbound: outbound_tx,
+            task,
+        }
+    }
+}
+
+/// Handle to a running client task
+///
+/// Dropping the handle stops the connection task.
+pub struct ClientHandle {
+    /// Events from the connection task
+    events: mpsc::UnboundedReceiver<ClientEvent>,
+    /// Events queued for the orchestrator
+    outbound: mpsc::UnboundedSender<EventEnvelope>,
+    /// Connection task
+    task: JoinHandle<()>,
+}
+
+impl ClientHandle {
+    /// Queue an event for the orchestrator
+    ///
+    /// Events sent while offline are buffered and delivered in order after
+    /// reconnecting. Returns false if the connection task has stopped.
+    pub fn send(&self, event: Event) -> bool {
+        self.send_envelope(EventEnvelope::new(event))
+    }
+
+    /// Queue a pre-built envelope for the orchestrator
+    pub fn send_envelope(&self, envelope: EventEnvelope) -> bool {
+        self.outbound.send(envelope).is_ok()
+    }
+
+    /// Try to receive a client event (non-blocking)
+    pub fn try_recv(&mut self) -> Option<ClientEvent> {
+        self.events.try_recv().ok()
+    }
+
+    /// Receive the next client event
+    pub async fn recv(&mut self) -> Option<ClientEvent> {
+        self.events.recv().await
+    }
+
+    /// Drain all pending client events (non-blocking)
+    pub fn drain(&mut self) -> Vec<ClientEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = self.events.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+
+    /// Check if the connection task has stopped
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Drop for ClientHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Decode a text frame into an event envelope
+pub fn decode_frame(text: &str) -> Result<EventEnvelope, ProtocolError> {
+    Ok(serde_json::from_str(text)?)
+}
+
+/// Encode an event envelope as a text frame
+pub fn encode_frame(envelope: &EventEnvelope) -> Result<String, ProtocolError> {
+    Ok(serde_json::to_string(envelope)?)
+}
+
+/// Result of the HELLO/WELCOME handshake
+enum Handshake {
+    /// Protocol agreed; carries the first frame if the server skipped WELCOME
+    Negotiated(NegotiatedProtocol, Option<ClientEvent>),
+    /// Server selected an incompatible version
+    Rejected(ProtocolError),
+    /// Connection dropped before the handshake finished
+    Lost,
+}
+
+/// Convert a received text frame into a client event
+fn frame_event(text: &str) -> ClientEvent {
+    match decode_frame(text) {
+        Ok(envelope) => ClientEvent::Event(Box::new(envelope)),
+        Err(e) => {
+            tracing::warn!("Failed to decode frame: {}", e);
+            ClientEvent::DecodeError(e.to_string())
+        }
+    }
+}
+
+/// Send HELLO and wait for the server's WELCOME
+async fn handshake<W, R>(write: &mut W, read: &mut R, timeout: Duration) -> Handshake
+where
+    W: Sink<WsMessage> + Unpin,
+    R: Stream<Item = Result<WsMessage, WsError>> + Unpin,
+{
+    let hello = Hello::new();
+    let frame = match encode_frame(&EventEnvelope::new(Event::Hello(hello.clone()))) {
+        Ok(frame) => frame,
+        Err(e) => return Handshake::Rejected(e),
+    };
+    if write.send(WsMessage::Text(frame)).await.is_err() {
+        return Handshake::Lost;
+    }
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        match tokio::time::timeout_at(deadline, read.next()).await {
+            // Pre-handshake servers stay silent until they have events to send
+            Err(_) => return Handshake::Negotiated(NegotiatedProtocol::legacy(), None),
+            Ok(Some(Ok(WsMessage::Text(text)))) => {
+                return match decode_frame(&text) {
+                    Ok(EventEnvelope {
+                        event: Event::Welcome(welcome),
+                        ..
+                    }) => match NegotiatedProtocol::negotiate(&hello, &welcome) {
+                        Ok(protocol) => Handshake::Negotiated(protocol, None),
+                        Err(e) => Handshake::Rejected(e),
+                    },
+                    // Pre-handshake server: keep the frame and assume the base protocol
+                    _ => Handshake::Negotiated(NegotiatedProtocol::legacy(), Some(frame_event(&text))),
+                };
+            }
+            Ok(Some(Ok(WsMessage::Close(_)))) | Ok(Some(Err(_))) | Ok(None) => {
+                return Handshake::Lost
+            }
+            Ok(Some(Ok(_))) => continue,
+        }
+    }
+}
+
+/// Write pending envelopes in order, stopping at the first failure
+///
+/// Envelopes are only removed once written, so anything left in `pending`
+/// is retried after the next reconnect.
+async fn flush_pending<S>(sink: &mut S, pending: &mut VecDeque<EventEnvelope>) -> bool
+where
+    S: Sink<WsMessage> + Unpin,
+{
+    while let Some(envelope) = pending.front() {
+        let frame = match encode_frame(envelope) {
+            Ok(frame) => frame,
+            Err(e) => {
+                tracing::error!("Dropping unencodable event {}: {}", envelope.id, e);
+                pending.pop_front();
+                continue;
+            }
+        };
+
+        if sink.send(WsMessage::Text(frame)).await.is_err() {
+            return false;
+        }
+        pending.pop_front();
+    }
+    true
+}
+
+/// Pump frames both ways until the connection drops
+///
+/// Returns false if the UI side has gone away and the task should stop.
+async fn run_session<W, R>(
+    write: &mut W,
+    read: &mut R,
+    pending: &mut VecDeque<EventEnvelope>,
+    events: &mpsc::UnboundedSender<ClientEvent>,
+    outbound: &mut mpsc::UnboundedReceiver<EventEnvelope>,
+) -> bool
+where
+    W: Sink<WsMessage> + Unpin,
+    R: Stream<Item = Result<WsMessage, WsError>> + Unpin,
+{
+    let mut connected = flush_pending(write, pending).await;
+
+    while connected {
+        tokio::select! {
+            frame = read.next() => {
+                let event = match frame {
+                    Some(Ok(WsMessage::Text(text))) => frame_event(&text),
+                    Some(Ok(WsMessage::Close(_))) | None => break,
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        tracing::warn!("WebSocket error: {}", e);
+                        break;
+                    }
+                };
+
+                if events.send(event).is_err() {
+                    return false;
+                }
+            }
+            Some(envelope) = outbound.recv() => {
+                pending.push_back(envelope);
+                connected = flush_pending(write, pending).await;
+            }
+        }
+    }
+
+    true
+}
+
+/// Connection loop: connect, handshake, pump frames, back off and retry
+async fn run_connection(
+    client: OrchestratorClient,
+    events: mpsc::UnboundedSender<ClientEvent>,
+    mut outbound: mpsc::UnboundedReceiver<EventEnvelope>,
+) {
+    let OrchestratorClient {
+        url,
+        mut strategy,
+        handshake_timeout,
+    } = client;
+    let _ = events.send(ClientEvent::StateChanged(ConnectionState::Connecting));
+
+    // Outbound events taken off the channel but not yet written
+    let mut pending = VecDeque::new();
+
+    loop {
+        match tokio_tungstenite::connect_async(url.as_str()).await {
+            Ok((socket, _response)) => {
+                let (mut write, mut read) = socket.split();
+
+                match handshake(&mut write, &mut read, handshake_timeout).await {
+                    Handshake::Negotiated(protocol, first_frame) => {
+                        tracing::info!(
+                            "Connected to orchestrator at {} (protocol {}{})",
+                            url,
+                            protocol.version,
+                            if protocol.legacy { ", no handshake" } else { "" }
+                        );
+                        strategy.reset();
+
+                        let announced = events.send(ClientEvent::Negotiated(protocol)).is_ok()
+                            && events
+                                .send(ClientEvent::StateChanged(ConnectionState::Connected))
+                                .is_ok()
+                            && first_frame.is_none_or(|event| events.send(event).is_ok());
+                        if !announced {
+                            return;
+                        }
+
+                        if !run_session(&mut write, &mut read, &mut pending, &events, &mut outbound)
+                            .await
+                        {
+                            return;
+                        }
+                        tracing::warn!("Connection to orchestrator lost");
+                    }
+                    Handshake::Rejected(e) => {
+                        tracing::error!("Orchestrator handshake rejected: {}", e);
+                        let _ = write.close().await;
+                        let _ = events.send(ClientEvent::HandshakeRejected(e.to_string()));
+                        let _ = events.send(ClientEvent::StateChanged(ConnectionState::Failed));
+                        return;
+                    }
+                    Handshake::Lost => {
+                        tracing::warn!("Connection to orchestrator lost during handshake");
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Failed to connect to {}: {}", url, e);
+            }
+        }
+
+        if strategy.is_exhausted() {
+            tracing::error!("Giving up on orchestrator after {} attempts", strategy.attempt());
+            let _ = events.send(ClientEvent::StateChanged(ConnectionState::Failed));
+            return;
+        }
+
+        strategy.record_attempt();
+        let delay = strategy.next_delay();
+        let state = ConnectionState::Reconnecting {
+            attempt: strategy.attempt(),
+            next_retry_secs: delay.as_secs_f64().ceil() as u32,
+        };
+        if events.send(ClientEvent::StateChanged(state)).is_err() {
+            return;
+        }
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{TextMessage, UserAction, Welcome, PROTOCOL_VERSION};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::WebSocketStream;
+
+    /// Accept a connection and read the client's HELLO
+    async fn accept_hello(listener: &TcpListener) -> (WebSocketStream<TcpStream>, Hello) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+        loop {
+            if let Some(Ok(WsMessage::Text(text))) = socket.next().await {
+                match decode_frame(&text).unwrap().event {
+                    Event::Hello(hello) => return (socket, hello),
+                    other => panic!("Expected HELLO, got {:?}", other),
+                }
+            }
+        }
+    }
+
+    /// Send a WELCOME selecting the given version
+    async fn send_welcome(socket: &mut WebSocketStream<TcpStream>, version: &str) {
+        let welcome = EventEnvelope::new(Event::Welcome(Welcome {
+            protocol_version: version.to_string(),
+            capabilities: vec!["user_action".to_string()],
+            server: None,
+        }));
+        socket
+            .send(WsMessage::Text(encode_frame(&welcome).unwrap()))
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_decode_frame() {
+        let envelope = EventEnvelope::new(Event::TextMessage(TextMessage {
+            sender: "agent".to_string(),
+            content: "Hello".to_string(),
+            metadata: None,
+        }));
+        let json = serde_json::to_string(&envelope).unwrap();
+
+        let decoded = decode_frame(&json).unwrap();
+        assert_eq!(decoded, envelope);
+
+        assert!(matches!(
+            decode_frame("{\"not\": \"an envelope\"}"),
+            Err(ProtocolError::JsonError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_client_receives_events() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let envelope = EventEnvelope::new(Event::TextMessage(TextMessage {
+            sender: "agent".to_string(),
+            content: "Hello".to_string(),
+            metadata: None,
+        }));
+        let frame = serde_json::to_string(&envelope).unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = accept_hello(&listener).await;
+            send_welcome(&mut socket, PROTOCOL_VERSION).await;
+            socket.send(WsMessage::Text("garbage".to_string())).await.unwrap();
+            socket.send(WsMessage::Text(frame)).await.unwrap();
+            // Keep the socket open until the client is done
+            let _ = socket.next().await;
+        });
+
+        let mut handle = OrchestratorClient::new(format!("ws://{}", addr))
+            .spawn(&tokio::runtime::Handle::current());
+
+        assert!(matches!(
+            handle.recv().await,
+            Some(ClientEvent::StateChanged(ConnectionState::Connecting))
+        ));
+        match handle.recv().await {
+            Some(ClientEvent::Negotiated(protocol)) => {
+                assert!(!protocol.legacy);
+                assert!(protocol.supports("user_action"));
+            }
+            other => panic!("Expected negotiated protocol, got {:?}", other),
+        }
+        assert!(matches!(
+            handle.recv().await,
+            Some(ClientEvent::StateChanged(ConnectionState::Connected))
+        ));
+        assert!(matches!(handle.recv().await, Some(ClientEvent::DecodeError(_))));
+        match handle.recv().await {
+            Some(ClientEvent::Event(received)) => assert_eq!(*received, envelope),
+            other => panic!("Expected event, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_gives_up_after_max_attempts() {
+        // Bind and drop a listener to get a port nobody is listening on
+        let addr = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap()
+        };
+
+        let strategy = ReconnectStrategy::new()
+            .with_base_delay(1)
+            .with_max_attempts(2);
+        let mut handle = OrchestratorClient::new(format!("ws://{}", addr))
+            .with_strategy(strategy)
+            .spawn(&tokio::runtime::Handle::current());
+
+        let mut states = Vec::new();
+        while let Some(ClientEvent::StateChanged(state)) = handle.recv().await {
+            states.push(state);
+        }
+
+        assert_eq!(states.first(), Some(&ConnectionState::Connecting));
+        assert_eq!(states.last(), Some(&ConnectionState::Failed));
+        assert_eq!(
+            states
+                .iter()
+                .filter(|s| matches!(s, ConnectionState::Reconnecting { .. }))
+                .count(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_outbound_buffered_until_connected() {
+        // Reserve a port, then leave it closed so the client starts offline
+        let addr = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap()
+        };
+
+        let strategy = ReconnectStrategy::new().with_base_delay(10).with_max_delay(50);
+        let mut handle = OrchestratorClient::new(format!("ws://{}", addr))
+            .with_strategy(strategy)
+            .spawn(&tokio::runtime::Handle::current());
+
+        // Wait until the first connect attempt has failed
+        while !matches!(
+            handle.recv().await,
+            Some(ClientEvent::StateChanged(ConnectionState::Reconnecting { .. }))
+        ) {}
+
+        for i in 0..3 {
+            assert!(handle.send(Event::UserAction(UserAction {
+                action_type: "click".to_string(),
+                component_id: format!("button-{}", i),
+                payload: serde_json::Value::Null,
+            })));
+        }
+
+        let listener = TcpListener::bind(addr).await.unwrap();
+        let (mut socket, _) = accept_hello(&listener).await;
+        send_welcome(&mut socket, PROTOCOL_VERSION).await;
+
+        let mut received = Vec::new();
+        while received.len() < 3 {
+            if let Some(Ok(WsMessage::Text(text))) = socket.next().await {
+                match decode_frame(&text).unwrap().event {
+                    Event::UserAction(action) => received.push(action.component_id),
+                    other => panic!("Expected user action, got {:?}", other),
+                }
+            }
+        }
+
+        assert_eq!(received, vec!["button-0", "button-1", "button-2"]);
+    }
+
+    #[tokio::test]
+    async fn test_handshake_rejects_major_mismatch() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = accept_hello(&listener).await;
+            send_welcome(&mut socket, "99.0.0").await;
+            let _ = socket.next().await;
+        });
+
+        let mut handle = OrchestratorClient::new(format!("ws://{}", addr))
+            .spawn(&tokio::runtime::Handle::current());
+
+        let mut events = Vec::new();
+        while let Some(event) = handle.recv().await {
+            events.push(event);
+        }
+
+        // Rejected without retrying
+        assert!(matches!(events[1], ClientEvent::HandshakeRejected(_)));
+        assert!(matches!(
+            events.last(),
+            Some(ClientEvent::StateChanged(ConnectionState::Failed))
+        ));
+        assert!(!events.iter().any(|e| matches!(
+            e,
+            ClientEvent::StateChanged(ConnectionState::Reconnecting { .. })
+                | ClientEvent::StateChanged(ConnectionState::Connected)
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_server_without_welcome() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let envelope = EventEnvelope::new(Event::TextMessage(TextMessage {
+            sender: "agent".to_string(),
+            content: "Hello".to_string(),
+            metadata: None,
+        }));
+        let frame = encode_frame(&envelope).unwrap();
+
+        tokio::spawn(async move {
+            // Ignore HELLO and start streaming events straight away
+            let (mut socket, _) = accept_hello(&listener).await;
+            socket.send(WsMessage::Text(frame)).await.unwrap();
+            let _ = socket.next().await;
+        });
+
+        let mut handle = OrchestratorClient::new(format!("ws://{}", addr))
+            .spawn(&tokio::runtime::Handle::current());
+
+        assert!(matches!(
+            handle.recv().await,
+            Some(ClientEvent::StateChanged(ConnectionState::Connecting))
+        ));
+        assert!(matches!(
+            handle.recv().await,
+            Some(ClientEvent::Negotiated(protocol)) if protocol.legacy
+        ));
+        assert!(matches!(
+            handle.recv().await,
+            Some(ClientEvent::StateChanged(ConnectionState::Connected))
+        ));
+        // The first frame is still delivered
+        match handle.recv().await {
+            Some(ClientEvent::Event(received)) => assert_eq!(*received, envelope),
+            other => panic!("Expected event, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handshake_timeout_assumes_legacy() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = accept_hello(&listener).await;
+            let _ = socket.next().await;
+        });
+
+        let mut handle = OrchestratorClient::new(format!("ws://{}", addr))
+            .with_handshake_timeout(Duration::from_millis(20))
+            .spawn(&tokio::runtime::Handle::current());
+
+        handle.recv().await;
+        assert!(matches!(
+            handle.recv().await,
+            Some(ClientEvent::Negotiated(protocol)) if protocol.legacy
+        ));
+    }
+}
@@ -16,6 +16,18 @@ pub struct AppConfig {
     pub project_root: PathBuf,
     /// WebSocket URL for orchestrator connection
     pub orchestrator_url: String,
+    /// Populate the stream and stage with demo content on startup
+    pub demo_mode: bool,
+    /// Directories file tools may access (defaults to the project root)
+    pub workspace_roots: Vec<PathBuf>,
+    /// Tool policy file (defaults to `tool-policy.json` in the user config directory)
+    pub tool_policy_path: Option<PathBuf>,
+    /// Plugin tool directory (defaults to `plugins` in the user config directory)
+    pub plugin_dir: Option<PathBuf>,
+    /// Tool calls that may run at once
+    pub max_tool_calls: usize,
+    /// Tool audit log file (defaults to `audit.jsonl` in the user state directory)
+    pub audit_log_path: Option<PathBuf>,
 }
 
 impl Default for AppConfig {
@@ -27,6 +39,12 @@ impl Default for AppConfig {
             enable_hot_reload: cfg!(debug_assertions),
             project_root: std::env::current_dir().unwrap_or_default(),
             orchestrator_url: "ws://localhost:8765".to_string(),
+            demo_mode: false,
+            workspace_roots: Vec::new(),
+            tool_policy_path: None,
+            plugin_dir: None,
+            max_tool_calls: crate::tools::DEFAULT_MAX_CONCURRENT_CALLS,
+            audit_log_path: None,
         }
     }
 }
@@ -61,6 +79,90 @@ impl AppConfig {
             config.orchestrator_url = orchestrator_url;
         }
 
+        if let Ok(demo_mode) = std::env::var("AGUI_DEMO") {
+            config.demo_mode = demo_mode.to_lowercase() == "true";
+        }
+
+        if let Some(roots) = std::env::var_os("AGUI_WORKSPACE_ROOTS") {
+            config.workspace_roots = std::env::split_paths(&roots).collect();
+        }
+
+        if let Ok(tool_policy_path) = std::env::var("AGUI_TOOL_POLICY") {
+            config.tool_policy_path = Some(PathBuf::from(tool_policy_path));
+        }
+
+        if let Ok(plugin_dir) = std::env::var("AGUI_PLUGIN_DIR") {
+            config.plugin_dir = Some(PathBuf::from(plugin_dir));
+        }
+
+        if let Ok(audit_log_path) = std::env::var("AGUI_AUDIT_LOG") {
+            config.audit_log_path = Some(PathBuf::from(audit_log_path));
+        }
+
+        if let Ok(max_tool_calls) = std::env::var("AGUI_MAX_TOOL_CALLS") {
+            if let Ok(max) = max_tool_calls.parse() {
+                config.max_tool_calls = max;
+            }
+        }
+
         config
     }
+
+    /// Get the directories file tools may access
+    pub fn workspace_roots(&self) -> Vec<PathBuf> {
+        if self.workspace_roots.is_empty() {
+            vec![self.project_root.clone()]
+        } else {
+            self.workspace_roots.clone()
+        }
+    }
+
+    /// Get the tool policy file path
+    pub fn tool_policy_path(&self) -> PathBuf {
+        self.tool_policy_path
+            .clone()
+            .unwrap_or_else(|| self.user_config_dir().join("tool-policy.json"))
+    }
+
+    /// Get the per-user directory for files tools must not edit
+    ///
+    /// Lives outside the workspace roots so the agent cannot rewrite its own
+    /// permissions or plant plugins that run at the next launch. Falls back
+    /// to `.agui` in the project root when the platform has no config
+    /// directory; the sandbox still protects it there.
+    pub fn user_config_dir(&self) -> PathBuf {
+        dirs::config_dir()
+            .map(|dir| dir.join("agui"))
+            .unwrap_or_else(|| self.project_root.join(".agui"))
+    }
+
+    /// Get the per-user directory for data the app writes, like the audit log
+    ///
+    /// Kept outside the workspace roots for the same reason as
+    /// `user_config_dir`, with the same fallback.
+    pub fn user_state_dir(&self) -> PathBuf {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("agui"))
+            .unwrap_or_else(|| self.project_root.join(".agui"))
+    }
+
+    /// Get the files and directories file tools may never touch
+    pub fn protected_paths(&self) -> Vec<PathBuf> {
+        vec![self.tool_policy_path(), self.plugin_dir(), self.audit_log_path()]
+    }
+
+    /// Get the plugin tool directory
+    pub fn plugin_dir(&self) -> PathBuf {
+        self.plugin_dir
+            .clone()
+            .unwrap_or_else(|| self.user_config_dir().join("plugins"))
+    }
+
+    /// Get the tool audit log path
+    pub fn audit_log_path(&self) -> PathBuf {
+        self.audit_log_path
+            .clone()
+            .unwrap_or_else(|| self.user_state_dir().join("audit.jsonl"))
+    }
 }
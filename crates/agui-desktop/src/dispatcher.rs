@@ -0,0 +1,869 @@
+//! Event Dispatcher
+//!
+//! Single reducer that applies protocol events to the UI models:
+//! - TEXT_MESSAGE / TEXT_MESSAGE_* → user/agent bubbles (streamed in place)
+//! - TOOL_CALL_* → tool-call cards
+//! - PLAN_CARD → plan checklists
+//! - ARTIFACT_* / STATE_DELTA → stage (rejected updates → error status lines)
+//! - ERROR → error status lines
+//!
+//! The dispatcher has no UI dependencies, so event logs can be replayed
+//! headlessly and the resulting state asserted on.
+
+use crate::protocol::{
+    CardStatus, ErrorEvent, Event, EventEnvelope, PlanCard, RenderRequest, ToolCallRequest,
+    ToolCallState,
+};
+use crate::stage::{ArtifactUpdateError, StageState};
+use crate::stream::{
+    AgentMessage, PlanBlock, PlanItem, PlanItemStatus, PlanStatus, ReasoningBlock, StatusBlock, StreamContent,
+    StreamItem, StreamState, ToolCallBlock, ToolCallStatus, UserMessage,
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Error code for ARTIFACT_UPDATE events the client could not apply
+pub const ARTIFACT_UPDATE_FAILED: &str = "ARTIFACT_UPDATE_FAILED";
+
+/// Result of dispatching a single event
+#[derive(Debug, Clone, PartialEq)]
+pub enum DispatchOutcome {
+    /// Event was applied to the stream or stage
+    Applied,
+    /// Event referenced an item that does not exist (unknown tool call, artifact, etc.)
+    Unmatched,
+    /// Event has no effect on the UI models
+    Ignored,
+    /// Render request that the caller must hand to the renderer
+    Render(RenderRequest),
+    /// Event could not be applied; the caller should report this ERROR to the orchestrator
+    Rejected(ErrorEvent),
+}
+
+impl DispatchOutcome {
+    fn from_applied(applied: bool) -> Self {
+        if applied {
+            DispatchOutcome::Applied
+        } else {
+            DispatchOutcome::Unmatched
+        }
+    }
+}
+
+/// Reducer that routes protocol events into `StreamState` and `StageState`
+#[derive(Debug, Clone, Default)]
+pub struct EventDispatcher {
+    /// Agent display names from the last AGENT_ROSTER
+    agent_names: HashMap<String, String>,
+}
+
+impl EventDispatcher {
+    /// Create a new dispatcher
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the display name of a known agent
+    pub fn agent_name(&self, agent_id: &str) -> Option<&str> {
+        self.agent_names.get(agent_id).map(String::as_str)
+    }
+
+    /// Apply an event envelope to the stream and stage
+    pub fn dispatch(
+        &mut self,
+        envelope: &EventEnvelope,
+        stream: &mut StreamState,
+        stage: &mut StageState,
+    ) -> DispatchOutcome {
+        let timestamp = chrono::DateTime::parse_from_rfc3339(&envelope.timestamp)
+            .map(|t| t.with_timezone(&chrono::Utc))
+            .unwrap_or_else(|_| chrono::Utc::now());
+        let new_item = |content: StreamContent| StreamItem {
+            timestamp,
+            ..StreamItem::new(envelope.id.clone(), content)
+        };
+
+        match &envelope.event {
+            Event::TextMessage(msg) => {
+                let content =
+                    self.text_message_content(&msg.sender, &msg.content, msg.metadata.as_ref(), false);
+                stream.push(new_item(content));
+                DispatchOutcome::Applied
+            }
+            Event::TextMessageStart(start) => {
+                // Keyed by message ID so deltas can find it
+                let content =
+                    self.text_message_content(&start.sender, "", start.metadata.as_ref(), true);
+                stream.push(StreamItem {
+                    timestamp,
+                    ..StreamItem::new(start.message_id.clone(), content)
+                });
+                DispatchOutcome::Applied
+            }
+            Event::TextMessageDelta(delta) => DispatchOutcome::from_applied(
+                stream.append_message_delta(&delta.message_id, &delta.delta),
+            ),
+            Event::TextMessageEnd(end) => {
+                DispatchOutcome::from_applied(stream.finish_message(&end.message_id))
+            }
+            Event::ReasoningStart(start) => {
+                // Reasoning is shown above the message it belongs to
+                let item = StreamItem {
+                    timestamp,
+                    ..StreamItem::new(
+                        start.reasoning_id.clone(),
+                        StreamContent::Reasoning(ReasoningBlock::new("").streaming()),
+                    )
+                };
+                match &start.message_id {
+                    Some(message_id) => stream.insert_before(message_id, item),
+                    None => stream.push(item),
+                }
+                DispatchOutcome::Applied
+            }
+            Event::ReasoningDelta(delta) => DispatchOutcome::from_applied(
+                stream.append_reasoning_delta(&delta.reasoning_id, &delta.delta),
+            ),
+            Event::ReasoningEnd(end) => {
+                if let Some(summary) = &end.summary {
+                    stream.set_reasoning_summary(&end.reasoning_id, summary.clone());
+                }
+                DispatchOutcome::from_applied(stream.finish_reasoning(&end.reasoning_id, timestamp))
+            }
+            Event::ReasoningSummary(summary) => DispatchOutcome::from_applied(
+                stream.set_reasoning_summary(&summary.reasoning_id, summary.summary.clone()),
+            ),
+            Event::ToolCallRequest(request) => {
+                stream.push(new_item(StreamContent::ToolCall(tool_call_block(request))));
+                DispatchOutcome::Applied
+            }
+            Event::ToolCallStatus(update) => {
+                DispatchOutcome::from_applied(stream.update_tool_call_status(
+                    &update.id,
+                    tool_call_status(&update.status),
+                    update.progress,
+                    update.message.as_deref(),
+                ))
+            }
+            Event::ToolCallOutput(output) => {
+                DispatchOutcome::from_applied(stream.append_tool_output(&output.id, &output.chunk))
+            }
+            Event::ToolCallResult(result) => {
+                DispatchOutcome::from_applied(stream.update_tool_call_result(
+                    &result.id,
+                    result.result.clone(),
+                    result.error.clone(),
+                ))
+            }
+            Event::PlanCard(card) => {
+                // Plan cards are keyed by card ID so re-sends update in place
+                let item_id = format!("plan-{}", card.id);
+                let content = StreamContent::Plan(plan_block(card));
+                if !stream.update(&item_id, content.clone()) {
+                    stream.push(StreamItem {
+                        timestamp,
+                        ..StreamItem::new(item_id, content)
+                    });
+                }
+                DispatchOutcome::Applied
+            }
+            Event::ArtifactOpen(open) => {
+                stage.open_artifact(open);
+                DispatchOutcome::Applied
+            }
+            Event::ArtifactUpdate(update) => match stage.update_artifact(update) {
+                Ok(()) => {
+                    if let Some(merge) = stage.merge(&update.id) {
+                        let title = stage.get_artifact(&update.id).map_or("", |a| a.title.as_str());
+                        let count = merge.conflict_count();
+                        stream.push(new_item(StreamContent::StatusUpdate(StatusBlock::warning(format!(
+                            "Update to {} conflicts with your unsaved edits: {} {} to resolve in the Stage",
+                            title,
+                            count,
+                            if count == 1 { "conflict" } else { "conflicts" }
+                        )))));
+                    }
+                    DispatchOutcome::Applied
+                }
+                Err(ArtifactUpdateError::UnknownArtifact(_)) => DispatchOutcome::Unmatched,
+                Err(e) => {
+                    // The current version lets the agent rebase and retry
+                    let version = stage.get_artifact(&update.id).map(|artifact| artifact.version);
+                    let error = ErrorEvent {
+                        code: ARTIFACT_UPDATE_FAILED.to_string(),
+                        message: e.to_string(),
+                        details: Some(json!({
+                            "id": update.id,
+                            "change_type": update.change_type,
+                            "version": version,
+                        })),
+                    };
+                    stream.push(new_item(StreamContent::StatusUpdate(error_status(&error))));
+                    DispatchOutcome::Rejected(error)
+                }
+            },
+            Event::StateDelta(delta) => {
+                DispatchOutcome::from_applied(stage.apply_state_delta(delta))
+            }
+            Event::Error(error) => {
+                stream.push(new_item(StreamContent::StatusUpdate(error_status(error))));
+                DispatchOutcome::Applied
+            }
+            Event::AgentRoster(roster) => {
+                self.agent_names = roster
+                    .agents
+                    .iter()
+                    .map(|agent| (agent.id.clone(), agent.name.clone()))
+                    .collect();
+                DispatchOutcome::Applied
+            }
+            Event::RenderRequest(request) => DispatchOutcome::Render(request.clone()),
+            // Handshake, client-originated, handled by the tool host, or not
+            // yet modelled in the UI
+            Event::Hello(_)
+            | Event::Welcome(_)
+            | Event::UserAction(_)
+            | Event::ToolCallCancel(_)
+            | Event::ToolManifest(_)
+            | Event::ArtifactSave(_)
+            | Event::ResourceTree(_)
+            | Event::ConnectionStatus(_) => DispatchOutcome::Ignored,
+        }
+    }
+
+    /// Apply a sequence of envelopes, returning render requests in order
+    pub fn replay<'a>(
+        &mut self,
+        envelopes: impl IntoIterator<Item = &'a EventEnvelope>,
+        stream: &mut StreamState,
+        stage: &mut StageState,
+    ) -> Vec<RenderRequest> {
+        envelopes
+            .into_iter()
+            .filter_map(|envelope| match self.dispatch(envelope, stream, stage) {
+                DispatchOutcome::Render(request) => Some(request),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Build the stream content for a text message
+    fn text_message_content(
+        &self,
+        sender: &str,
+        content: &str,
+        metadata: Option<&HashMap<String, String>>,
+        streaming: bool,
+    ) -> StreamContent {
+        let metadata = metadata.cloned().unwrap_or_default();
+        let name = metadata.get("name").cloned();
+
+        if is_user_message(sender, &metadata) {
+            StreamContent::UserMessage(UserMessage {
+                content: content.to_string(),
+                sender_name: name,
+                avatar: metadata.get("avatar").cloned(),
+                metadata,
+            })
+        } else {
+            let mut agent = AgentMessage::new(sender, content);
+            agent.agent_name = name.or_else(|| self.agent_name(sender).map(String::from));
+            agent.avatar = metadata.get("avatar").cloned();
+            agent.streaming = streaming;
+            StreamContent::AgentMessage(agent)
+        }
+    }
+}
+
+/// Check whether a text message was sent by the user
+///
+/// An explicit `role` metadata entry wins; otherwise senders named
+/// `user` or `user:<id>` / `user-<id>` are treated as the user.
+fn is_user_message(sender: &str, metadata: &HashMap<String, String>) -> bool {
+    if let Some(role) = metadata.get("role") {
+        return role == "user";
+    }
+    sender == "user"
+        || sender.starts_with("user:")
+        || sender.starts_with("user-")
+        || sender.starts_with("user_")
+}
+
+/// Build a pending tool-call block from a request
+fn tool_call_block(request: &ToolCallRequest) -> ToolCallBlock {
+    ToolCallBlock {
+        call_id: request.id.clone(),
+        tool_name: request.tool_name.clone(),
+        parameters: request.parameters.clone(),
+        status: ToolCallStatus::Pending,
+        result: None,
+        error: None,
+        duration_ms: None,
+        progress: None,
+        expanded: false,
+        output: String::new(),
+    }
+}
+
+/// Map a protocol tool-call state to the stream status
+fn tool_call_status(state: &ToolCallState) -> ToolCallStatus {
+    match state {
+        ToolCallState::Pending => ToolCallStatus::Pending,
+        ToolCallState::Running => ToolCallStatus::Running,
+        ToolCallState::Completed => ToolCallStatus::Completed,
+        ToolCallState::Failed => ToolCallStatus::Failed,
+        ToolCallState::Cancelled => ToolCallStatus::Cancelled,
+    }
+}
+
+/// Build a plan block from a plan card
+///
+/// Markdown list items in the card content become plan items. Task-list
+/// markers set the item status: `[x]` completed, `[~]` in progress,
+/// `[-]` skipped, `[ ]` pending.
+fn plan_block(card: &PlanCard) -> PlanBlock {
+    let items = card
+        .content
+        .lines()
+        .filter_map(parse_list_item)
+        .enumerate()
+        .map(|(i, (status, description))| PlanItem {
+            status,
+            ..PlanItem::new(format!("{}-{}", card.id, i + 1), description)
+        })
+        .collect();
+
+    PlanBlock {
+        title: card.title.clone(),
+        items,
+        status: match card.status {
+            CardStatus::Active => PlanStatus::Active,
+            CardStatus::Completed => PlanStatus::Completed,
+            CardStatus::Failed | CardStatus::Cancelled => PlanStatus::Cancelled,
+        },
+        editable: false,
+    }
+}
+
+/// Parse a markdown list line into a status and description
+fn parse_list_item(line: &str) -> Option<(PlanItemStatus, String)> {
+    let line = line.trim();
+    let rest = if let Some(rest) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        rest
+    } else {
+        // Ordered list: "1. step"
+        let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+        line[digits..].strip_prefix(". ").filter(|_| digits > 0)?
+    };
+
+    let (status, description) = match rest.get(..3) {
+        Some("[x]") | Some("[X]") => (PlanItemStatus::Completed, &rest[3..]),
+        Some("[~]") => (PlanItemStatus::InProgress, &rest[3..]),
+        Some("[-]") => (PlanItemStatus::Skipped, &rest[3..]),
+        Some("[ ]") => (PlanItemStatus::Pending, &rest[3..]),
+        _ => (PlanItemStatus::Pending, rest),
+    };
+
+    let description = description.trim();
+    if description.is_empty() {
+        None
+    } else {
+        Some((status, description.to_string()))
+    }
+}
+
+/// Build an error status line from an ERROR event
+fn error_status(error: &ErrorEvent) -> StatusBlock {
+    StatusBlock::error(format!("{}: {}", error.code, error.message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{
+        AgentInfo, AgentRoster, AgentStatus, ArtifactOpen, ArtifactUpdate, OutputStream,
+        ReasoningDelta,
+        ReasoningEnd, ReasoningStart, ReasoningSummary, StateDelta, TextMessage, TextMessageDelta, TextMessageEnd, TextMessageStart, ToolCallOutput, ToolCallResult,
+        ToolCallStatus as ProtocolToolCallStatus,
+    };
+    use crate::stream::StatusType;
+
+    fn text(sender: &str, content: &str) -> EventEnvelope {
+        EventEnvelope::new(Event::TextMessage(TextMessage {
+            sender: sender.to_string(),
+            content: content.to_string(),
+            metadata: None,
+        }))
+    }
+
+    #[test]
+    fn test_text_messages() {
+        let mut dispatcher = EventDispatcher::new();
+        let mut stream = StreamState::new();
+        let mut stage = StageState::new();
+
+        let mut role_override = text("alice", "Hi from metadata");
+        if let Event::TextMessage(msg) = &mut role_override.event {
+            msg.metadata = Some(HashMap::from([("role".to_string(), "user".to_string())]));
+        }
+
+        dispatcher.replay(
+            &[text("user", "Hello"), text("planner", "Hi there"), role_override],
+            &mut stream,
+            &mut stage,
+        );
+
+        let items = stream.items();
+        assert_eq!(items.len(), 3);
+        assert!(matches!(&items[0].content, StreamContent::UserMessage(m) if m.content == "Hello"));
+        assert!(matches!(&items[1].content, StreamContent::AgentMessage(m) if m.agent_id == "planner"));
+        assert!(matches!(&items[2].content, StreamContent::UserMessage(_)));
+    }
+
+    #[test]
+    fn test_streamed_text_message() {
+        let mut dispatcher = EventDispatcher::new();
+        let mut stream = StreamState::new();
+        let mut stage = StageState::new();
+
+        let delta = |text: &str| {
+            EventEnvelope::new(Event::TextMessageDelta(TextMessageDelta {
+                message_id: "m1".to_string(),
+                delta: text.to_string(),
+            }))
+        };
+
+        dispatcher.replay(
+            &[
+                EventEnvelope::new(Event::TextMessageStart(TextMessageStart {
+                    message_id: "m1".to_string(),
+                    sender: "coder".to_string(),
+                    metadata: None,
+                })),
+                delta("Hel"),
+                delta("lo"),
+            ],
+            &mut stream,
+            &mut stage,
+        );
+
+        match &stream.get("m1").unwrap().content {
+            StreamContent::AgentMessage(m) => {
+                assert_eq!(m.content, "Hello");
+                assert!(m.streaming);
+            }
+            other => panic!("Expected agent message, got {:?}", other),
+        }
+
+        let end = EventEnvelope::new(Event::TextMessageEnd(TextMessageEnd {
+            message_id: "m1".to_string(),
+        }));
+        assert_eq!(
+            dispatcher.dispatch(&end, &mut stream, &mut stage),
+            DispatchOutcome::Applied
+        );
+        assert!(matches!(
+            &stream.get("m1").unwrap().content,
+            StreamContent::AgentMessage(m) if !m.streaming
+        ));
+
+        let mut orphan = delta("x");
+        if let Event::TextMessageDelta(d) = &mut orphan.event {
+            d.message_id = "unknown".to_string();
+        }
+        assert_eq!(
+            dispatcher.dispatch(&orphan, &mut stream, &mut stage),
+            DispatchOutcome::Unmatched
+        );
+        assert_eq!(stream.len(), 1);
+    }
+
+    #[test]
+    fn test_streamed_reasoning() {
+        let mut dispatcher = EventDispatcher::new();
+        let mut stream = StreamState::new();
+        let mut stage = StageState::new();
+
+        let at = |event: Event, ms: i64| {
+            let mut envelope = EventEnvelope::new(event);
+            envelope.timestamp = format!("2026-01-09T12:00:{:02}.{:03}Z", ms / 1000, ms % 1000);
+            envelope
+        };
+
+        dispatcher.replay(
+            &[
+                at(
+                    Event::TextMessageStart(TextMessageStart {
+                        message_id: "m1".to_string(),
+                        sender: "coder".to_string(),
+                        metadata: None,
+                    }),
+                    0,
+                ),
+                at(
+                    Event::ReasoningStart(ReasoningStart {
+                        reasoning_id: "r1".to_string(),
+                        message_id: Some("m1".to_string()),
+                    }),
+                    100,
+                ),
+                at(
+                    Event::ReasoningDelta(ReasoningDelta {
+                        reasoning_id: "r1".to_string(),
+                        delta: "Check the ".to_string(),
+                    }),
+                    200,
+                ),
+                at(
+                    Event::ReasoningDelta(ReasoningDelta {
+                        reasoning_id: "r1".to_string(),
+                        delta: "lock order".to_string(),
+                    }),
+                    300,
+                ),
+                at(
+                    Event::ReasoningEnd(ReasoningEnd {
+                        reasoning_id: "r1".to_string(),
+                        summary: None,
+                    }),
+                    2350,
+                ),
+            ],
+            &mut stream,
+            &mut stage,
+        );
+
+        // Reasoning sits above its parent message
+        let ids: Vec<_> = stream.items().iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec!["r1", "m1"]);
+
+        let summary = at(
+            Event::ReasoningSummary(ReasoningSummary {
+                reasoning_id: "r1".to_string(),
+                summary: "Lock ordering".to_string(),
+            }),
+            5000,
+        );
+        assert_eq!(
+            dispatcher.dispatch(&summary, &mut stream, &mut stage),
+            DispatchOutcome::Applied
+        );
+
+        match &stream.get("r1").unwrap().content {
+            StreamContent::Reasoning(r) => {
+                assert_eq!(r.content, "Check the lock order");
+                assert_eq!(r.duration_ms, Some(2250));
+                assert_eq!(r.summary.as_deref(), Some("Lock ordering"));
+                assert!(!r.streaming);
+            }
+            other => panic!("Expected reasoning, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_agent_names_from_roster() {
+        let mut dispatcher = EventDispatcher::new();
+        let mut stream = StreamState::new();
+        let mut stage = StageState::new();
+
+        let roster = EventEnvelope::new(Event::AgentRoster(AgentRoster {
+            agents: vec![AgentInfo {
+                id: "planner".to_string(),
+                name: "Planner".to_string(),
+                status: AgentStatus::Online,
+                metadata: None,
+            }],
+        }));
+        dispatcher.replay(&[roster, text("planner", "Ready")], &mut stream, &mut stage);
+
+        match &stream.items()[0].content {
+            StreamContent::AgentMessage(m) => assert_eq!(m.agent_name.as_deref(), Some("Planner")),
+            other => panic!("Expected agent message, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tool_call_lifecycle() {
+        let mut dispatcher = EventDispatcher::new();
+        let mut stream = StreamState::new();
+        let mut stage = StageState::new();
+
+        let events = [
+            EventEnvelope::new(Event::ToolCallRequest(ToolCallRequest {
+                id: "tc_1".to_string(),
+                tool_name: "grep".to_string(),
+                parameters: serde_json::json!({"pattern": "fn"}),
+                agent_id: "coder".to_string(),
+            })),
+            EventEnvelope::new(Event::ToolCallStatus(ProtocolToolCallStatus {
+                id: "tc_1".to_string(),
+                status: ToolCallState::Running,
+                progress: Some(50),
+                message: None,
+            })),
+            EventEnvelope::new(Event::ToolCallOutput(ToolCallOutput {
+                id: "tc_1".to_string(),
+                stream: OutputStream::Stdout,
+                chunk: "src/main.rs: fn main\n".to_string(),
+            })),
+            EventEnvelope::new(Event::ToolCallOutput(ToolCallOutput {
+                id: "tc_1".to_string(),
+                stream: OutputStream::Stderr,
+                chunk: "warning: binary file skipped\n".to_string(),
+            })),
+        ];
+        dispatcher.replay(&events, &mut stream, &mut stage);
+
+        match &stream.items()[0].content {
+            StreamContent::ToolCall(tc) => {
+                assert_eq!(tc.status, ToolCallStatus::Running);
+                assert_eq!(tc.progress, Some(50));
+                assert_eq!(
+                    tc.output_tail(5),
+                    vec!["src/main.rs: fn main", "warning: binary file skipped"]
+                );
+            }
+            other => panic!("Expected tool call, got {:?}", other),
+        }
+
+        let result = EventEnvelope::new(Event::ToolCallResult(ToolCallResult {
+            id: "tc_1".to_string(),
+            result: serde_json::json!({"matches": 3}),
+            error: None,
+        }));
+        assert_eq!(
+            dispatcher.dispatch(&result, &mut stream, &mut stage),
+            DispatchOutcome::Applied
+        );
+        match &stream.items()[0].content {
+            StreamContent::ToolCall(tc) => {
+                assert_eq!(tc.status, ToolCallStatus::Completed);
+                assert_eq!(tc.result, Some(serde_json::json!({"matches": 3})));
+            }
+            other => panic!("Expected tool call, got {:?}", other),
+        }
+
+        let unknown = EventEnvelope::new(Event::ToolCallResult(ToolCallResult {
+            id: "tc_missing".to_string(),
+            result: serde_json::Value::Null,
+            error: None,
+        }));
+        assert_eq!(
+            dispatcher.dispatch(&unknown, &mut stream, &mut stage),
+            DispatchOutcome::Unmatched
+        );
+    }
+
+    #[test]
+    fn test_plan_card() {
+        let mut dispatcher = EventDispatcher::new();
+        let mut stream = StreamState::new();
+        let mut stage = StageState::new();
+
+        let card = |content: &str, status: CardStatus| {
+            EventEnvelope::new(Event::PlanCard(PlanCard {
+                id: "p1".to_string(),
+                title: "Refactor".to_string(),
+                content: content.to_string(),
+                status,
+            }))
+        };
+
+        dispatcher.replay(
+            &[
+                card("Steps:\n- [x] Read code\n- [~] Edit\n1. Test", CardStatus::Active),
+                card("- [x] Read code\n- [x] Edit\n- [x] Test", CardStatus::Completed),
+            ],
+            &mut stream,
+            &mut stage,
+        );
+
+        // Second card updates the first in place
+        assert_eq!(stream.len(), 1);
+        match &stream.get("plan-p1").unwrap().content {
+            StreamContent::Plan(plan) => {
+                assert_eq!(plan.items.len(), 3);
+                assert_eq!(plan.status, PlanStatus::Completed);
+                assert_eq!(plan.completion_percentage(), 100);
+            }
+            other => panic!("Expected plan, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_list_item() {
+        assert_eq!(
+            parse_list_item("- [~] Edit"),
+            Some((PlanItemStatus::InProgress, "Edit".to_string()))
+        );
+        assert_eq!(
+            parse_list_item("12. Deploy"),
+            Some((PlanItemStatus::Pending, "Deploy".to_string()))
+        );
+        assert_eq!(parse_list_item("Plain text"), None);
+        assert_eq!(parse_list_item("- [ ]"), None);
+    }
+
+    #[test]
+    fn test_artifacts_and_state_delta() {
+        let mut dispatcher = EventDispatcher::new();
+        let mut stream = StreamState::new();
+        let mut stage = StageState::new();
+
+        dispatcher.replay(
+            &[
+                EventEnvelope::new(Event::ArtifactOpen(ArtifactOpen {
+                    id: "a1".to_string(),
+                    title: "main.rs".to_string(),
+                    content: "fn main() {}".to_string(),
+                    content_type: "code".to_string(),
+                    read_only: false,
+                    language: Some("rust".to_string()),
+                })),
+                EventEnvelope::new(Event::ArtifactUpdate(ArtifactUpdate {
+                    id: "a1".to_string(),
+                    content: "fn main() { run(); }".to_string(),
+                    change_type: "full_replace".to_string(),
+                    base_version: None,
+                    edits: Vec::new(),
+                })),
+                EventEnvelope::new(Event::StateDelta(StateDelta {
+                    path: "artifact.a1.title".to_string(),
+                    old_value: None,
+                    new_value: serde_json::json!("lib.rs"),
+                })),
+            ],
+            &mut stream,
+            &mut stage,
+        );
+
+        let artifact = stage.get_artifact("a1").unwrap();
+        assert_eq!(artifact.title, "lib.rs");
+        assert_eq!(artifact.content_str(), "fn main() { run(); }");
+        assert!(stream.is_empty());
+    }
+
+    #[test]
+    fn test_rejected_artifact_update() {
+        let mut dispatcher = EventDispatcher::new();
+        let mut stream = StreamState::new();
+        let mut stage = StageState::new();
+        stage.open_artifact(&ArtifactOpen {
+            id: "a1".to_string(),
+            title: "notes.txt".to_string(),
+            content: "one\ntwo\n".to_string(),
+            content_type: "text".to_string(),
+            read_only: false,
+            language: None,
+        });
+
+        let outcome = dispatcher.dispatch(
+            &EventEnvelope::new(Event::ArtifactUpdate(ArtifactUpdate {
+                id: "a1".to_string(),
+                content: "@@ -1 +1 @@\n-three\n+3\n".to_string(),
+                change_type: "diff".to_string(),
+                base_version: None,
+                edits: Vec::new(),
+            })),
+            &mut stream,
+            &mut stage,
+        );
+
+        let DispatchOutcome::Rejected(error) = outcome else {
+            panic!("Expected rejection, got {:?}", outcome);
+        };
+        assert_eq!(error.code, ARTIFACT_UPDATE_FAILED);
+        let details = error.details.unwrap();
+        assert_eq!(details["id"], "a1");
+        assert_eq!(details["version"], 0);
+        assert_eq!(stage.get_artifact("a1").unwrap().content_str(), "one\ntwo\n");
+        assert!(matches!(
+            &stream.items()[0].content,
+            StreamContent::StatusUpdate(s) if s.status_type == StatusType::Error
+        ));
+    }
+
+    #[test]
+    fn test_conflicting_artifact_update() {
+        let mut dispatcher = EventDispatcher::new();
+        let mut stream = StreamState::new();
+        let mut stage = StageState::new();
+        stage.open_artifact(&ArtifactOpen {
+            id: "a1".to_string(),
+            title: "notes.txt".to_string(),
+            content: "one\n".to_string(),
+            content_type: "text".to_string(),
+            read_only: false,
+            language: None,
+        });
+        stage.edit_active(&crate::stage::EditCommand::Insert("my ".to_string()));
+
+        let outcome = dispatcher.dispatch(
+            &EventEnvelope::new(Event::ArtifactUpdate(ArtifactUpdate {
+                id: "a1".to_string(),
+                content: "their one\n".to_string(),
+                change_type: "full_replace".to_string(),
+                base_version: None,
+                edits: Vec::new(),
+            })),
+            &mut stream,
+            &mut stage,
+        );
+
+        assert_eq!(outcome, DispatchOutcome::Applied);
+        assert_eq!(stage.merge("a1").unwrap().conflict_count(), 1);
+        assert!(matches!(
+            &stream.items()[0].content,
+            StreamContent::StatusUpdate(s) if s.status_type == StatusType::Warning
+                && s.message.ends_with("1 conflict to resolve in the Stage")
+        ));
+    }
+
+    #[test]
+    fn test_error_and_render_request() {
+        let mut dispatcher = EventDispatcher::new();
+        let mut stream = StreamState::new();
+        let mut stage = StageState::new();
+
+        let renders = dispatcher.replay(
+            &[
+                EventEnvelope::new(Event::Error(ErrorEvent {
+                    code: "TOOL_TIMEOUT".to_string(),
+                    message: "grep timed out".to_string(),
+                    details: None,
+                })),
+                EventEnvelope::new(Event::RenderRequest(RenderRequest {
+                    component_id: "form-1".to_string(),
+                    schema: serde_json::json!({"type": "text", "content": "Hi"}),
+                    props: None,
+                })),
+            ],
+            &mut stream,
+            &mut stage,
+        );
+
+        assert_eq!(renders.len(), 1);
+        assert_eq!(renders[0].component_id, "form-1");
+        match &stream.items()[0].content {
+            StreamContent::StatusUpdate(status) => {
+                assert_eq!(status.status_type, StatusType::Error);
+                assert_eq!(status.message, "TOOL_TIMEOUT: grep timed out");
+            }
+            other => panic!("Expected status, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_item_timestamp_from_envelope() {
+        let mut dispatcher = EventDispatcher::new();
+        let mut stream = StreamState::new();
+        let mut stage = StageState::new();
+
+        let mut envelope = text("agent", "Hello");
+        envelope.timestamp = "2026-01-09T12:00:00.000Z".to_string();
+        dispatcher.dispatch(&envelope, &mut stream, &mut stage);
+
+        let item = stream.get(&envelope.id).unwrap();
+        assert_eq!(item.timestamp.to_rfc3339(), "2026-01-09T12:00:00+00:00");
+    }
+}
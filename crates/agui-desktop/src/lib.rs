@@ -4,7 +4,9 @@
 //! Built on GPUI for GPU-accelerated rendering and hot-reload support.
 
 pub mod app;
+pub mod client;
 pub mod config;
+pub mod dispatcher;
 pub mod hot_reload;
 pub mod layout;
 pub mod logging;
@@ -15,9 +17,12 @@ pub mod renderer;
 pub mod resilience;
 pub mod stage;
 pub mod stream;
+pub mod tools;
 
 pub use app::AguiApp;
+pub use client::{ClientEvent, ClientHandle, OrchestratorClient};
 pub use config::AppConfig;
+pub use dispatcher::{DispatchOutcome, EventDispatcher};
 pub use layout::{LayoutState, Pane};
 pub use mock_server::{MockServer, MockServerConfig};
 pub use protocol::Event;
@@ -38,6 +43,7 @@ pub use resilience::{
     ConnectionState, ReconnectStrategy, UpdateBatcher,
     ErrorSeverity, AppError, ErrorManager, SessionCompactor,
 };
+pub use tools::{create_standard_registry, Tool, ToolHost, ToolRegistry};
 pub use metrics::{
     PerformanceMode, PerformanceMetrics, MetricsSnapshot, get_metrics,
 };
@@ -1,15 +1,26 @@
 //! AGUI Desktop - Main entry point
 
 use agui_desktop::{
-    app::AguiApp,
+    app::{AguiApp, Message},
+    client::{ClientEvent, ClientHandle, OrchestratorClient},
     config::AppConfig,
+    dispatcher::{DispatchOutcome, EventDispatcher},
     hot_reload::HotReloadWatcher,
     layout::{LayoutState, Pane},
     logging, metrics, VERSION,
-    protocol::ArtifactOpen,
-    renderer::{parse_component, render_component, Component, FormAction, FormState, RenderContext},
+    protocol::{ArtifactOpen, ArtifactUpdate, Event, EventEnvelope},
+    tools::{
+        create_sandboxed_registry, file_ops::read_result_artifact,
+        manifest::{form_parameters, import_manifest, parameter_form, INVOKE_TOOL_ACTION},
+        register_plugins, shell::RunCommand, AuditLog, Sandbox, Submission, TerminalEvent, TerminalManager, ToolHost,
+        ToolMetadata, ToolPolicy, ToolRegistry,
+    },
+    renderer::{
+        parse_component, render_component, ActionCallback, Component, FormAction, FormState,
+        FormValue, RenderContext,
+    },
     stage::{
-        StageState, render_stage_pane, render_artifact_status_bar,
+        EditCommand, OnResolveConflict, StageState, render_stage_pane, render_artifact_status_bar,
         components::tabs::TabItem,
     },
     stream::{
@@ -17,10 +28,12 @@ use agui_desktop::{
         UserMessage, AgentMessage, ReasoningBlock, ToolCallBlock, ToolCallStatus,
         PlanBlock, PlanItem, PlanItemStatus, ApprovalBlock, ApprovalAction,
         ApprovalActionVariant, StatusBlock,
-        components::render_stream_timeline,
+        components::{render_output_log, render_result_view, render_stream_timeline},
     },
 };
+use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
 use gpui::{
     actions, div, prelude::*, px, rgb, rgba, size, App, Application, Bounds, Context, KeyBinding,
     Window, WindowBounds, WindowOptions,
@@ -41,9 +54,14 @@ actions!(
     ]
 );
 
+/// How often the window drains events from the orchestrator client
+const CLIENT_POLL_INTERVAL: Duration = Duration::from_millis(16);
+
 /// Main AGUI window view
 struct AguiWindow {
     app: AguiApp,
+    /// Connection to the orchestrator
+    client: ClientHandle,
     layout: LayoutState,
     /// Form state for dynamic UI components
     form_state: FormState,
@@ -53,26 +71,226 @@ struct AguiWindow {
     stream_timeline: StreamTimeline,
     /// Stage state for artifact workspace
     stage_state: StageState,
+    /// Applies orchestrator events to the stream and stage
+    dispatcher: EventDispatcher,
+    /// Executes tool calls requested by agents
+    tool_host: ToolHost,
+    /// Interactive terminal sessions shown in the Stage
+    terminals: Arc<TerminalManager>,
+    /// Tools declared by the orchestrator, shown as forms in the Context Rail
+    remote_tools: Vec<ToolMetadata>,
 }
 
 impl AguiWindow {
-    fn new() -> Self {
+    fn new(
+        client: ClientHandle,
+        tool_host: ToolHost,
+        terminals: Arc<TerminalManager>,
+        demo_mode: bool,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self::start_client_polling(cx);
+
         let mut window = Self {
             app: AguiApp::new(),
+            client,
             layout: LayoutState::new(),
             form_state: FormState::new(),
             rendered_component: None,
             stream_timeline: StreamTimeline::new(),
             stage_state: StageState::new(),
+            dispatcher: EventDispatcher::new(),
+            tool_host,
+            terminals,
+            remote_tools: Vec::new(),
         };
 
-        // Add demo items to showcase the stream timeline
-        window.add_demo_items();
-        // Add demo artifacts to showcase the stage
-        window.add_demo_artifacts();
+        if demo_mode {
+            // Add demo items to showcase the stream timeline
+            window.add_demo_items();
+            // Add demo artifacts to showcase the stage
+            window.add_demo_artifacts();
+        }
         window
     }
 
+    /// Periodically drain orchestrator client events on the UI thread
+    fn start_client_polling(cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| loop {
+            cx.background_executor().timer(CLIENT_POLL_INTERVAL).await;
+            let updated = this.update(cx, |this, cx| {
+                if this.poll_client() {
+                    cx.notify();
+                }
+            });
+            if updated.is_err() {
+                break;
+            }
+        })
+        .detach();
+    }
+
+    /// Send the user's edits to the active artifact back to the orchestrator
+    fn save_active_artifact(&mut self) {
+        let Some(id) = self.stage_state.active_artifact().map(|a| a.id.clone()) else {
+            return;
+        };
+        if !self.app.supports("artifact_save") {
+            tracing::warn!("Orchestrator does not accept artifact saves; keeping edits to {}", id);
+            return;
+        }
+        if let Some(save) = self.stage_state.save_artifact(&id) {
+            self.client.send(Event::ArtifactSave(save));
+        }
+    }
+
+    /// Apply pending client and tool events, returning whether anything changed
+    fn poll_client(&mut self) -> bool {
+        let events = self.client.drain();
+        let tool_events = self.tool_host.drain();
+        let terminal_events = self.terminals.drain_events();
+        let changed =
+            !events.is_empty() || !tool_events.is_empty() || !terminal_events.is_empty();
+
+        for event in events {
+            match event {
+                ClientEvent::StateChanged(state) => {
+                    self.app.handle_message(Message::ConnectionStateChanged(state));
+                }
+                ClientEvent::Event(envelope) => self.dispatch_event(&envelope),
+                ClientEvent::DecodeError(error) => {
+                    tracing::warn!("Dropped undecodable frame: {}", error);
+                }
+                ClientEvent::Negotiated(protocol) => {
+                    // Advertise the local tools so the orchestrator can call them
+                    if protocol.supports("tool_manifest") {
+                        self.client
+                            .send(Event::ToolManifest(self.tool_host.registry().manifest()));
+                    }
+                    self.app.handle_message(Message::Negotiated(protocol));
+                }
+                ClientEvent::HandshakeRejected(reason) => {
+                    self.app
+                        .handle_message(Message::Error(format!("Incompatible orchestrator: {}", reason)));
+                }
+            }
+        }
+
+        // Report tool progress to the orchestrator and mirror it locally
+        for event in tool_events {
+            self.client.send(event.clone());
+            self.dispatch_event(&EventEnvelope::new(event));
+        }
+
+        for event in terminal_events {
+            self.apply_terminal_event(event);
+        }
+
+        changed
+    }
+
+    /// Mirror a terminal session in its Stage artifact
+    fn apply_terminal_event(&mut self, event: TerminalEvent) {
+        match event {
+            TerminalEvent::Opened {
+                session_id,
+                title,
+                rows,
+                cols,
+            } => {
+                self.stage_state.open_artifact(&ArtifactOpen {
+                    id: session_id.clone(),
+                    title,
+                    content: String::new(),
+                    content_type: "terminal".to_string(),
+                    read_only: true,
+                    language: None,
+                });
+                self.set_terminal_size(&session_id, rows, cols);
+            }
+            TerminalEvent::Output { session_id, data } => {
+                self.append_terminal_output(session_id, data);
+            }
+            TerminalEvent::Resized {
+                session_id,
+                rows,
+                cols,
+            } => self.set_terminal_size(&session_id, rows, cols),
+            TerminalEvent::Exited {
+                session_id,
+                exit_code,
+            } => {
+                let status = exit_code.map_or("[Process exited]".to_string(), |code| {
+                    format!("[Process exited with code {}]", code)
+                });
+                self.append_terminal_output(session_id, format!("\r\n\x1b[2m{}\x1b[0m\r\n", status));
+            }
+        }
+    }
+
+    fn append_terminal_output(&mut self, session_id: String, data: String) {
+        // The user may have closed the session's tab
+        if self.stage_state.get_artifact(&session_id).is_some() {
+            if let Err(e) = self.stage_state.update_artifact(&ArtifactUpdate {
+                id: session_id,
+                content: data,
+                change_type: "append".to_string(),
+                base_version: None,
+                edits: Vec::new(),
+            }) {
+                tracing::warn!("Failed to append terminal output: {}", e);
+            }
+        }
+    }
+
+    fn set_terminal_size(&mut self, session_id: &str, rows: u16, cols: u16) {
+        if let Some(artifact) = self.stage_state.get_artifact_mut(session_id) {
+            artifact.metadata.insert("rows".to_string(), rows.to_string());
+            artifact.metadata.insert("cols".to_string(), cols.to_string());
+        }
+    }
+
+    /// Apply an orchestrator event to the stream and stage
+    fn dispatch_event(&mut self, envelope: &EventEnvelope) {
+        let outcome = self.dispatcher.dispatch(
+            envelope,
+            &mut self.stream_timeline.state,
+            &mut self.stage_state,
+        );
+
+        // Tool calls run locally, subject to the tool policy
+        match &envelope.event {
+            Event::ToolCallRequest(request) => {
+                if let Submission::NeedsApproval(gate) = self.tool_host.submit(request) {
+                    self.stream_timeline.push(*gate);
+                }
+            }
+            Event::ToolCallCancel(cancel) => self.cancel_tool_call(&cancel.id),
+            Event::ToolManifest(manifest) => match import_manifest(manifest) {
+                Ok(tools) => self.remote_tools = tools,
+                Err(e) => tracing::warn!("Invalid tool manifest: {}", e),
+            },
+            _ => {}
+        }
+
+        match outcome {
+            DispatchOutcome::Render(request) => {
+                if let Err(e) = self.handle_render_request(&request.schema) {
+                    tracing::warn!("Invalid render request {}: {}", request.component_id, e);
+                }
+            }
+            DispatchOutcome::Unmatched => {
+                tracing::debug!("Event {} did not match any item", envelope.id);
+            }
+            DispatchOutcome::Rejected(error) => {
+                // Let the agent resend, e.g. the full content instead of a stale diff
+                tracing::warn!("Rejected event {}: {}", envelope.id, error.message);
+                self.client.send(Event::Error(error));
+            }
+            DispatchOutcome::Applied | DispatchOutcome::Ignored => {}
+        }
+    }
+
     /// Add demo artifacts to the stage for testing
     fn add_demo_artifacts(&mut self) {
         // Add a Rust code artifact
@@ -193,6 +411,7 @@ tokio = { version = "1.35", features = ["full"] }
                 duration_ms: Some(234),
                 progress: None,
                 expanded: false,
+                output: String::new(),
             }),
         ));
 
@@ -304,10 +523,72 @@ tokio = { version = "1.35", features = ["full"] }
     }
 
     /// Handle a form action from rendered components
-    fn handle_form_action(&mut self, action: FormAction) {
-        tracing::info!("Form action: {:?}", action);
-        // In a real implementation, this would send a UserAction event to the orchestrator
-        // For now, just log the action
+    fn handle_form_action(&mut self, mut action: FormAction) {
+        tracing::debug!("Form action: {:?}", action);
+
+        // Remote tool forms submit typed parameters for the named tool
+        if let Some(name) = action.action_type.strip_prefix(&format!("{}:", INVOKE_TOOL_ACTION)) {
+            let Some(tool) = self.remote_tools.iter().find(|t| t.name == name) else {
+                tracing::warn!("Form submitted for unknown remote tool {}", name);
+                return;
+            };
+            let values = action.payload.as_object().cloned().unwrap_or_default();
+            action.payload = serde_json::json!({
+                "tool_name": tool.name,
+                "parameters": form_parameters(tool, &values),
+            });
+            action.action_type = INVOKE_TOOL_ACTION.to_string();
+        }
+
+        // Keep local form state in sync so inputs reflect the new value
+        if action.action_type == "value_change" {
+            if let Ok(value) = serde_json::from_value::<FormValue>(action.payload.clone()) {
+                self.form_state.set_value(action.component_id.clone(), value);
+            }
+        }
+
+        if !self.client.send(Event::UserAction(action.into())) {
+            tracing::warn!("Orchestrator client stopped; dropping form action");
+        }
+    }
+
+    /// Cancel a tool call from its card or at the orchestrator's request
+    fn cancel_tool_call(&mut self, call_id: &str) {
+        if !self.tool_host.cancel(call_id) {
+            return;
+        }
+
+        tracing::info!("Tool call {} cancelled", call_id);
+        // A call still waiting for approval closes its gate
+        self.stream_timeline
+            .state
+            .resolve_approval(&ToolHost::approval_item_id(call_id), "deny");
+    }
+
+    /// Open a `read_file` result in the Stage
+    fn open_read_result(&mut self, call_id: &str, result: &serde_json::Value) {
+        if let Some(open) = read_result_artifact(call_id, result) {
+            self.stage_state.open_artifact(&open);
+        }
+    }
+
+    /// Handle a click on an approval gate action
+    fn handle_approval_action(&mut self, item_id: &str, action: &ApprovalAction) {
+        if !self.stream_timeline.state.resolve_approval(item_id, &action.id) {
+            return;
+        }
+
+        tracing::info!("Approval {} resolved with {}", item_id, action.id);
+        // Tool approvals are handled locally rather than by the orchestrator
+        if self.tool_host.resolve_approval(item_id, &action.id) {
+            return;
+        }
+        if !self
+            .client
+            .send(Event::UserAction(action.to_user_action(item_id)))
+        {
+            tracing::warn!("Orchestrator client stopped; dropping approval action");
+        }
     }
 }
 
@@ -434,13 +715,26 @@ impl Render for AguiWindow {
                                 .child("Ctrl+1"),
                         ),
                 )
-                .child(
+                .child(if self.remote_tools.is_empty() {
                     div()
                         .flex_1()
                         .text_color(text_secondary)
                         .text_xs()
-                        .child("Sessions, history, and context will appear here."),
-                )
+                        .child("Sessions, history, and context will appear here.")
+                } else {
+                    // Parameter forms for tools declared by the orchestrator
+                    let on_action: ActionCallback<Self> =
+                        Arc::new(|this: &mut Self, _window, cx, action| {
+                            this.handle_form_action(action);
+                            cx.notify();
+                        });
+                    let ctx = RenderContext::new(&self.form_state, on_action);
+                    let mut tools = div().flex_1().flex().flex_col().gap_3();
+                    for tool in &self.remote_tools {
+                        tools = tools.child(render_component(&parameter_form(tool), &ctx, cx));
+                    }
+                    tools
+                })
                 .on_mouse_down(gpui::MouseButton::Left, cx.listener(|this, _, _, _cx| {
                     this.layout.focus_pane(Pane::ContextRail);
                 }))
@@ -452,6 +746,22 @@ impl Render for AguiWindow {
         let stream_empty = stream_items.is_empty();
         let selected_id = self.stream_timeline.state.selected().cloned();
 
+        // Dynamic UI from the last RENDER_REQUEST
+        let rendered_panel = self.rendered_component.as_ref().map(|component| {
+            let on_action: ActionCallback<Self> =
+                Arc::new(|this: &mut Self, _window, cx, action| {
+                    this.handle_form_action(action);
+                    cx.notify();
+                });
+            let ctx = RenderContext::new(&self.form_state, on_action);
+            div()
+                .px_3()
+                .py_2()
+                .border_b_1()
+                .border_color(border_color)
+                .child(render_component(component, &ctx, cx))
+        });
+
         let stream = div()
             .flex()
             .flex_col()
@@ -511,6 +821,7 @@ impl Render for AguiWindow {
                             .child("Ctrl+2"),
                     ),
             )
+            .children(rendered_panel)
             // Timeline content
             .child(
                 div()
@@ -584,6 +895,16 @@ impl Render for AguiWindow {
                     this.layout.toggle_stage();
                 }))
         } else {
+            // Conflict actions resolve against this window's stage
+            let view = cx.entity().downgrade();
+            let on_resolve: OnResolveConflict = Rc::new(move |id, index, resolution, _window, cx| {
+                let _ = view.update(cx, |this, cx| {
+                    if this.stage_state.resolve_conflict(id, index, resolution) {
+                        cx.notify();
+                    }
+                });
+            });
+
             // Render the full stage with artifact workspace
             div()
                 .flex()
@@ -644,10 +965,24 @@ impl Render for AguiWindow {
                         ),
                 )
                 // Stage content - artifact workspace
-                .child(render_stage_pane(&mut self.stage_state))
+                .child(render_stage_pane(&mut self.stage_state, Some(on_resolve)))
                 .on_mouse_down(gpui::MouseButton::Left, cx.listener(|this, _, _, _cx| {
                     this.layout.focus_pane(Pane::Stage);
                 }))
+                .on_key_down(cx.listener(|this, event: &gpui::KeyDownEvent, _window, cx| {
+                    if !this.layout.is_focused(Pane::Stage) {
+                        return;
+                    }
+                    let Some(command) = EditCommand::from_keystroke(&event.keystroke) else {
+                        return;
+                    };
+                    if command == EditCommand::Save {
+                        this.save_active_artifact();
+                    } else if this.stage_state.edit_active(&command) {
+                        cx.notify();
+                    }
+                    cx.stop_propagation();
+                }))
         };
 
         // Main content area (3-column layout)
@@ -720,10 +1055,10 @@ impl Render for AguiWindow {
 }
 
 /// Render a stream item inline (simplified version for main.rs)
-fn render_stream_item_inline<V: 'static + Render>(
+fn render_stream_item_inline(
     item: &StreamItem,
     selected: bool,
-    cx: &mut Context<V>,
+    cx: &mut Context<AguiWindow>,
 ) -> gpui::Div {
     use agui_desktop::stream::components::colors;
 
@@ -827,12 +1162,18 @@ fn render_stream_item_inline<V: 'static + Render>(
                     .clone()
                     .unwrap_or_else(|| {
                         let first_line = reasoning.content.lines().next().unwrap_or(&reasoning.content);
-                        if first_line.len() > 50 {
-                            format!("{}...", &first_line[..50])
+                        if first_line.chars().count() > 50 {
+                            format!("{}...", first_line.chars().take(50).collect::<String>())
                         } else {
                             first_line.to_string()
                         }
                     });
+                let title = match reasoning.duration_ms {
+                    _ if reasoning.streaming => "Thinking...".to_string(),
+                    Some(ms) if ms < 1000 => format!("Thought for {}ms", ms),
+                    Some(ms) => format!("Thought for {:.1}s", ms as f64 / 1000.0),
+                    None => "Thinking".to_string(),
+                };
                 div()
                     .w_full()
                     .py_1()
@@ -853,6 +1194,14 @@ fn render_stream_item_inline<V: 'static + Render>(
                                     .justify_between()
                                     .px_3()
                                     .py_2()
+                                    .cursor_pointer()
+                                    .on_mouse_down(gpui::MouseButton::Left, {
+                                        let item_id = item_id.clone();
+                                        cx.listener(move |this, _, _, cx| {
+                                            this.stream_timeline.state.toggle_expanded(&item_id);
+                                            cx.notify();
+                                        })
+                                    })
                                     .child(
                                         div()
                                             .flex()
@@ -869,10 +1218,14 @@ fn render_stream_item_inline<V: 'static + Render>(
                                                 div()
                                                     .text_xs()
                                                     .font_weight(gpui::FontWeight::MEDIUM)
-                                                    .text_color(rgb(0x606060))
-                                                    .child("Thinking"),
+                                                    .text_color(if reasoning.streaming {
+                                                        rgb(0x007acc)
+                                                    } else {
+                                                        rgb(0x606060)
+                                                    })
+                                                    .child(title),
                                             )
-                                            .when(!reasoning.expanded, |el| {
+                                            .when(!reasoning.expanded && !summary.is_empty(), |el| {
                                                 el.child(
                                                     div()
                                                         .text_xs()
@@ -881,7 +1234,17 @@ fn render_stream_item_inline<V: 'static + Render>(
                                                 )
                                             }),
                                     ),
-                            ),
+                            )
+                            .when(reasoning.expanded, |el| {
+                                el.child(
+                                    div()
+                                        .px_3()
+                                        .pb_2()
+                                        .text_xs()
+                                        .text_color(rgb(0x808080))
+                                        .child(reasoning.content.clone()),
+                                )
+                            }),
                     )
             }
             StreamContent::ToolCall(tc) => {
@@ -945,8 +1308,53 @@ fn render_stream_item_inline<V: 'static + Render>(
                                                 .text_color(rgb(0x606060))
                                                 .child(format!("{}ms", ms)),
                                         )
+                                    })
+                                    .when_some(
+                                        tc.result.clone().filter(|_| tc.tool_name == "read_file"),
+                                        |el, result| {
+                                            el.child(
+                                                div()
+                                                    .px_2()
+                                                    .py_px()
+                                                    .rounded_sm()
+                                                    .bg(rgb(0x0e639c))
+                                                    .text_xs()
+                                                    .text_color(rgb(0xffffff))
+                                                    .cursor_pointer()
+                                                    .child("Open")
+                                                    .on_mouse_down(gpui::MouseButton::Left, {
+                                                        let call_id = tc.call_id.clone();
+                                                        cx.listener(move |this, _, _, cx| {
+                                                            this.open_read_result(&call_id, &result);
+                                                            cx.notify();
+                                                        })
+                                                    }),
+                                            )
+                                        },
+                                    )
+                                    .when(!tc.status.is_terminal(), |el| {
+                                        el.child(
+                                            div()
+                                                .px_2()
+                                                .py_px()
+                                                .rounded_sm()
+                                                .bg(rgb(0x5a1d1d))
+                                                .text_xs()
+                                                .text_color(rgb(0xf14c4c))
+                                                .cursor_pointer()
+                                                .child("Cancel")
+                                                .on_mouse_down(gpui::MouseButton::Left, {
+                                                    let call_id = tc.call_id.clone();
+                                                    cx.listener(move |this, _, _, cx| {
+                                                        this.cancel_tool_call(&call_id);
+                                                        cx.notify();
+                                                    })
+                                                }),
+                                        )
                                     }),
-                            ),
+                            )
+                            .when(!tc.output.is_empty(), |el| el.child(render_output_log(tc)))
+                            .when_some(render_result_view(tc), |el, view| el.child(view)),
                     )
             }
             StreamContent::Plan(plan) => {
@@ -1139,6 +1547,14 @@ fn render_stream_item_inline<V: 'static + Render>(
                                                     .text_color(text)
                                                     .cursor_pointer()
                                                     .child(action.label.clone())
+                                                    .on_mouse_down(gpui::MouseButton::Left, {
+                                                        let item_id = item_id.clone();
+                                                        let action = action.clone();
+                                                        cx.listener(move |this, _, _, cx| {
+                                                            this.handle_approval_action(&item_id, &action);
+                                                            cx.notify();
+                                                        })
+                                                    })
                                             }).collect::<Vec<_>>(),
                                         ),
                                 )
@@ -1224,10 +1640,12 @@ fn main() {
     }
     logging::log_startup(&VERSION.to_string(), &format!("{:#?}", config));
 
-    // Initialize metrics if enabled (need async runtime for this)
+    // Async runtime for metrics export and the orchestrator connection
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+
+    // Initialize metrics if enabled
     if config.enable_metrics {
-        let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
-        rt.block_on(async {
+        runtime.block_on(async {
             if let Err(e) = metrics::init(config.metrics_port).await {
                 tracing::error!("Failed to initialize metrics: {}", e);
             }
@@ -1250,8 +1668,43 @@ fn main() {
         None
     };
 
+    // Connect to the orchestrator
+    tracing::info!("Connecting to orchestrator at {}", config.orchestrator_url);
+    let client = OrchestratorClient::new(config.orchestrator_url.clone()).spawn(runtime.handle());
+    let tool_policy = ToolPolicy::load(config.tool_policy_path()).unwrap_or_else(|e| {
+        // Never fall back to a more permissive policy than the standard one
+        tracing::error!("Failed to load tool policy: {}", e);
+        ToolPolicy::standard()
+    });
+    // File and terminal tools are confined to the workspace roots
+    let (registry, terminals) = match Sandbox::new(config.workspace_roots()) {
+        Ok(sandbox) => {
+            let sandbox = sandbox.with_protected(config.protected_paths());
+            let terminals = Arc::new(TerminalManager::new(sandbox.clone()));
+            let mut registry = create_sandboxed_registry(sandbox);
+            terminals.register_tools(&mut registry);
+            (registry, terminals)
+        }
+        Err(e) => {
+            tracing::error!("Invalid workspace roots, file and terminal tools disabled: {}", e);
+            let mut registry = ToolRegistry::new();
+            registry.register(Box::new(RunCommand));
+            let sandbox = Sandbox::unrestricted().with_protected(config.protected_paths());
+            (registry, Arc::new(TerminalManager::new(sandbox)))
+        }
+    };
+    // Plugins add project-specific tools; they never replace built-in ones
+    let mut registry = registry.with_max_concurrency(config.max_tool_calls);
+    for (path, e) in runtime.block_on(register_plugins(&mut registry, &config.plugin_dir())) {
+        tracing::warn!("Failed to load plugin {}: {}", path.display(), e);
+    }
+    let tool_host = ToolHost::new(registry, runtime.handle())
+        .with_policy(tool_policy)
+        .with_audit(AuditLog::new(config.audit_log_path()));
+    let demo_mode = config.demo_mode;
+
     // Run the gpui application
-    Application::new().run(|cx: &mut App| {
+    Application::new().run(move |cx: &mut App| {
         // Set up window bounds (centered, reasonable default size)
         let bounds = Bounds::centered(None, size(px(1280.0), px(800.0)), cx);
 
@@ -1261,7 +1714,7 @@ fn main() {
                 window_bounds: Some(WindowBounds::Windowed(bounds)),
                 ..Default::default()
             },
-            |_window, cx| cx.new(|_cx| AguiWindow::new()),
+            |_window, cx| cx.new(|cx| AguiWindow::new(client, tool_host, terminals, demo_mode, cx)),
         )
         .expect("Failed to open window");
 
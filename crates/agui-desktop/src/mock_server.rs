@@ -1,12 +1,15 @@
 //! Mock WebSocket server for development and testing
 //!
 //! Simulates the orchestrator server by sending AG-UI protocol events
-//! to connected clients.
+//! to connected clients. Answers the client HELLO with a WELCOME.
 
 use crate::protocol::*;
 use anyhow::Result;
 use axum::{
-    extract::ws::{WebSocket, WebSocketUpgrade},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
     response::IntoResponse,
     routing::get,
     Router,
@@ -141,55 +144,107 @@ impl MockServer {
 /// WebSocket handler
 async fn ws_handler(
     ws: WebSocketUpgrade,
+    State(server): State<MockServer>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(handle_socket)
+    ws.on_upgrade(move |socket| handle_socket(socket, server))
+}
+
+/// Serialize an event and send it on the socket
+async fn send_event<S>(sender: &mut S, event: Event) -> bool
+where
+    S: futures::Sink<Message> + Unpin,
+{
+    match serde_json::to_string(&EventEnvelope::new(event)) {
+        Ok(json) => sender.send(Message::Text(json)).await.is_ok(),
+        Err(e) => {
+            error!("Failed to serialize event: {}", e);
+            false
+        }
+    }
+}
+
+/// Build the reply to a client HELLO
+fn handshake_reply(hello: &Hello) -> Event {
+    match Welcome::for_hello(hello, CLIENT_CAPABILITIES) {
+        Ok(mut welcome) => {
+            welcome.server = Some(format!("agui-mock-server/{}", crate::VERSION));
+            Event::Welcome(welcome)
+        }
+        Err(e) => Event::Error(ErrorEvent {
+            code: "INVALID_VERSION".to_string(),
+            message: e.to_string(),
+            details: Some(serde_json::json!({ "server_version": PROTOCOL_VERSION })),
+        }),
+    }
 }
 
 /// Handle individual WebSocket connection
-async fn handle_socket(socket: WebSocket) {
+async fn handle_socket(socket: WebSocket, server: MockServer) {
     let (mut sender, mut receiver) = socket.split();
+    let mut broadcast_rx = server.broadcast_tx.subscribe();
 
     info!("New WebSocket connection");
 
-    // For now, just echo received messages
-    while let Some(msg) = receiver.next().await {
-        if let Ok(msg) = msg {
-            match msg {
-                axum::extract::ws::Message::Text(text) => {
-                    // Try to parse as JSON protocol event
-                    match serde_json::from_str::<EventEnvelope>(&text) {
-                        Ok(envelope) => {
-                            info!("Received event: {:?}", envelope.event);
-                        }
-                        Err(e) => {
-                            tracing::warn!("Failed to parse event: {}", e);
-                            let error = ErrorEvent {
-                                code: "PARSE_ERROR".to_string(),
-                                message: e.to_string(),
-                                details: None,
-                            };
-                            let error_envelope = EventEnvelope::new(Event::Error(error));
-                            if let Ok(json) = serde_json::to_string(&error_envelope) {
-                                let _ = sender.send(axum::extract::ws::Message::Text(json)).await;
+    loop {
+        tokio::select! {
+            msg = receiver.next() => {
+                let Some(Ok(msg)) = msg else {
+                    error!("WebSocket error");
+                    break;
+                };
+
+                match msg {
+                    Message::Text(text) => {
+                        // Try to parse as JSON protocol event
+                        match serde_json::from_str::<EventEnvelope>(&text) {
+                            Ok(EventEnvelope { event: Event::Hello(hello), .. }) => {
+                                info!("Client hello: {:?}", hello.client);
+                                let reply = handshake_reply(&hello);
+                                let rejected = matches!(reply, Event::Error(_));
+                                if !send_event(&mut sender, reply).await || rejected {
+                                    break;
+                                }
+                            }
+                            Ok(envelope) => {
+                                info!("Received event: {:?}", envelope.event);
+                            }
+                            Err(e) => {
+                                tracing::warn!("Failed to parse event: {}", e);
+                                let error = ErrorEvent {
+                                    code: "PARSE_ERROR".to_string(),
+                                    message: e.to_string(),
+                                    details: None,
+                                };
+                                let _ = send_event(&mut sender, Event::Error(error)).await;
                             }
                         }
                     }
+                    Message::Binary(_) => {
+                        tracing::warn!("Received binary message, not supported");
+                    }
+                    Message::Ping(_) => {
+                        let _ = sender.send(Message::Pong(vec![])).await;
+                    }
+                    Message::Pong(_) => {}
+                    Message::Close(_) => {
+                        info!("WebSocket connection closed");
+                        break;
+                    }
                 }
-                axum::extract::ws::Message::Binary(_) => {
-                    tracing::warn!("Received binary message, not supported");
-                }
-                axum::extract::ws::Message::Ping(_) => {
-                    let _ = sender.send(axum::extract::ws::Message::Pong(vec![])).await;
-                }
-                axum::extract::ws::Message::Pong(_) => {}
-                axum::extract::ws::Message::Close(_) => {
-                    info!("WebSocket connection closed");
-                    break;
+            }
+            envelope = broadcast_rx.recv() => {
+                match envelope {
+                    Ok(envelope) => {
+                        if !send_event(&mut sender, envelope.event).await {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Client lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
-        } else {
-            error!("WebSocket error");
-            break;
         }
     }
 }
@@ -217,4 +272,25 @@ mod tests {
         assert_eq!(server.config.port, 8080);
         assert!(!server.config.auto_stream);
     }
+
+    #[test]
+    fn test_handshake_reply() {
+        match handshake_reply(&Hello::new()) {
+            Event::Welcome(welcome) => {
+                assert_eq!(welcome.protocol_version, PROTOCOL_VERSION);
+                assert!(welcome.server.is_some());
+            }
+            other => panic!("Expected WELCOME, got {:?}", other),
+        }
+
+        let future_client = Hello {
+            supported_versions: vec!["9.0.0".to_string()],
+            capabilities: vec![],
+            client: None,
+        };
+        assert!(matches!(
+            handshake_reply(&future_client),
+            Event::Error(ErrorEvent { code, .. }) if code == "INVALID_VERSION"
+        ));
+    }
 }
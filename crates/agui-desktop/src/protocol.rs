@@ -4,12 +4,24 @@
 //! communication between the AGUI client and orchestrator.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use thiserror::Error;
 
 /// Protocol version
 pub const PROTOCOL_VERSION: &str = "0.1.0";
 
+/// Capability flags advertised by the client in HELLO
+pub const CLIENT_CAPABILITIES: &[&str] = &[
+    "user_action",
+    "render_request",
+    "artifacts",
+    "text_streaming",
+    "reasoning_streaming",
+    "tool_execution",
+    "tool_manifest",
+    "artifact_save",
+];
+
 /// AG-UI event envelope that wraps all messages
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct EventEnvelope {
@@ -42,10 +54,46 @@ impl EventEnvelope {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type")]
 pub enum Event {
+    /// Client handshake (first frame after connecting)
+    #[serde(rename = "HELLO")]
+    Hello(Hello),
+
+    /// Server handshake reply
+    #[serde(rename = "WELCOME")]
+    Welcome(Welcome),
+
     /// User sent a text message
     #[serde(rename = "TEXT_MESSAGE")]
     TextMessage(TextMessage),
 
+    /// Start of a streamed text message
+    #[serde(rename = "TEXT_MESSAGE_START")]
+    TextMessageStart(TextMessageStart),
+
+    /// Incremental text for a streamed message
+    #[serde(rename = "TEXT_MESSAGE_DELTA")]
+    TextMessageDelta(TextMessageDelta),
+
+    /// End of a streamed text message
+    #[serde(rename = "TEXT_MESSAGE_END")]
+    TextMessageEnd(TextMessageEnd),
+
+    /// Start of streamed reasoning for a message
+    #[serde(rename = "REASONING_START")]
+    ReasoningStart(ReasoningStart),
+
+    /// Incremental reasoning text
+    #[serde(rename = "REASONING_DELTA")]
+    ReasoningDelta(ReasoningDelta),
+
+    /// End of streamed reasoning
+    #[serde(rename = "REASONING_END")]
+    ReasoningEnd(ReasoningEnd),
+
+    /// Summary for a reasoning block (may arrive after REASONING_END)
+    #[serde(rename = "REASONING_SUMMARY")]
+    ReasoningSummary(ReasoningSummary),
+
     /// Tool call initiated
     #[serde(rename = "TOOL_CALL_REQUEST")]
     ToolCallRequest(ToolCallRequest),
@@ -54,10 +102,22 @@ pub enum Event {
     #[serde(rename = "TOOL_CALL_STATUS")]
     ToolCallStatus(ToolCallStatus),
 
+    /// Incremental output from a running tool call
+    #[serde(rename = "TOOL_CALL_OUTPUT")]
+    ToolCallOutput(ToolCallOutput),
+
+    /// Request to cancel an in-flight tool call
+    #[serde(rename = "TOOL_CALL_CANCEL")]
+    ToolCallCancel(ToolCallCancel),
+
     /// Tool call completed with result
     #[serde(rename = "TOOL_CALL_RESULT")]
     ToolCallResult(ToolCallResult),
 
+    /// Tools one side can execute, advertised to the other
+    #[serde(rename = "TOOL_MANIFEST")]
+    ToolManifest(ToolManifest),
+
     /// State delta update from orchestrator
     #[serde(rename = "STATE_DELTA")]
     StateDelta(StateDelta),
@@ -86,6 +146,10 @@ pub enum Event {
     #[serde(rename = "ARTIFACT_UPDATE")]
     ArtifactUpdate(ArtifactUpdate),
 
+    /// User edits to an artifact (client to server)
+    #[serde(rename = "ARTIFACT_SAVE")]
+    ArtifactSave(ArtifactSave),
+
     /// User action (button click, form submission, etc.)
     #[serde(rename = "USER_ACTION")]
     UserAction(UserAction),
@@ -99,6 +163,144 @@ pub enum Event {
     ConnectionStatus(ConnectionStatus),
 }
 
+/// Client handshake
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Hello {
+    /// Protocol versions supported by the client (preferred first)
+    pub supported_versions: Vec<String>,
+    /// Capability flags supported by the client
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// Client name and version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+}
+
+impl Default for Hello {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hello {
+    /// Create the handshake for this client build
+    pub fn new() -> Self {
+        Self {
+            supported_versions: vec![PROTOCOL_VERSION.to_string()],
+            capabilities: CLIENT_CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+            client: Some(format!("agui-desktop/{}", crate::VERSION)),
+        }
+    }
+}
+
+/// Server handshake reply
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Welcome {
+    /// Protocol version selected by the server
+    pub protocol_version: String,
+    /// Capability flags supported by the server
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// Server name and version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+}
+
+impl Welcome {
+    /// Build the reply to a client HELLO
+    ///
+    /// Picks the first client version sharing this build's major version.
+    pub fn for_hello(hello: &Hello, capabilities: &[&str]) -> Result<Self, ProtocolError> {
+        let major = major_version(PROTOCOL_VERSION)?;
+        let protocol_version = hello
+            .supported_versions
+            .iter()
+            .find(|v| major_version(v).ok() == Some(major))
+            .cloned()
+            .ok_or_else(|| {
+                ProtocolError::InvalidVersion(format!(
+                    "no supported version in [{}] (server speaks {})",
+                    hello.supported_versions.join(", "),
+                    PROTOCOL_VERSION
+                ))
+            })?;
+
+        Ok(Self {
+            protocol_version,
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            server: None,
+        })
+    }
+}
+
+/// Outcome of the HELLO/WELCOME handshake
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegotiatedProtocol {
+    /// Protocol version selected by the server
+    pub version: String,
+    /// Capabilities supported by both sides
+    pub capabilities: BTreeSet<String>,
+    /// Server skipped the handshake (pre-handshake orchestrator)
+    pub legacy: bool,
+}
+
+impl NegotiatedProtocol {
+    /// Validate a WELCOME against the HELLO that was sent
+    ///
+    /// Fails with `InvalidVersion` if the selected version's major version
+    /// is not one the client supports.
+    pub fn negotiate(hello: &Hello, welcome: &Welcome) -> Result<Self, ProtocolError> {
+        let major = major_version(&welcome.protocol_version)?;
+        let compatible = hello
+            .supported_versions
+            .iter()
+            .any(|v| major_version(v).ok() == Some(major));
+        if !compatible {
+            return Err(ProtocolError::InvalidVersion(format!(
+                "server selected {}, client supports [{}]",
+                welcome.protocol_version,
+                hello.supported_versions.join(", ")
+            )));
+        }
+
+        let capabilities = welcome
+            .capabilities
+            .iter()
+            .filter(|c| hello.capabilities.contains(c))
+            .cloned()
+            .collect();
+
+        Ok(Self {
+            version: welcome.protocol_version.clone(),
+            capabilities,
+            legacy: false,
+        })
+    }
+
+    /// Assume the base protocol for servers that never send WELCOME
+    pub fn legacy() -> Self {
+        Self {
+            version: PROTOCOL_VERSION.to_string(),
+            capabilities: BTreeSet::new(),
+            legacy: true,
+        }
+    }
+
+    /// Check whether a capability was negotiated
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.contains(capability)
+    }
+}
+
+/// Parse the major component of a `major.minor.patch` version
+pub fn major_version(version: &str) -> Result<u64, ProtocolError> {
+    version
+        .split('.')
+        .next()
+        .and_then(|major| major.trim().parse().ok())
+        .ok_or_else(|| ProtocolError::InvalidVersion(version.to_string()))
+}
+
 /// Text message event
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TextMessage {
@@ -111,6 +313,72 @@ pub struct TextMessage {
     pub metadata: Option<HashMap<String, String>>,
 }
 
+/// Start of a streamed text message
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TextMessageStart {
+    /// Message ID referenced by deltas
+    pub message_id: String,
+    /// Message sender (user ID, agent ID, system, etc.)
+    pub sender: String,
+    /// Optional metadata (author name, avatar, etc.)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+/// Incremental text for a streamed message
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TextMessageDelta {
+    /// Message ID from TEXT_MESSAGE_START
+    pub message_id: String,
+    /// Text to append
+    pub delta: String,
+}
+
+/// End of a streamed text message
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TextMessageEnd {
+    /// Message ID from TEXT_MESSAGE_START
+    pub message_id: String,
+}
+
+/// Start of streamed reasoning
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReasoningStart {
+    /// Reasoning ID referenced by deltas
+    pub reasoning_id: String,
+    /// Message this reasoning belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+}
+
+/// Incremental reasoning text
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReasoningDelta {
+    /// Reasoning ID from REASONING_START
+    pub reasoning_id: String,
+    /// Text to append
+    pub delta: String,
+}
+
+/// End of streamed reasoning
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReasoningEnd {
+    /// Reasoning ID from REASONING_START
+    pub reasoning_id: String,
+    /// Optional one-line summary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// Summary for a reasoning block
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReasoningSummary {
+    /// Reasoning ID from REASONING_START
+    pub reasoning_id: String,
+    /// One-line summary
+    pub summary: String,
+}
+
 /// Tool call request
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ToolCallRequest {
@@ -151,6 +419,32 @@ pub enum ToolCallState {
     Cancelled,
 }
 
+/// Incremental tool output
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolCallOutput {
+    /// Tool call ID
+    pub id: String,
+    /// Stream the output was written to
+    pub stream: OutputStream,
+    /// Output text, usually one or more complete lines
+    pub chunk: String,
+}
+
+/// Process output streams
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Tool call cancellation request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolCallCancel {
+    /// Tool call ID
+    pub id: String,
+}
+
 /// Tool call result
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ToolCallResult {
@@ -163,6 +457,26 @@ pub struct ToolCallResult {
     pub error: Option<String>,
 }
 
+/// Tools advertised by the client or declared by the orchestrator
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolManifest {
+    /// Tool definitions
+    pub tools: Vec<ToolDefinition>,
+}
+
+/// A tool's name, description and parameter schema
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolDefinition {
+    /// Tool name/identifier
+    pub name: String,
+    /// Human-readable description
+    #[serde(default)]
+    pub description: String,
+    /// JSON Schema for the tool's parameters
+    #[serde(default)]
+    pub parameters: serde_json::Value,
+}
+
 /// State delta update
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct StateDelta {
@@ -286,17 +600,88 @@ pub struct ArtifactOpen {
 pub struct ArtifactUpdate {
     /// Artifact ID
     pub id: String,
-    /// Updated content
+    /// Updated content (unused by partial updates)
+    #[serde(default)]
     pub content: String,
     /// Change type (full_replace, partial, diff, etc.)
     #[serde(default = "default_change_type")]
     pub change_type: String,
+    /// Artifact version the update was made against
+    ///
+    /// Updates against a stale version are rejected. Required for partial updates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_version: Option<u64>,
+    /// Edits for partial updates, applied in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edits: Vec<ArtifactEdit>,
 }
 
 fn default_change_type() -> String {
     "full_replace".to_string()
 }
 
+/// User edits saved from the Stage
+///
+/// The saved content becomes version `base_version + 1`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ArtifactSave {
+    /// Artifact ID
+    pub id: String,
+    /// Version of the content the edits were made to
+    pub base_version: u64,
+    /// Unified diff from that version to the saved content
+    pub diff: String,
+    /// Full saved content, sent when the diff alone does not reproduce it
+    ///
+    /// Line diffs cannot express a change to the trailing newline, so such
+    /// saves carry the content as well. When set it takes precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+/// A position in text content
+///
+/// Lines are separated by `\n` and columns count characters, both from 0.
+/// The end of a line is at column equal to its length.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TextPosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl TextPosition {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+/// A range of text content, from `start` up to but not including `end`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TextRange {
+    pub start: TextPosition,
+    pub end: TextPosition,
+}
+
+impl TextRange {
+    pub fn new(start: TextPosition, end: TextPosition) -> Self {
+        Self { start, end }
+    }
+}
+
+/// One edit of a partial artifact update
+///
+/// Positions refer to the content left by the previous edit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ArtifactEdit {
+    /// Replace a range with text
+    Replace { range: TextRange, text: String },
+    /// Insert text at a position
+    Insert { position: TextPosition, text: String },
+    /// Delete a range
+    Delete { range: TextRange },
+}
+
 /// User action event
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct UserAction {
@@ -448,4 +833,160 @@ mod tests {
 
         assert_eq!(delta, deserialized);
     }
+
+    #[test]
+    fn test_text_message_stream_serialization() {
+        let delta = Event::TextMessageDelta(TextMessageDelta {
+            message_id: "msg_1".to_string(),
+            delta: "Hel".to_string(),
+        });
+
+        let json = serde_json::to_value(&delta).unwrap();
+        assert_eq!(json["type"], "TEXT_MESSAGE_DELTA");
+        assert_eq!(json["message_id"], "msg_1");
+
+        let deserialized: Event = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, delta);
+    }
+
+    #[test]
+    fn test_reasoning_stream_serialization() {
+        let start: Event = serde_json::from_str(
+            r#"{"type": "REASONING_START", "reasoning_id": "r1", "message_id": "msg_1"}"#,
+        )
+        .unwrap();
+        assert!(matches!(start, Event::ReasoningStart(s) if s.message_id.as_deref() == Some("msg_1")));
+
+        let end = Event::ReasoningEnd(ReasoningEnd {
+            reasoning_id: "r1".to_string(),
+            summary: None,
+        });
+        let json = serde_json::to_value(&end).unwrap();
+        assert_eq!(json["type"], "REASONING_END");
+        assert!(json.get("summary").is_none());
+        assert_eq!(serde_json::from_value::<Event>(json).unwrap(), end);
+    }
+
+    #[test]
+    fn test_major_version() {
+        assert_eq!(major_version("0.1.0").unwrap(), 0);
+        assert_eq!(major_version("12.3").unwrap(), 12);
+        assert!(matches!(
+            major_version("v1"),
+            Err(ProtocolError::InvalidVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_handshake_serialization() {
+        let json = serde_json::to_value(Event::Hello(Hello::new())).unwrap();
+        assert_eq!(json["type"], "HELLO");
+        assert_eq!(json["supported_versions"][0], PROTOCOL_VERSION);
+
+        let welcome: Event = serde_json::from_str(
+            r#"{"type": "WELCOME", "protocol_version": "0.1.0"}"#,
+        )
+        .unwrap();
+        assert!(matches!(welcome, Event::Welcome(w) if w.capabilities.is_empty()));
+    }
+
+    #[test]
+    fn test_tool_manifest_serialization() {
+        let event: Event = serde_json::from_str(
+            r#"{"type": "TOOL_MANIFEST", "tools": [{"name": "deploy"}]}"#,
+        )
+        .unwrap();
+        let Event::ToolManifest(manifest) = &event else {
+            panic!("expected TOOL_MANIFEST");
+        };
+        assert_eq!(manifest.tools[0].name, "deploy");
+        assert!(manifest.tools[0].parameters.is_null());
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(serde_json::from_value::<Event>(json).unwrap(), event);
+    }
+
+    #[test]
+    fn test_partial_artifact_update_serialization() {
+        let event: Event = serde_json::from_str(
+            r#"{
+                "type": "ARTIFACT_UPDATE",
+                "id": "art_1",
+                "change_type": "partial",
+                "base_version": 3,
+                "edits": [
+                    {"op": "insert", "position": {"line": 0, "column": 0}, "text": "// header\n"},
+                    {"op": "delete", "range": {"start": {"line": 2, "column": 0}, "end": {"line": 3, "column": 0}}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let Event::ArtifactUpdate(update) = &event else {
+            panic!("expected ARTIFACT_UPDATE");
+        };
+        assert!(update.content.is_empty());
+        assert_eq!(update.base_version, Some(3));
+        assert_eq!(
+            update.edits[0],
+            ArtifactEdit::Insert {
+                position: TextPosition::new(0, 0),
+                text: "// header\n".to_string(),
+            }
+        );
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["edits"][1]["op"], "delete");
+        assert_eq!(serde_json::from_value::<Event>(json).unwrap(), event);
+    }
+
+    #[test]
+    fn test_negotiate_capabilities() {
+        let hello = Hello::new();
+        let welcome = Welcome {
+            protocol_version: "0.4.2".to_string(),
+            capabilities: vec!["user_action".to_string(), "telepathy".to_string()],
+            server: None,
+        };
+
+        let negotiated = NegotiatedProtocol::negotiate(&hello, &welcome).unwrap();
+        assert_eq!(negotiated.version, "0.4.2");
+        assert!(negotiated.supports("user_action"));
+        assert!(!negotiated.supports("telepathy"));
+        assert!(!negotiated.supports("artifacts"));
+        assert!(!negotiated.legacy);
+    }
+
+    #[test]
+    fn test_negotiate_major_mismatch() {
+        let hello = Hello::new();
+        let welcome = Welcome {
+            protocol_version: "1.0.0".to_string(),
+            capabilities: vec![],
+            server: None,
+        };
+
+        assert!(matches!(
+            NegotiatedProtocol::negotiate(&hello, &welcome),
+            Err(ProtocolError::InvalidVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_welcome_for_hello() {
+        let hello = Hello {
+            supported_versions: vec!["1.0.0".to_string(), "0.3.0".to_string()],
+            capabilities: vec![],
+            client: None,
+        };
+        let welcome = Welcome::for_hello(&hello, &["artifacts"]).unwrap();
+        assert_eq!(welcome.protocol_version, "0.3.0");
+        assert_eq!(welcome.capabilities, vec!["artifacts"]);
+
+        let future_only = Hello {
+            supported_versions: vec!["2.0.0".to_string()],
+            capabilities: vec![],
+            client: None,
+        };
+        assert!(Welcome::for_hello(&future_only, &[]).is_err());
+    }
 }
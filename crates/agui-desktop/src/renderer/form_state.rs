@@ -3,6 +3,7 @@
 //! Tracks the state of dynamically rendered forms and handles form submissions.
 //! Form state is keyed by component ID and can hold various value types.
 
+use crate::protocol::UserAction;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -166,6 +167,16 @@ impl FormAction {
     }
 }
 
+impl From<FormAction> for UserAction {
+    fn from(action: FormAction) -> Self {
+        UserAction {
+            action_type: action.action_type,
+            component_id: action.component_id,
+            payload: action.payload,
+        }
+    }
+}
+
 /// Form state container
 #[derive(Debug, Clone, Default)]
 pub struct FormState {
@@ -418,6 +429,15 @@ mod tests {
         assert_eq!(action.component_id, "btn-1");
     }
 
+    #[test]
+    fn test_form_action_to_user_action() {
+        let action = FormAction::value_change("toggle-1", FormValue::Bool(true));
+        let user_action = UserAction::from(action);
+        assert_eq!(user_action.action_type, "value_change");
+        assert_eq!(user_action.component_id, "toggle-1");
+        assert_eq!(user_action.payload, serde_json::Value::Bool(true));
+    }
+
     #[test]
     fn test_form_submit_action() {
         let mut values = HashMap::new();
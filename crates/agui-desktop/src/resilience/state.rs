@@ -79,6 +79,24 @@ impl ReconnectStrategy {
         }
     }
 
+    /// Set the base delay in milliseconds
+    pub fn with_base_delay(mut self, base_delay_ms: u64) -> Self {
+        self.base_delay_ms = base_delay_ms;
+        self
+    }
+
+    /// Set the maximum delay in milliseconds
+    pub fn with_max_delay(mut self, max_delay_ms: u64) -> Self {
+        self.max_delay_ms = max_delay_ms;
+        self
+    }
+
+    /// Set the maximum number of attempts (0 = unlimited)
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
     /// Record a connection attempt
     pub fn record_attempt(&mut self) {
         self.attempt += 1;
@@ -99,7 +117,7 @@ impl ReconnectStrategy {
 
     /// Check if we should retry
     pub fn should_retry(&self) -> bool {
-        if self.max_attempts > 0 && self.attempt >= self.max_attempts {
+        if self.is_exhausted() {
             return false;
         }
 
@@ -110,6 +128,11 @@ impl ReconnectStrategy {
         }
     }
 
+    /// Check if the maximum number of attempts has been used up
+    pub fn is_exhausted(&self) -> bool {
+        self.max_attempts > 0 && self.attempt >= self.max_attempts
+    }
+
     /// Get current attempt number
     pub fn attempt(&self) -> u32 {
         self.attempt
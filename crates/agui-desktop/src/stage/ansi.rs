@@ -0,0 +1,579 @@
+//! ANSI Terminal Screen
+//!
+//! Interprets terminal output (text plus ANSI escape sequences) into a
+//! screen of styled lines for terminal artifacts. Supports SGR colors
+//! (16, 256 and 24-bit), cursor movement and line/screen erasure, which
+//! covers shells, REPLs, debuggers and progress bars. Other sequences
+//! are consumed and ignored.
+
+/// Default terminal height in rows
+pub const DEFAULT_ROWS: usize = 24;
+/// Default terminal width in columns
+pub const DEFAULT_COLS: usize = 80;
+/// Lines kept before the oldest scrollback is dropped
+pub const SCROLLBACK_LINES: usize = 5000;
+
+/// Default foreground color
+pub const DEFAULT_FG: u32 = 0xcccccc;
+/// Default background color
+pub const DEFAULT_BG: u32 = 0x1e1e1e;
+
+/// Standard 16-color palette (VS Code dark terminal theme)
+const PALETTE: [u32; 16] = [
+    0x000000, 0xcd3131, 0x0dbc79, 0xe5e510, 0x2472c8, 0xbc3fbc, 0x11a8cd, 0xe5e5e5,
+    0x666666, 0xf14c4c, 0x23d18b, 0xf5f543, 0x3b8eea, 0xd670d6, 0x29b8db, 0xffffff,
+];
+
+/// Character attributes set by SGR sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CellStyle {
+    /// Foreground color (None for the default)
+    pub fg: Option<u32>,
+    /// Background color (None for the default)
+    pub bg: Option<u32>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    /// Swap foreground and background
+    pub inverse: bool,
+}
+
+impl CellStyle {
+    /// Resolve the colors to draw with (foreground, optional background)
+    pub fn colors(&self) -> (u32, Option<u32>) {
+        if self.inverse {
+            (self.bg.unwrap_or(DEFAULT_BG), Some(self.fg.unwrap_or(DEFAULT_FG)))
+        } else {
+            (self.fg.unwrap_or(DEFAULT_FG), self.bg)
+        }
+    }
+}
+
+/// A run of text sharing one style
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyledSpan {
+    pub text: String,
+    pub style: CellStyle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cell {
+    ch: char,
+    style: CellStyle,
+}
+
+impl Cell {
+    fn blank() -> Self {
+        Self {
+            ch: ' ',
+            style: CellStyle::default(),
+        }
+    }
+}
+
+/// Escape sequence parser state
+#[derive(Debug, Clone, PartialEq)]
+enum ParseState {
+    Ground,
+    Escape,
+    /// Control sequence; collects parameter and intermediate bytes
+    Csi(String),
+    /// Operating system command (window title etc.), ignored
+    Osc,
+    /// ESC seen inside an OSC, expecting the `\` of ST
+    OscEscape,
+    /// Character set designation; the next character is skipped
+    Charset,
+}
+
+/// Terminal screen built from a stream of output
+#[derive(Debug, Clone)]
+pub struct TerminalScreen {
+    rows: usize,
+    cols: usize,
+    /// Scrollback plus screen; the screen is the last `rows` lines
+    lines: Vec<Vec<Cell>>,
+    cursor_row: usize,
+    cursor_col: usize,
+    saved_cursor: (usize, usize),
+    style: CellStyle,
+    state: ParseState,
+}
+
+impl Default for TerminalScreen {
+    fn default() -> Self {
+        Self::new(DEFAULT_ROWS, DEFAULT_COLS)
+    }
+}
+
+impl TerminalScreen {
+    /// Create an empty screen of the given size
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows: rows.max(1),
+            cols: cols.max(1),
+            lines: vec![Vec::new()],
+            cursor_row: 0,
+            cursor_col: 0,
+            saved_cursor: (0, 0),
+            style: CellStyle::default(),
+            state: ParseState::Ground,
+        }
+    }
+
+    /// Build a screen from a complete output transcript
+    pub fn from_output(output: &str, rows: usize, cols: usize) -> Self {
+        let mut screen = Self::new(rows, cols);
+        screen.feed(output);
+        screen
+    }
+
+    /// Screen height in rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Screen width in columns
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Change the screen size; existing lines are kept as they are
+    pub fn resize(&mut self, rows: usize, cols: usize) {
+        self.rows = rows.max(1);
+        self.cols = cols.max(1);
+        self.cursor_col = self.cursor_col.min(self.cols - 1);
+    }
+
+    /// Cursor position as (line index, column)
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.cursor_row, self.cursor_col)
+    }
+
+    /// Interpret a chunk of output
+    ///
+    /// Chunks may split escape sequences; parsing resumes on the next call.
+    pub fn feed(&mut self, output: &str) {
+        for ch in output.chars() {
+            self.advance(ch);
+        }
+    }
+
+    /// Get every line (scrollback and screen) as styled spans
+    ///
+    /// Trailing blank cells are dropped.
+    pub fn lines(&self) -> Vec<Vec<StyledSpan>> {
+        self.lines.iter().map(|line| spans(line)).collect()
+    }
+
+    /// Get the plain text of every line, without styling
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.iter().map(|cell| cell.ch).collect::<String>().trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn advance(&mut self, ch: char) {
+        match std::mem::replace(&mut self.state, ParseState::Ground) {
+            ParseState::Ground => self.ground(ch),
+            ParseState::Escape => self.escape(ch),
+            ParseState::Csi(mut params) => {
+                if ('\u{40}'..='\u{7e}').contains(&ch) {
+                    self.csi_dispatch(&params, ch);
+                } else if ch == '\u{1b}' {
+                    self.state = ParseState::Escape;
+                } else {
+                    params.push(ch);
+                    self.state = ParseState::Csi(params);
+                }
+            }
+            ParseState::Osc => {
+                self.state = match ch {
+                    '\u{07}' => ParseState::Ground,
+                    '\u{1b}' => ParseState::OscEscape,
+                    _ => ParseState::Osc,
+                }
+            }
+            ParseState::OscEscape | ParseState::Charset => {}
+        }
+    }
+
+    fn ground(&mut self, ch: char) {
+        match ch {
+            '\u{1b}' => self.state = ParseState::Escape,
+            // Treat LF as a full newline so plain (non-PTY) logs render too
+            '\n' => {
+                self.line_feed();
+                self.cursor_col = 0;
+            }
+            '\r' => self.cursor_col = 0,
+            '\u{08}' => self.cursor_col = self.cursor_col.saturating_sub(1),
+            '\t' => self.cursor_col = ((self.cursor_col / 8 + 1) * 8).min(self.cols - 1),
+            c if c.is_control() => {}
+            c => self.print(c),
+        }
+    }
+
+    fn escape(&mut self, ch: char) {
+        match ch {
+            '[' => self.state = ParseState::Csi(String::new()),
+            ']' => self.state = ParseState::Osc,
+            '(' | ')' | '*' | '+' => self.state = ParseState::Charset,
+            '7' => self.saved_cursor = (self.cursor_row, self.cursor_col),
+            '8' => self.restore_cursor(),
+            // Reverse index
+            'M' => self.cursor_row = self.cursor_row.saturating_sub(1).max(self.top()),
+            'c' => *self = Self::new(self.rows, self.cols),
+            _ => {}
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &str, action: char) {
+        // Private modes (cursor visibility, alternate screen, ...) are ignored
+        if params.starts_with(['?', '>', '<', '=']) {
+            return;
+        }
+        let args: Vec<usize> = params
+            .split(';')
+            .map(|p| p.trim().parse().unwrap_or(0))
+            .collect();
+        let arg = |i: usize| args.get(i).copied().unwrap_or(0);
+        let count = arg(0).max(1);
+
+        match action {
+            'm' => self.select_graphic_rendition(&args),
+            'A' => self.cursor_row = self.cursor_row.saturating_sub(count).max(self.top()),
+            'B' => self.cursor_row = self.cursor_row.saturating_add(count).min(self.top() + self.rows - 1),
+            'C' => self.cursor_col = self.cursor_col.saturating_add(count).min(self.cols - 1),
+            'D' => self.cursor_col = self.cursor_col.saturating_sub(count),
+            'E' => {
+                self.cursor_row = self.cursor_row.saturating_add(count).min(self.top() + self.rows - 1);
+                self.cursor_col = 0;
+            }
+            'F' => {
+                self.cursor_row = self.cursor_row.saturating_sub(count).max(self.top());
+                self.cursor_col = 0;
+            }
+            'G' | '`' => self.cursor_col = (count - 1).min(self.cols - 1),
+            'd' => self.cursor_row = self.top() + (count - 1).min(self.rows - 1),
+            'H' | 'f' => {
+                self.cursor_row = self.top() + (arg(0).max(1) - 1).min(self.rows - 1);
+                self.cursor_col = (arg(1).max(1) - 1).min(self.cols - 1);
+            }
+            'K' => self.erase_in_line(arg(0)),
+            'J' => self.erase_in_display(arg(0)),
+            'P' => {
+                let col = self.cursor_col;
+                let line = self.line_mut();
+                let col = col.min(line.len());
+                let end = col.saturating_add(count).min(line.len());
+                line.drain(col..end);
+            }
+            'X' => {
+                let col = self.cursor_col;
+                let line = self.line_mut();
+                for cell in line.iter_mut().skip(col).take(count) {
+                    *cell = Cell::blank();
+                }
+            }
+            '@' => {
+                let col = self.cursor_col;
+                let cols = self.cols;
+                let line = self.line_mut();
+                if col < line.len() {
+                    line.splice(col..col, std::iter::repeat_n(Cell::blank(), count.min(cols)));
+                    line.truncate(cols);
+                }
+            }
+            's' => self.saved_cursor = (self.cursor_row, self.cursor_col),
+            'u' => self.restore_cursor(),
+            _ => {}
+        }
+        self.ensure_line(self.cursor_row);
+    }
+
+    fn select_graphic_rendition(&mut self, args: &[usize]) {
+        let mut i = 0;
+        while i < args.len() {
+            match args[i] {
+                0 => self.style = CellStyle::default(),
+                1 => self.style.bold = true,
+                2 => self.style.dim = true,
+                3 => self.style.italic = true,
+                4 => self.style.underline = true,
+                7 => self.style.inverse = true,
+                22 => {
+                    self.style.bold = false;
+                    self.style.dim = false;
+                }
+                23 => self.style.italic = false,
+                24 => self.style.underline = false,
+                27 => self.style.inverse = false,
+                n @ 30..=37 => self.style.fg = Some(PALETTE[n - 30]),
+                39 => self.style.fg = None,
+                n @ 40..=47 => self.style.bg = Some(PALETTE[n - 40]),
+                49 => self.style.bg = None,
+                n @ 90..=97 => self.style.fg = Some(PALETTE[n - 90 + 8]),
+                n @ 100..=107 => self.style.bg = Some(PALETTE[n - 100 + 8]),
+                n @ (38 | 48) => {
+                    let (color, used) = extended_color(&args[i + 1..]);
+                    if n == 38 {
+                        self.style.fg = color;
+                    } else {
+                        self.style.bg = color;
+                    }
+                    i += used;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    fn erase_in_line(&mut self, mode: usize) {
+        let col = self.cursor_col;
+        let line = self.line_mut();
+        match mode {
+            0 => line.truncate(col),
+            1 => {
+                for cell in line.iter_mut().take(col + 1) {
+                    *cell = Cell::blank();
+                }
+            }
+            _ => line.clear(),
+        }
+    }
+
+    fn erase_in_display(&mut self, mode: usize) {
+        match mode {
+            0 => {
+                self.erase_in_line(0);
+                self.lines.truncate(self.cursor_row + 1);
+            }
+            1 => {
+                let top = self.top().min(self.cursor_row);
+                for line in &mut self.lines[top..self.cursor_row] {
+                    line.clear();
+                }
+                self.erase_in_line(1);
+            }
+            2 => {
+                let top = self.top();
+                for line in &mut self.lines[top..] {
+                    line.clear();
+                }
+            }
+            // Clear scrollback, keeping the screen
+            _ => {
+                let top = self.top();
+                self.lines.drain(..top);
+                self.cursor_row = self.cursor_row.saturating_sub(top);
+                self.saved_cursor.0 = self.saved_cursor.0.saturating_sub(top);
+            }
+        }
+    }
+
+    fn print(&mut self, ch: char) {
+        if self.cursor_col >= self.cols {
+            self.line_feed();
+            self.cursor_col = 0;
+        }
+
+        let cell = Cell {
+            ch,
+            style: self.style,
+        };
+        let col = self.cursor_col;
+        let line = self.line_mut();
+        if line.len() < col {
+            line.resize(col, Cell::blank());
+        }
+        if col < line.len() {
+            line[col] = cell;
+        } else {
+            line.push(cell);
+        }
+        self.cursor_col += 1;
+    }
+
+    fn line_feed(&mut self) {
+        self.cursor_row += 1;
+        self.ensure_line(self.cursor_row);
+
+        if self.lines.len() > SCROLLBACK_LINES {
+            let excess = self.lines.len() - SCROLLBACK_LINES;
+            self.lines.drain(..excess);
+            self.cursor_row = self.cursor_row.saturating_sub(excess);
+            self.saved_cursor.0 = self.saved_cursor.0.saturating_sub(excess);
+        }
+    }
+
+    fn restore_cursor(&mut self) {
+        let (row, col) = self.saved_cursor;
+        // The saved row may have scrolled off the screen since it was saved
+        self.cursor_row = row.min(self.lines.len().saturating_sub(1)).max(self.top());
+        self.cursor_col = col;
+    }
+
+    /// Index of the first line on screen
+    fn top(&self) -> usize {
+        self.lines.len().saturating_sub(self.rows)
+    }
+
+    fn ensure_line(&mut self, row: usize) {
+        if self.lines.len() <= row {
+            self.lines.resize(row + 1, Vec::new());
+        }
+    }
+
+    fn line_mut(&mut self) -> &mut Vec<Cell> {
+        self.ensure_line(self.cursor_row);
+        &mut self.lines[self.cursor_row]
+    }
+}
+
+/// Parse the color after SGR 38/48, returning it and the arguments consumed
+fn extended_color(args: &[usize]) -> (Option<u32>, usize) {
+    match args {
+        [5, n, ..] => (Some(color_256(*n)), 2),
+        [2, r, g, b, ..] => {
+            let channel = |c: usize| c.min(255) as u32;
+            (Some(channel(*r) << 16 | channel(*g) << 8 | channel(*b)), 4)
+        }
+        _ => (None, args.len()),
+    }
+}
+
+/// Resolve an xterm 256-color palette index
+fn color_256(index: usize) -> u32 {
+    const LEVELS: [u32; 6] = [0, 95, 135, 175, 215, 255];
+    match index {
+        0..=15 => PALETTE[index],
+        16..=231 => {
+            let i = index - 16;
+            LEVELS[i / 36] << 16 | LEVELS[i / 6 % 6] << 8 | LEVELS[i % 6]
+        }
+        232..=255 => {
+            let gray = 8 + 10 * (index - 232) as u32;
+            gray << 16 | gray << 8 | gray
+        }
+        _ => DEFAULT_FG,
+    }
+}
+
+/// Merge a line's cells into runs of equal style
+fn spans(line: &[Cell]) -> Vec<StyledSpan> {
+    let end = line
+        .iter()
+        .rposition(|cell| cell.ch != ' ' || cell.style.bg.is_some() || cell.style.inverse)
+        .map_or(0, |i| i + 1);
+
+    let mut spans: Vec<StyledSpan> = Vec::new();
+    for cell in &line[..end] {
+        match spans.last_mut() {
+            Some(span) if span.style == cell.style => span.text.push(cell.ch),
+            _ => spans.push(StyledSpan {
+                text: cell.ch.to_string(),
+                style: cell.style,
+            }),
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text_and_newlines() {
+        let screen = TerminalScreen::from_output("$ echo hi\r\nhi\r\n$ ", 24, 80);
+        assert_eq!(screen.text(), "$ echo hi\nhi\n$");
+        assert_eq!(screen.cursor(), (2, 2));
+    }
+
+    #[test]
+    fn test_sgr_colors() {
+        let screen = TerminalScreen::from_output(
+            "\x1b[1;31merror\x1b[0m: \x1b[38;5;208mwarn\x1b[38;2;1;2;3mrgb\x1b[m",
+            24,
+            80,
+        );
+        let lines = screen.lines();
+        let spans = &lines[0];
+
+        assert_eq!(spans[0].text, "error");
+        assert_eq!(spans[0].style.fg, Some(PALETTE[1]));
+        assert!(spans[0].style.bold);
+        assert_eq!(spans[1].text, ": ");
+        assert_eq!(spans[1].style, CellStyle::default());
+        assert_eq!(spans[2].style.fg, Some(0xff8700));
+        assert_eq!(spans[3].text, "rgb");
+        assert_eq!(spans[3].style.fg, Some(0x010203));
+    }
+
+    #[test]
+    fn test_carriage_return_overwrites() {
+        // Progress bars redraw the same line
+        let screen = TerminalScreen::from_output("progress 10%\rprogress 100%\r\ndone", 24, 80);
+        assert_eq!(screen.text(), "progress 100%\ndone");
+
+        let screen = TerminalScreen::from_output("long line\r\x1b[Kshort", 24, 80);
+        assert_eq!(screen.text(), "short");
+    }
+
+    #[test]
+    fn test_cursor_movement_and_erase() {
+        let mut screen = TerminalScreen::new(3, 10);
+        screen.feed("a\r\nb\r\nc");
+        screen.feed("\x1b[1;1HX\x1b[2;3HY");
+        assert_eq!(screen.text(), "X\nb Y\nc");
+
+        screen.feed("\x1b[2J");
+        assert_eq!(screen.text(), "\n\n");
+
+        screen.feed("\x1b[Habc\x1b[2D\x1b[P");
+        assert_eq!(screen.text(), "ac\n\n");
+    }
+
+    #[test]
+    fn test_restored_cursor_above_screen() {
+        // Save, scroll the saved row off screen, restore, then clear scrollback
+        let mut screen = TerminalScreen::new(24, 80);
+        screen.feed(&format!("\x1b7{}\x1b8\x1b[3J", "x\n".repeat(30)));
+        screen.feed("y");
+        assert_eq!(screen.cursor().0, 0);
+        assert!(screen.text().starts_with('y'));
+    }
+
+    #[test]
+    fn test_huge_counts_saturate() {
+        let mut screen = TerminalScreen::new(3, 10);
+        screen.feed("ab\x1b[18446744073709551615B\x1b[18446744073709551615C");
+        assert_eq!(screen.cursor(), (2, 9));
+        screen.feed("\x1b[18446744073709551615E\x1b[1;1H\x1b[18446744073709551615@\x1b[18446744073709551615P");
+        assert_eq!(screen.cursor(), (0, 0));
+    }
+
+    #[test]
+    fn test_wraps_and_splits_sequences() {
+        let mut screen = TerminalScreen::new(24, 4);
+        screen.feed("abcdef\x1b[3");
+        screen.feed("2mg\x1b]0;title\x07h");
+        assert_eq!(screen.text(), "abcd\nefgh");
+        assert_eq!(screen.lines()[1][1].style.fg, Some(PALETTE[2]));
+    }
+
+    #[test]
+    fn test_inverse_colors() {
+        let style = CellStyle {
+            fg: Some(0x123456),
+            inverse: true,
+            ..Default::default()
+        };
+        assert_eq!(style.colors(), (DEFAULT_BG, Some(0x123456)));
+    }
+}
@@ -0,0 +1,515 @@
+//! Editable Text Buffer
+//!
+//! Holds the user's edits to an artifact: text, cursor, selection and an
+//! undo/redo history. Keystrokes are translated into `EditCommand`s,
+//! which the buffer applies.
+
+use std::ops::Range;
+
+use gpui::Keystroke;
+
+use super::edit::{byte_offset, position_at};
+use crate::protocol::{TextPosition, TextRange};
+
+/// Most edits kept for undo
+const MAX_UNDO_STEPS: usize = 500;
+
+/// Cursor movement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    LineStart,
+    LineEnd,
+    DocumentStart,
+    DocumentEnd,
+}
+
+/// An editing operation triggered by the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditCommand {
+    /// Replace the selection with text
+    Insert(String),
+    /// Delete the selection or the character before the cursor
+    Backspace,
+    /// Delete the selection or the character after the cursor
+    Delete,
+    /// Move the cursor, extending the selection if `extend` is set
+    Move { motion: Motion, extend: bool },
+    SelectAll,
+    Undo,
+    Redo,
+    /// Send the edits to the orchestrator; handled by the caller
+    Save,
+}
+
+impl EditCommand {
+    /// Translate a keystroke, if it edits text
+    ///
+    /// Shortcuts accept either Ctrl or Cmd.
+    pub fn from_keystroke(keystroke: &Keystroke) -> Option<Self> {
+        let modifiers = &keystroke.modifiers;
+        let shortcut = modifiers.control || modifiers.platform;
+        let extend = modifiers.shift;
+        let motion = |motion| Some(Self::Move { motion, extend });
+
+        match keystroke.key.as_str() {
+            "left" => motion(Motion::Left),
+            "right" => motion(Motion::Right),
+            "up" => motion(Motion::Up),
+            "down" => motion(Motion::Down),
+            "home" if shortcut => motion(Motion::DocumentStart),
+            "end" if shortcut => motion(Motion::DocumentEnd),
+            "home" => motion(Motion::LineStart),
+            "end" => motion(Motion::LineEnd),
+            "backspace" => Some(Self::Backspace),
+            "delete" => Some(Self::Delete),
+            "enter" => Some(Self::Insert("\n".to_string())),
+            "tab" if !shortcut => Some(Self::Insert("\t".to_string())),
+            "a" if shortcut => Some(Self::SelectAll),
+            "z" if shortcut && extend => Some(Self::Redo),
+            "z" if shortcut => Some(Self::Undo),
+            "y" if shortcut => Some(Self::Redo),
+            "s" if shortcut => Some(Self::Save),
+            _ if shortcut => None,
+            _ => keystroke
+                .key_char
+                .as_ref()
+                .filter(|text| !text.is_empty() && !text.chars().any(char::is_control))
+                .map(|text| Self::Insert(text.clone())),
+        }
+    }
+}
+
+/// One undoable replacement of `removed` by `inserted` at `offset`
+#[derive(Debug, Clone)]
+struct Change {
+    offset: usize,
+    removed: String,
+    inserted: String,
+    cursor_before: usize,
+    anchor_before: Option<usize>,
+}
+
+/// Text being edited, with cursor, selection and undo history
+///
+/// Offsets are bytes into the text and always lie on character
+/// boundaries, never inside a `\r\n` pair.
+#[derive(Debug, Clone)]
+pub struct EditBuffer {
+    text: String,
+    /// Cursor offset
+    cursor: usize,
+    /// Other end of the selection, if any
+    anchor: Option<usize>,
+    /// Column kept while moving up and down through shorter lines
+    goal_column: Option<usize>,
+    undo_stack: Vec<Change>,
+    redo_stack: Vec<Change>,
+    /// Whether the next typed character joins the last undo step
+    coalesce: bool,
+}
+
+impl EditBuffer {
+    /// Create a buffer with the cursor at the start
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            cursor: 0,
+            anchor: None,
+            goal_column: None,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            coalesce: false,
+        }
+    }
+
+    /// Get the text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Get the cursor position
+    pub fn cursor(&self) -> TextPosition {
+        position_at(&self.text, self.cursor)
+    }
+
+    /// Get the selected range, if it is not empty
+    pub fn selection(&self) -> Option<TextRange> {
+        self.selection_offsets().map(|range| {
+            TextRange::new(position_at(&self.text, range.start), position_at(&self.text, range.end))
+        })
+    }
+
+    /// Get the selected text
+    pub fn selected_text(&self) -> &str {
+        self.selection_offsets().map_or("", |range| &self.text[range])
+    }
+
+    /// Check whether there is anything to undo
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Check whether there is anything to redo
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Apply a command, returning whether the text changed
+    ///
+    /// `Save` does nothing here.
+    pub fn apply(&mut self, command: &EditCommand) -> bool {
+        match command {
+            EditCommand::Insert(text) => {
+                self.insert(text);
+                true
+            }
+            EditCommand::Backspace => self.backspace(),
+            EditCommand::Delete => self.delete(),
+            EditCommand::Move { motion, extend } => {
+                self.move_cursor(*motion, *extend);
+                false
+            }
+            EditCommand::SelectAll => {
+                self.select_all();
+                false
+            }
+            EditCommand::Undo => self.undo(),
+            EditCommand::Redo => self.redo(),
+            EditCommand::Save => false,
+        }
+    }
+
+    /// Move the cursor to a position
+    ///
+    /// Returns false if the position is outside the text.
+    pub fn set_cursor(&mut self, position: TextPosition, extend: bool) -> bool {
+        let Some(offset) = byte_offset(&self.text, position) else {
+            return false;
+        };
+        self.goal_column = None;
+        self.move_to(offset, extend);
+        true
+    }
+
+    /// Move the cursor
+    ///
+    /// Without `extend`, moving left or right from a selection collapses
+    /// it to that side.
+    pub fn move_cursor(&mut self, motion: Motion, extend: bool) {
+        let selection = self.selection_offsets().filter(|_| !extend);
+        let target = match motion {
+            Motion::Left => match selection {
+                Some(range) => range.start,
+                None => self.previous_boundary(self.cursor),
+            },
+            Motion::Right => match selection {
+                Some(range) => range.end,
+                None => self.next_boundary(self.cursor),
+            },
+            Motion::Up | Motion::Down => {
+                let position = self.cursor();
+                let column = *self.goal_column.get_or_insert(position.column);
+                let line_count = self.text.matches('\n').count() + 1;
+                let target = match motion {
+                    Motion::Up if position.line == 0 => 0,
+                    Motion::Down if position.line + 1 == line_count => self.text.len(),
+                    _ => {
+                        let line = if motion == Motion::Up {
+                            position.line - 1
+                        } else {
+                            position.line + 1
+                        };
+                        let length = line_length(&self.text, line);
+                        byte_offset(&self.text, TextPosition::new(line, column.min(length)))
+                            .unwrap_or(self.cursor)
+                    }
+                };
+                self.move_to(target, extend);
+                return;
+            }
+            Motion::LineStart => self.text[..self.cursor].rfind('\n').map_or(0, |newline| newline + 1),
+            Motion::LineEnd => {
+                let position = self.cursor();
+                let length = line_length(&self.text, position.line);
+                byte_offset(&self.text, TextPosition::new(position.line, length)).unwrap_or(self.cursor)
+            }
+            Motion::DocumentStart => 0,
+            Motion::DocumentEnd => self.text.len(),
+        };
+        self.goal_column = None;
+        self.move_to(target, extend);
+    }
+
+    /// Select the whole text
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.cursor = self.text.len();
+        self.goal_column = None;
+        self.coalesce = false;
+    }
+
+    /// Replace the selection, or insert at the cursor
+    pub fn insert(&mut self, text: &str) {
+        let range = self.selection_offsets().unwrap_or(self.cursor..self.cursor);
+        // Typing joins the previous undo step until a line break or a move
+        let typing = range.is_empty() && !text.contains('\n');
+        self.replace(range, text, typing);
+    }
+
+    /// Delete the selection or the character before the cursor
+    pub fn backspace(&mut self) -> bool {
+        let range = self
+            .selection_offsets()
+            .unwrap_or_else(|| self.previous_boundary(self.cursor)..self.cursor);
+        if range.is_empty() {
+            return false;
+        }
+        self.replace(range, "", false);
+        true
+    }
+
+    /// Delete the selection or the character after the cursor
+    pub fn delete(&mut self) -> bool {
+        let range = self
+            .selection_offsets()
+            .unwrap_or_else(|| self.cursor..self.next_boundary(self.cursor));
+        if range.is_empty() {
+            return false;
+        }
+        self.replace(range, "", false);
+        true
+    }
+
+    /// Revert the last edit
+    pub fn undo(&mut self) -> bool {
+        let Some(change) = self.undo_stack.pop() else {
+            return false;
+        };
+        let end = change.offset + change.inserted.len();
+        self.text.replace_range(change.offset..end, &change.removed);
+        self.cursor = change.cursor_before;
+        self.anchor = change.anchor_before;
+        self.goal_column = None;
+        self.coalesce = false;
+        self.redo_stack.push(change);
+        true
+    }
+
+    /// Reapply the last undone edit
+    pub fn redo(&mut self) -> bool {
+        let Some(change) = self.redo_stack.pop() else {
+            return false;
+        };
+        let end = change.offset + change.removed.len();
+        self.text.replace_range(change.offset..end, &change.inserted);
+        self.cursor = change.offset + change.inserted.len();
+        self.anchor = None;
+        self.goal_column = None;
+        self.coalesce = false;
+        self.undo_stack.push(change);
+        true
+    }
+
+    /// Replace a range and record the change for undo
+    fn replace(&mut self, range: Range<usize>, text: &str, typing: bool) {
+        let removed = self.text[range.clone()].to_string();
+        self.text.replace_range(range.clone(), text);
+        self.redo_stack.clear();
+
+        let joined = typing && self.coalesce && self.undo_stack.last().is_some_and(|last| {
+            last.removed.is_empty() && last.offset + last.inserted.len() == range.start
+        });
+        if joined {
+            if let Some(last) = self.undo_stack.last_mut() {
+                last.inserted.push_str(text);
+            }
+        } else {
+            self.undo_stack.push(Change {
+                offset: range.start,
+                removed,
+                inserted: text.to_string(),
+                cursor_before: self.cursor,
+                anchor_before: self.anchor,
+            });
+            if self.undo_stack.len() > MAX_UNDO_STEPS {
+                self.undo_stack.remove(0);
+            }
+        }
+
+        self.cursor = range.start + text.len();
+        self.anchor = None;
+        self.goal_column = None;
+        self.coalesce = typing;
+    }
+
+    /// Move the cursor to an offset, keeping or dropping the selection
+    fn move_to(&mut self, offset: usize, extend: bool) {
+        if extend {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = offset;
+        self.coalesce = false;
+    }
+
+    /// Ordered selection offsets, if the selection is not empty
+    fn selection_offsets(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor.filter(|&anchor| anchor != self.cursor)?;
+        Some(anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
+
+    /// Offset of the character boundary before an offset
+    fn previous_boundary(&self, offset: usize) -> usize {
+        let before = &self.text[..offset];
+        if before.ends_with("\r\n") {
+            return offset - 2;
+        }
+        before.chars().next_back().map_or(0, |c| offset - c.len_utf8())
+    }
+
+    /// Offset of the character boundary after an offset
+    fn next_boundary(&self, offset: usize) -> usize {
+        let after = &self.text[offset..];
+        if after.starts_with("\r\n") {
+            return offset + 2;
+        }
+        after.chars().next().map_or(offset, |c| offset + c.len_utf8())
+    }
+}
+
+/// Length of a line in characters, excluding its line ending
+fn line_length(text: &str, line: usize) -> usize {
+    let line = text.split('\n').nth(line).unwrap_or_default();
+    line.strip_suffix('\r').unwrap_or(line).chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keystroke(source: &str) -> Keystroke {
+        Keystroke::parse(source).unwrap()
+    }
+
+    #[test]
+    fn test_typing_and_undo() {
+        let mut buffer = EditBuffer::new("fn main() {}\n");
+        buffer.move_cursor(Motion::LineEnd, false);
+        buffer.move_cursor(Motion::Left, false);
+        for c in ["r", "u", "n"] {
+            buffer.insert(c);
+        }
+        buffer.insert("\n");
+        assert_eq!(buffer.text(), "fn main() {run\n}\n");
+        assert_eq!(buffer.cursor(), TextPosition::new(1, 0));
+
+        // The typed word is one undo step, the line break another
+        assert!(buffer.undo());
+        assert_eq!(buffer.text(), "fn main() {run}\n");
+        assert!(buffer.undo());
+        assert_eq!(buffer.text(), "fn main() {}\n");
+        assert_eq!(buffer.cursor(), TextPosition::new(0, 11));
+        assert!(!buffer.undo());
+
+        assert!(buffer.redo());
+        assert!(buffer.redo());
+        assert_eq!(buffer.text(), "fn main() {run\n}\n");
+        assert!(!buffer.can_redo());
+
+        // A new edit drops the redo history
+        buffer.undo();
+        buffer.backspace();
+        assert!(!buffer.can_redo());
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut buffer = EditBuffer::new("héllo\r\nworld");
+        buffer.move_cursor(Motion::Right, false);
+        buffer.move_cursor(Motion::Down, true);
+        assert_eq!(
+            buffer.selection(),
+            Some(TextRange::new(TextPosition::new(0, 1), TextPosition::new(1, 1)))
+        );
+        assert_eq!(buffer.selected_text(), "éllo\r\nw");
+
+        buffer.insert("ey, ");
+        assert_eq!(buffer.text(), "hey, orld");
+        assert!(buffer.selection().is_none());
+
+        buffer.undo();
+        assert_eq!(buffer.selected_text(), "éllo\r\nw");
+
+        // Moving without extending collapses the selection to that side
+        buffer.move_cursor(Motion::Left, false);
+        assert_eq!(buffer.cursor(), TextPosition::new(0, 1));
+
+        buffer.select_all();
+        assert!(buffer.delete());
+        assert_eq!(buffer.text(), "");
+        assert!(!buffer.delete());
+    }
+
+    #[test]
+    fn test_cursor_motion() {
+        let mut buffer = EditBuffer::new("long line\r\nab\nanother line");
+        buffer.move_cursor(Motion::LineEnd, false);
+        assert_eq!(buffer.cursor(), TextPosition::new(0, 9));
+
+        // The goal column survives a shorter line
+        buffer.move_cursor(Motion::Down, false);
+        assert_eq!(buffer.cursor(), TextPosition::new(1, 2));
+        buffer.move_cursor(Motion::Down, false);
+        assert_eq!(buffer.cursor(), TextPosition::new(2, 9));
+        buffer.move_cursor(Motion::Down, false);
+        assert_eq!(buffer.cursor(), TextPosition::new(2, 12));
+
+        buffer.move_cursor(Motion::LineStart, false);
+        buffer.move_cursor(Motion::Left, false);
+        assert_eq!(buffer.cursor(), TextPosition::new(1, 2));
+        // CRLF is stepped over as one character
+        buffer.move_cursor(Motion::Up, false);
+        buffer.move_cursor(Motion::LineEnd, false);
+        buffer.move_cursor(Motion::Right, false);
+        assert_eq!(buffer.cursor(), TextPosition::new(1, 0));
+        buffer.backspace();
+        assert_eq!(buffer.text(), "long lineab\nanother line");
+
+        assert!(buffer.set_cursor(TextPosition::new(1, 3), false));
+        assert!(!buffer.set_cursor(TextPosition::new(5, 0), false));
+        buffer.move_cursor(Motion::DocumentStart, true);
+        assert_eq!(buffer.selected_text(), "long lineab\nano");
+    }
+
+    #[test]
+    fn test_commands_from_keystrokes() {
+        let command = |source| EditCommand::from_keystroke(&keystroke(source));
+
+        assert_eq!(command("ctrl-z"), Some(EditCommand::Undo));
+        assert_eq!(command("cmd-shift-z"), Some(EditCommand::Redo));
+        assert_eq!(command("ctrl-s"), Some(EditCommand::Save));
+        assert_eq!(
+            command("shift-home"),
+            Some(EditCommand::Move {
+                motion: Motion::LineStart,
+                extend: true,
+            })
+        );
+        assert_eq!(command("enter"), Some(EditCommand::Insert("\n".to_string())));
+        assert_eq!(command("ctrl-q"), None);
+
+        let typed = Keystroke {
+            key_char: Some("é".to_string()),
+            ..keystroke("e")
+        };
+        assert_eq!(
+            EditCommand::from_keystroke(&typed),
+            Some(EditCommand::Insert("é".to_string()))
+        );
+    }
+}
@@ -6,7 +6,9 @@
 use std::collections::{HashMap, VecDeque};
 use std::hash::Hash;
 
+use super::diff::DiffOptions;
 use super::syntax::Token;
+use super::types::DiffContent;
 
 /// Generic LRU cache
 #[derive(Debug)]
@@ -141,6 +143,8 @@ pub struct ArtifactCache {
     line_cache: LruCache<LineCacheKey, CachedLine>,
     /// Max lines to cache per artifact
     lines_per_artifact: usize,
+    /// Last computed diff per artifact, with the options used
+    diffs: HashMap<String, (DiffOptions, DiffContent)>,
 }
 
 impl Default for ArtifactCache {
@@ -156,6 +160,7 @@ impl ArtifactCache {
             // Cache up to 10000 lines across all artifacts
             line_cache: LruCache::new(10000),
             lines_per_artifact: 5000,
+            diffs: HashMap::new(),
         }
     }
 
@@ -164,6 +169,7 @@ impl ArtifactCache {
         Self {
             line_cache: LruCache::new(line_capacity),
             lines_per_artifact,
+            diffs: HashMap::new(),
         }
     }
 
@@ -186,8 +192,31 @@ impl ArtifactCache {
         self.line_cache.insert(key, CachedLine { tokens, has_changes });
     }
 
+    /// Get the diff between two versions of an artifact
+    ///
+    /// The diff is computed once and reused until either version or the
+    /// options change.
+    pub fn diff(
+        &mut self,
+        artifact_id: &str,
+        original: &str,
+        modified: &str,
+        options: &DiffOptions,
+    ) -> &DiffContent {
+        let stale = self.diffs.get(artifact_id).is_none_or(|(cached_options, diff)| {
+            cached_options != options || diff.original != original || diff.modified != modified
+        });
+        if stale {
+            let diff = DiffContent::with_options(original, modified, options);
+            self.diffs.insert(artifact_id.to_string(), (options.clone(), diff));
+        }
+        &self.diffs[artifact_id].1
+    }
+
     /// Invalidate all cached lines for an artifact
     pub fn invalidate_artifact(&mut self, artifact_id: &str) {
+        self.diffs.remove(artifact_id);
+
         // This is O(n) but should be infrequent
         let keys_to_remove: Vec<_> = self
             .line_cache
@@ -213,6 +242,7 @@ impl ArtifactCache {
     /// Clear entire cache
     pub fn clear(&mut self) {
         self.line_cache.clear();
+        self.diffs.clear();
     }
 }
 
@@ -391,6 +421,20 @@ mod tests {
         assert!(cache.get_line("test", 0, "different").is_none()); // Different content hash
     }
 
+    #[test]
+    fn test_diff_cache() {
+        let mut cache = ArtifactCache::new();
+        let options = DiffOptions::new();
+
+        let unified = cache.diff("a", "one\n", "two\n", &options).unified.clone();
+        assert!(unified.contains("+two"));
+        assert_eq!(cache.diff("a", "one\n", "two\n", &options).unified, unified);
+        assert!(cache.diff("a", "one\n", "three\n", &options).unified.contains("+three"));
+
+        let options = options.with_context_lines(0);
+        assert_eq!(cache.diff("a", "x\none\n", "x\ntwo\n", &options).hunks[0].lines.len(), 2);
+    }
+
     #[test]
     fn test_invalidate_artifact() {
         let mut cache = ArtifactCache::new();
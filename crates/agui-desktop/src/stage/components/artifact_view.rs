@@ -5,15 +5,21 @@
 use gpui::{div, prelude::*, px, rgb, Div};
 
 use super::colors;
+use super::conflict_view::{render_conflict_view, OnResolveConflict};
 use super::diff_view::{render_diff_view, DiffViewConfig};
 use super::tabs::{render_empty_tabs, render_tab_bar_simple, TabItem};
+use super::terminal_view::render_terminal_view;
 use super::text_view::{render_text_view, TextViewConfig};
+use crate::protocol::TextPosition;
+use crate::stage::ansi::TerminalScreen;
 use crate::stage::state::StageState;
 use crate::stage::syntax::SyntaxHighlighter;
 use crate::stage::types::{Artifact, ArtifactContent, ContentType, ViewMode};
 
 /// Render the complete Stage pane with tabs and artifact content
-pub fn render_stage_pane(state: &mut StageState) -> Div {
+///
+/// `on_resolve` handles the actions on merge conflicts.
+pub fn render_stage_pane(state: &mut StageState, on_resolve: Option<OnResolveConflict>) -> Div {
     let has_artifacts = state.has_artifacts();
 
     div()
@@ -32,16 +38,17 @@ pub fn render_stage_pane(state: &mut StageState) -> Div {
                 .collect();
 
             el.child(render_tab_bar_simple(&tabs))
-                .child(render_active_artifact(state))
+                .child(render_active_artifact(state, on_resolve))
         })
 }
 
 /// Render the active artifact content
-fn render_active_artifact(state: &mut StageState) -> Div {
+fn render_active_artifact(state: &mut StageState, on_resolve: Option<OnResolveConflict>) -> Div {
     let view_mode = state.view_mode();
     let show_line_numbers = state.show_line_numbers();
     let font_size = state.font_size();
     let scroll_position = state.scroll_position();
+    let diff_options = state.diff_options().clone();
 
     // Get artifact info before mutable borrow
     let artifact_info = state.active_artifact().map(|a| {
@@ -52,29 +59,65 @@ fn render_active_artifact(state: &mut StageState) -> Div {
             a.read_only,
             a.content.clone(),
             a.title.clone(),
+            a.terminal_size(),
+            a.is_editable(),
         )
     });
 
-    if let Some((_id, content_type, language, read_only, content, title)) = artifact_info {
+    if let Some((id, content_type, language, read_only, content, title, terminal_size, editable)) = artifact_info {
         // Calculate visible lines based on scroll position
         let line_height = font_size * 1.4;
         let first_visible_line = (scroll_position / line_height) as usize;
 
         match content {
+            ArtifactContent::Text(ref text_content) if content_type == ContentType::Terminal => {
+                let (rows, cols) = terminal_size;
+                let screen = TerminalScreen::from_output(&text_content.content, rows, cols);
+                let config = TextViewConfig {
+                    font_size,
+                    visible_lines: rows,
+                    read_only: true,
+                    ..Default::default()
+                };
+
+                div()
+                    .flex()
+                    .flex_col()
+                    .flex_1()
+                    .overflow_hidden()
+                    .child(render_artifact_toolbar(&title, content_type, read_only, view_mode))
+                    .child(render_terminal_view(&screen, &config))
+            }
             ArtifactContent::Text(ref text_content) => {
                 // Check if we should show diff view
                 let should_show_diff = matches!(view_mode, ViewMode::InlineChanges | ViewMode::Unified | ViewMode::SideBySide)
                     && text_content.previous_content.is_some();
 
-                if should_show_diff {
+                if let Some(merge) = state.merge(&id) {
+                    // Agent update conflicting with unsaved edits
+                    let config = TextViewConfig {
+                        font_size,
+                        ..Default::default()
+                    };
+
+                    div()
+                        .flex()
+                        .flex_col()
+                        .flex_1()
+                        .overflow_hidden()
+                        .child(render_artifact_toolbar(&title, content_type, read_only, view_mode))
+                        .child(render_conflict_view(&id, merge, &config, on_resolve))
+                } else if should_show_diff {
                     let original = text_content.previous_content.as_deref().unwrap_or("");
                     let modified = &text_content.content;
 
-                    let diff_content = crate::stage::types::DiffContent::new(original, modified);
+                    let diff_content = state.artifact_diff(&id, original, modified);
                     let config = DiffViewConfig {
                         view_mode,
                         show_line_numbers,
                         font_size,
+                        context_lines: diff_options.context_lines,
+                        algorithm: diff_options.algorithm,
                         first_visible_line,
                         ..Default::default()
                     };
@@ -85,15 +128,24 @@ fn render_active_artifact(state: &mut StageState) -> Div {
                         .flex_1()
                         .overflow_hidden()
                         .child(render_artifact_toolbar(&title, content_type, read_only, view_mode))
-                        .child(render_diff_view(&diff_content, &config))
+                        .child(render_diff_view(diff_content, &config))
                 } else {
-                    // Normal text view
+                    // Normal text view, with the cursor and selection of editable artifacts
                     let highlighter = language.as_ref().map(|l| SyntaxHighlighter::new(l));
+                    let (cursor, selection) = match state.edit_buffer(&id) {
+                        Some(buffer) => (Some(buffer.cursor()), buffer.selection()),
+                        None if editable => (Some(TextPosition::new(0, 0)), None),
+                        None => (None, None),
+                    };
                     let config = TextViewConfig {
                         show_line_numbers,
                         font_size,
                         first_visible_line,
                         read_only,
+                        cursor_line: cursor.map(|c| c.line),
+                        cursor_column: cursor.map(|c| c.column),
+                        selection: selection
+                            .map(|s| (s.start.line, s.start.column, s.end.line, s.end.column)),
                         ..Default::default()
                     };
 
@@ -116,6 +168,8 @@ fn render_active_artifact(state: &mut StageState) -> Div {
                     view_mode,
                     show_line_numbers,
                     font_size,
+                    context_lines: diff_options.context_lines,
+                    algorithm: diff_options.algorithm,
                     first_visible_line,
                     ..Default::default()
                 };
@@ -0,0 +1,151 @@
+//! Conflict View Component
+//!
+//! Renders an agent update merged into the user's edits while conflicts
+//! remain: each conflict shows the user's and the agent's lines with actions
+//! to keep either or both. Unchanged lines away from conflicts are folded.
+
+use std::rc::Rc;
+
+use gpui::{div, prelude::*, px, rgb, App, Div, Window};
+
+use super::colors;
+use super::text_view::TextViewConfig;
+use crate::stage::merge::{Merge, MergeChunk, Resolution};
+
+/// Unchanged lines shown before and after each conflict
+const CONTEXT_LINES: usize = 3;
+
+/// Callback resolving a conflict, given the artifact ID and the conflict's index
+pub type OnResolveConflict = Rc<dyn Fn(&str, usize, Resolution, &mut Window, &mut App)>;
+
+/// Render a merge with its unresolved conflicts
+pub fn render_conflict_view(
+    id: &str,
+    merge: &Merge,
+    config: &TextViewConfig,
+    on_resolve: Option<OnResolveConflict>,
+) -> Div {
+    let line_height = config.line_height_px();
+    let line = |text: &str, bg: Option<u32>| {
+        div()
+            .h(line_height)
+            .px_2()
+            .flex()
+            .items_center()
+            .when_some(bg, |el, bg| el.bg(rgb(bg)))
+            .child(text.trim_end_matches(['\n', '\r']).replace('\t', &" ".repeat(config.tab_width)))
+    };
+    let label = |text: &'static str| {
+        div()
+            .px_2()
+            .text_xs()
+            .text_color(rgb(colors::LINE_NUMBER))
+            .child(text)
+    };
+
+    let chunks = merge.chunks();
+    let total = merge.conflict_count();
+    let mut index = 0;
+    let mut rows = Vec::new();
+    for (position, chunk) in chunks.iter().enumerate() {
+        match chunk {
+            MergeChunk::Resolved(lines) => {
+                let head = if position > 0 { CONTEXT_LINES } else { 0 };
+                let tail = if position + 1 < chunks.len() { CONTEXT_LINES } else { 0 };
+                if lines.len() > head + tail {
+                    rows.extend(lines[..head].iter().map(|text| line(text, None)));
+                    rows.push(render_fold(lines.len() - head - tail));
+                    rows.extend(lines[lines.len() - tail..].iter().map(|text| line(text, None)));
+                } else {
+                    rows.extend(lines.iter().map(|text| line(text, None)));
+                }
+            }
+            MergeChunk::Conflict(conflict) => {
+                rows.push(render_conflict_header(id, index, total, on_resolve.clone()));
+                rows.push(label("Yours"));
+                rows.extend(
+                    conflict
+                        .mine
+                        .iter()
+                        .map(|text| line(text, Some(colors::CONFLICT_MINE_BG))),
+                );
+                rows.push(label("Agent"));
+                rows.extend(
+                    conflict
+                        .theirs
+                        .iter()
+                        .map(|text| line(text, Some(colors::CONFLICT_THEIRS_BG))),
+                );
+                index += 1;
+            }
+        }
+    }
+
+    div()
+        .flex()
+        .flex_col()
+        .flex_1()
+        .w_full()
+        .bg(rgb(colors::EDITOR_BG))
+        .overflow_hidden()
+        .font_family("monospace")
+        .text_size(px(config.font_size))
+        .text_color(rgb(0xcccccc))
+        .children(rows)
+}
+
+/// Row standing in for folded unchanged lines
+fn render_fold(count: usize) -> Div {
+    div()
+        .px_2()
+        .py_1()
+        .text_xs()
+        .text_color(rgb(colors::LINE_NUMBER))
+        .child(format!("⋯ {} unchanged lines", count))
+}
+
+/// Header of a conflict with its resolution actions
+fn render_conflict_header(
+    id: &str,
+    index: usize,
+    total: usize,
+    on_resolve: Option<OnResolveConflict>,
+) -> Div {
+    let action = |text: &'static str, resolution: Resolution| {
+        div()
+            .px_2()
+            .py_px()
+            .rounded_sm()
+            .bg(rgb(0x0e639c))
+            .text_xs()
+            .text_color(rgb(0xffffff))
+            .cursor_pointer()
+            .child(text)
+            .when_some(on_resolve.clone(), |el, on_resolve| {
+                let id = id.to_string();
+                el.on_mouse_down(gpui::MouseButton::Left, move |_, window, cx| {
+                    on_resolve(&id, index, resolution, window, cx);
+                })
+            })
+    };
+
+    div()
+        .flex()
+        .flex_row()
+        .items_center()
+        .gap_2()
+        .mt_1()
+        .px_2()
+        .py_1()
+        .bg(rgb(colors::CONFLICT_HEADER_BG))
+        .child(
+            div()
+                .flex_1()
+                .text_xs()
+                .text_color(rgb(0xcca700))
+                .child(format!("Conflict {} of {} with the agent's update", index + 1, total)),
+        )
+        .child(action("Accept mine", Resolution::Mine))
+        .child(action("Accept theirs", Resolution::Theirs))
+        .child(action("Accept both", Resolution::Both))
+}
@@ -1,11 +1,17 @@
 //! Diff View Component
 //!
-//! Renders diff content in unified or side-by-side format.
+//! Renders diff content in unified or side-by-side format, highlighting
+//! the changed words or characters of each changed line.
+
+use std::ops::Range;
 
 use gpui::{div, prelude::*, px, rgb, Div, Pixels};
 
 use super::colors;
-use crate::stage::diff::{diff_stats, DiffStats};
+use crate::stage::diff::{
+    diff_stats, inline_changes, paired_line, DiffAlgorithm, DiffOptions, DiffStats,
+    InlineGranularity, DEFAULT_CONTEXT_LINES,
+};
 use crate::stage::types::{DiffContent, DiffHunk, DiffLine, DiffLineType, ViewMode};
 
 /// Configuration for diff view rendering
@@ -21,6 +27,10 @@ pub struct DiffViewConfig {
     pub line_height: f32,
     /// Context lines to show around changes
     pub context_lines: usize,
+    /// Line matching algorithm
+    pub algorithm: DiffAlgorithm,
+    /// Unit of intra-line change highlighting
+    pub inline_granularity: InlineGranularity,
     /// First visible line (for virtualization)
     pub first_visible_line: usize,
     /// Number of visible lines
@@ -34,7 +44,9 @@ impl Default for DiffViewConfig {
             show_line_numbers: true,
             font_size: 13.0,
             line_height: 1.4,
-            context_lines: 3,
+            context_lines: DEFAULT_CONTEXT_LINES,
+            algorithm: DiffAlgorithm::default(),
+            inline_granularity: InlineGranularity::default(),
             first_visible_line: 0,
             visible_lines: 50,
         }
@@ -45,6 +57,61 @@ impl DiffViewConfig {
     pub fn line_height_px(&self) -> Pixels {
         px(self.font_size * self.line_height)
     }
+
+    /// Options for computing the diff
+    pub fn diff_options(&self) -> DiffOptions {
+        DiffOptions::new()
+            .with_algorithm(self.algorithm)
+            .with_context_lines(self.context_lines)
+    }
+}
+
+/// A hunk with the part of it inside the visible window
+struct VisibleHunk<'a> {
+    hunk: &'a DiffHunk,
+    /// Whether the hunk header row is visible
+    show_header: bool,
+    /// Visible line indices
+    lines: Range<usize>,
+}
+
+/// Hunks within the visible rows, counting `header_rows` rows before each hunk's lines
+fn visible_hunks<'a>(hunks: &'a [DiffHunk], config: &DiffViewConfig, header_rows: usize) -> Vec<VisibleHunk<'a>> {
+    let first = config.first_visible_line;
+    let last = first + config.visible_lines;
+
+    let mut visible = Vec::new();
+    let mut row = 0;
+    for hunk in hunks {
+        let header = row;
+        let lines_start = header + header_rows;
+        row = lines_start + hunk.lines.len();
+        if row <= first {
+            continue;
+        }
+        if header >= last {
+            break;
+        }
+        let clamp = |row: usize| row.saturating_sub(lines_start).min(hunk.lines.len());
+        visible.push(VisibleHunk {
+            hunk,
+            show_header: header_rows > 0 && header >= first,
+            lines: clamp(first)..clamp(last),
+        });
+    }
+    visible
+}
+
+/// Changed byte ranges of a line, relative to its paired line
+fn line_changes(lines: &[DiffLine], index: usize, granularity: InlineGranularity) -> Vec<Range<usize>> {
+    let Some(partner) = paired_line(lines, index) else {
+        return Vec::new();
+    };
+    let line = &lines[index];
+    match line.line_type {
+        DiffLineType::Deletion => inline_changes(&line.content, &lines[partner].content, granularity).0,
+        _ => inline_changes(&lines[partner].content, &line.content, granularity).1,
+    }
 }
 
 /// Render a diff view
@@ -80,8 +147,8 @@ fn render_unified_diff(diff: &DiffContent, config: &DiffViewConfig) -> Div {
                 .flex_1()
                 .overflow_hidden()
                 .children(
-                    diff.hunks.iter().map(|hunk| {
-                        render_unified_hunk(hunk, config.show_line_numbers, line_height)
+                    visible_hunks(&diff.hunks, config, 1).into_iter().map(|visible| {
+                        render_unified_hunk(&visible, config, line_height)
                     })
                 )
         )
@@ -127,8 +194,8 @@ fn render_side_by_side_diff(diff: &DiffContent, config: &DiffViewConfig) -> Div
                                 .child("Original")
                         )
                         .children(
-                            diff.hunks.iter().map(|hunk| {
-                                render_side_hunk(hunk, true, config.show_line_numbers, line_height)
+                            visible_hunks(&diff.hunks, config, 0).into_iter().map(|visible| {
+                                render_side_hunk(&visible, true, config, line_height)
                             })
                         )
                 )
@@ -150,8 +217,8 @@ fn render_side_by_side_diff(diff: &DiffContent, config: &DiffViewConfig) -> Div
                                 .child("Modified")
                         )
                         .children(
-                            diff.hunks.iter().map(|hunk| {
-                                render_side_hunk(hunk, false, config.show_line_numbers, line_height)
+                            visible_hunks(&diff.hunks, config, 0).into_iter().map(|visible| {
+                                render_side_hunk(&visible, false, config, line_height)
                             })
                         )
                 )
@@ -199,15 +266,16 @@ fn render_diff_stats_header(stats: &DiffStats) -> Div {
         )
 }
 
-/// Render a single hunk in unified format
-fn render_unified_hunk(hunk: &DiffHunk, show_line_numbers: bool, line_height: Pixels) -> Div {
+/// Render the visible part of a hunk in unified format
+fn render_unified_hunk(visible: &VisibleHunk, config: &DiffViewConfig, line_height: Pixels) -> Div {
+    let hunk = visible.hunk;
     div()
         .flex()
         .flex_col()
         .w_full()
         .mb_2()
         // Hunk header
-        .child(
+        .when(visible.show_header, |el| el.child(
             div()
                 .w_full()
                 .px_2()
@@ -223,17 +291,23 @@ fn render_unified_hunk(hunk: &DiffHunk, show_line_numbers: bool, line_height: Pi
                     hunk.new_start + 1,
                     hunk.new_count
                 ))
-        )
+        ))
         // Lines
         .children(
-            hunk.lines.iter().map(|line| {
-                render_unified_line(line, show_line_numbers, line_height)
+            visible.lines.clone().map(|index| {
+                let changes = line_changes(&hunk.lines, index, config.inline_granularity);
+                render_unified_line(&hunk.lines[index], &changes, config.show_line_numbers, line_height)
             })
         )
 }
 
 /// Render a single line in unified diff
-fn render_unified_line(line: &DiffLine, show_line_numbers: bool, line_height: Pixels) -> Div {
+fn render_unified_line(
+    line: &DiffLine,
+    changes: &[Range<usize>],
+    show_line_numbers: bool,
+    line_height: Pixels,
+) -> Div {
     let (bg_color, prefix, text_color) = match line.line_type {
         DiffLineType::Addition => (colors::DIFF_ADD_BG, "+", 0x4ec9b0),
         DiffLineType::Deletion => (colors::DIFF_DEL_BG, "-", 0xf14c4c),
@@ -316,33 +390,61 @@ fn render_unified_line(line: &DiffLine, show_line_numbers: bool, line_height: Pi
                 .child(prefix)
         )
         // Content
-        .child(
+        .child(render_line_content(line, changes, text_color).pl_1())
+}
+
+/// Render the content of a diff line, marking its changed ranges
+fn render_line_content(line: &DiffLine, changes: &[Range<usize>], text_color: u32) -> Div {
+    let change_bg = match line.line_type {
+        DiffLineType::Deletion => colors::DIFF_DEL_WORD_BG,
+        _ => colors::DIFF_ADD_WORD_BG,
+    };
+
+    // Alternate unchanged and changed segments
+    let mut segments = Vec::new();
+    let mut offset = 0;
+    for range in changes {
+        if range.start > offset {
+            segments.push((offset..range.start, false));
+        }
+        segments.push((range.clone(), true));
+        offset = range.end;
+    }
+    if offset < line.content.len() || segments.is_empty() {
+        segments.push((offset..line.content.len(), false));
+    }
+
+    div()
+        .flex_1()
+        .h_full()
+        .flex()
+        .flex_row()
+        .items_center()
+        .text_sm()
+        .font_family("monospace")
+        .text_color(rgb(text_color))
+        .children(segments.into_iter().map(|(range, changed)| {
             div()
-                .flex_1()
-                .h_full()
-                .flex()
-                .items_center()
-                .pl_1()
-                .text_sm()
-                .font_family("monospace")
-                .text_color(rgb(text_color))
-                .child(line.content.clone())
-        )
+                .when(changed, |el| el.bg(rgb(change_bg)))
+                .child(line.content[range].to_string())
+        }))
 }
 
-/// Render a hunk for one side of side-by-side view
+/// Render the visible part of a hunk for one side of side-by-side view
 fn render_side_hunk(
-    hunk: &DiffHunk,
+    visible: &VisibleHunk,
     is_original: bool,
-    show_line_numbers: bool,
+    config: &DiffViewConfig,
     line_height: Pixels,
 ) -> Div {
+    let lines = &visible.hunk.lines;
     div()
         .flex()
         .flex_col()
         .w_full()
         .children(
-            hunk.lines.iter().filter_map(|line| {
+            visible.lines.clone().filter_map(|index| {
+                let line = &lines[index];
                 // Filter lines based on which side we're rendering
                 let should_show = match line.line_type {
                     DiffLineType::Addition => !is_original,
@@ -352,7 +454,8 @@ fn render_side_hunk(
                 };
 
                 if should_show {
-                    Some(render_side_line(line, is_original, show_line_numbers, line_height))
+                    let changes = line_changes(lines, index, config.inline_granularity);
+                    Some(render_side_line(line, &changes, is_original, config.show_line_numbers, line_height))
                 } else {
                     // Add empty placeholder to keep sides aligned
                     if (is_original && line.line_type == DiffLineType::Addition)
@@ -370,6 +473,7 @@ fn render_side_hunk(
 /// Render a line for side-by-side view
 fn render_side_line(
     line: &DiffLine,
+    changes: &[Range<usize>],
     is_original: bool,
     show_line_numbers: bool,
     line_height: Pixels,
@@ -412,18 +516,7 @@ fn render_side_line(
             )
         })
         // Content
-        .child(
-            div()
-                .flex_1()
-                .h_full()
-                .flex()
-                .items_center()
-                .pl_2()
-                .text_sm()
-                .font_family("monospace")
-                .text_color(rgb(text_color))
-                .child(line.content.clone())
-        )
+        .child(render_line_content(line, changes, text_color).pl_2())
 }
 
 /// Render an empty placeholder line (for alignment)
@@ -440,7 +533,7 @@ pub fn render_inline_changes(
     modified: &str,
     config: &DiffViewConfig,
 ) -> Div {
-    let diff = crate::stage::types::DiffContent::new(original, modified);
+    let diff = DiffContent::with_options(original, modified, &config.diff_options());
     render_unified_diff(&diff, config)
 }
 
@@ -455,6 +548,31 @@ mod tests {
         assert!(config.show_line_numbers);
     }
 
+    #[test]
+    fn test_visible_hunks() {
+        let diff = DiffContent::new(
+            (0..40).map(|i| format!("line{}\n", i)).collect::<String>(),
+            (0..40)
+                .map(|i| if i % 20 == 5 { "changed\n".to_string() } else { format!("line{}\n", i) })
+                .collect::<String>(),
+        );
+        assert_eq!(diff.hunks.len(), 2);
+        assert_eq!(diff.hunks[0].lines.len(), 8);
+
+        // Rows 0-8 are the first hunk's header and lines, 9-17 the second's
+        let config = DiffViewConfig {
+            first_visible_line: 5,
+            visible_lines: 6,
+            ..Default::default()
+        };
+        let visible = visible_hunks(&diff.hunks, &config, 1);
+        assert_eq!(visible.len(), 2);
+        assert_eq!((visible[0].show_header, visible[0].lines.clone()), (false, 4..8));
+        assert_eq!((visible[1].show_header, visible[1].lines.clone()), (true, 0..1));
+
+        assert_eq!(visible_hunks(&diff.hunks, &config, 0)[0].lines, 5..8);
+    }
+
     #[test]
     fn test_line_height() {
         let config = DiffViewConfig {
@@ -3,13 +3,17 @@
 //! UI components for rendering artifacts in the Stage pane.
 
 pub mod artifact_view;
+pub mod conflict_view;
 pub mod diff_view;
 pub mod tabs;
+pub mod terminal_view;
 pub mod text_view;
 
 pub use artifact_view::*;
+pub use conflict_view::*;
 pub use diff_view::*;
 pub use tabs::*;
+pub use terminal_view::*;
 pub use text_view::*;
 
 /// Common colors for Stage components (VS Code dark theme)
@@ -54,10 +58,20 @@ pub mod colors {
     pub const DIFF_DEL_BG: u32 = 0x4a2d2d;
     /// Diff deletion line number background
     pub const DIFF_DEL_GUTTER: u32 = 0x6b3d3d;
+    /// Changed words within an added line
+    pub const DIFF_ADD_WORD_BG: u32 = 0x3f7a3f;
+    /// Changed words within a deleted line
+    pub const DIFF_DEL_WORD_BG: u32 = 0x7a3a3a;
     /// Diff context (unchanged) - slightly dimmed
     pub const DIFF_CONTEXT: u32 = 0x1e1e1e;
     /// Hunk header background
     pub const DIFF_HUNK_HEADER: u32 = 0x2d2d5a;
+    /// Conflict header background
+    pub const CONFLICT_HEADER_BG: u32 = 0x3c3c3c;
+    /// Background of the user's side of a conflict
+    pub const CONFLICT_MINE_BG: u32 = 0x2d4a2d;
+    /// Background of the agent's side of a conflict
+    pub const CONFLICT_THEIRS_BG: u32 = 0x263d5a;
     /// Scrollbar track
     pub const SCROLLBAR_TRACK: u32 = 0x1e1e1e;
     /// Scrollbar thumb
@@ -0,0 +1,62 @@
+//! Terminal View Component
+//!
+//! Renders terminal session output with ANSI colors, following the
+//! bottom of the output like a live terminal.
+
+use gpui::{div, prelude::*, rgb, Div};
+
+use super::colors;
+use super::text_view::TextViewConfig;
+use crate::stage::ansi::{StyledSpan, TerminalScreen};
+
+/// Render a terminal screen, showing the last `config.visible_lines` lines
+pub fn render_terminal_view(screen: &TerminalScreen, config: &TextViewConfig) -> Div {
+    let lines = screen.lines();
+    let (cursor_row, _) = screen.cursor();
+
+    // Follow the cursor, or the last line with output if it is further down
+    let last_line = lines
+        .iter()
+        .rposition(|spans| !spans.is_empty())
+        .map_or(cursor_row, |row| row.max(cursor_row));
+    let end = (last_line + 1).min(lines.len());
+    let start = end.saturating_sub(config.visible_lines);
+    let line_height = config.line_height_px();
+
+    div()
+        .flex()
+        .flex_col()
+        .flex_1()
+        .w_full()
+        .h_full()
+        .justify_end()
+        .bg(rgb(colors::EDITOR_BG))
+        .overflow_hidden()
+        .px_2()
+        .children(
+            lines[start..end].iter().map(|spans| {
+                div()
+                    .h(line_height)
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .font_family("monospace")
+                    .text_sm()
+                    .when(spans.is_empty(), |el| el.child(" "))
+                    .children(spans.iter().map(render_span))
+            })
+        )
+}
+
+/// Render a run of styled terminal text
+fn render_span(span: &StyledSpan) -> Div {
+    let (fg, bg) = span.style.colors();
+    div()
+        .text_color(rgb(fg))
+        .when_some(bg, |el, bg| el.bg(rgb(bg)))
+        .when(span.style.bold, |el| el.font_weight(gpui::FontWeight::BOLD))
+        .when(span.style.dim, |el| el.opacity(0.6))
+        .when(span.style.italic, |el| el.italic())
+        .when(span.style.underline, |el| el.underline())
+        .child(span.text.clone())
+}
@@ -22,6 +22,8 @@ pub struct TextViewConfig {
     pub word_wrap: bool,
     /// Current cursor line (0-indexed, None for no cursor)
     pub cursor_line: Option<usize>,
+    /// Cursor column in characters on the cursor line (None to only highlight the line)
+    pub cursor_column: Option<usize>,
     /// Selection range (start_line, start_col, end_line, end_col)
     pub selection: Option<(usize, usize, usize, usize)>,
     /// First visible line (for virtualization)
@@ -41,6 +43,7 @@ impl Default for TextViewConfig {
             tab_width: 4,
             word_wrap: false,
             cursor_line: None,
+            cursor_column: None,
             selection: None,
             first_visible_line: 0,
             visible_lines: 50,
@@ -55,6 +58,17 @@ impl TextViewConfig {
         px(self.font_size * self.line_height)
     }
 
+    /// Selected columns on a line as (start, end), with None for an end past the line break
+    pub fn selection_on_line(&self, line: usize) -> Option<(usize, Option<usize>)> {
+        let (start_line, start_col, end_line, end_col) = self.selection?;
+        if line < start_line || line > end_line {
+            return None;
+        }
+        let start = if line == start_line { start_col } else { 0 };
+        let end = if line == end_line { Some(end_col) } else { None };
+        Some((start, end))
+    }
+
     /// Calculate gutter width based on line count
     pub fn gutter_width(&self, total_lines: usize) -> Pixels {
         if !self.show_line_numbers {
@@ -147,6 +161,18 @@ pub fn render_text_view(
                         let is_current = config.cursor_line == Some(line_num);
                         let tokens = highlighted.get(idx);
 
+                        let cursor = config.cursor_column.filter(|_| is_current);
+                        let selection = config.selection_on_line(line_num);
+                        if !config.read_only && (cursor.is_some() || selection.is_some()) {
+                            return render_edit_line(
+                                line,
+                                cursor,
+                                selection,
+                                line_height,
+                                config.tab_width,
+                            );
+                        }
+
                         render_line(
                             line,
                             line_num,
@@ -212,6 +238,65 @@ fn render_line(
     }
 }
 
+/// Render a line being edited, with its selection and cursor
+fn render_edit_line(
+    line: &str,
+    cursor: Option<usize>,
+    selection: Option<(usize, Option<usize>)>,
+    line_height: Pixels,
+    tab_width: usize,
+) -> Div {
+    let chars: Vec<char> = line.chars().collect();
+    let len = chars.len();
+    let selection = selection.map(|(start, end)| (start.min(len), end.map(|end| end.min(len))));
+
+    // Split the line wherever the selection or cursor starts or ends
+    let mut cuts = vec![0, len];
+    cuts.extend(cursor.map(|column| column.min(len)));
+    if let Some((start, end)) = selection {
+        cuts.push(start);
+        cuts.push(end.unwrap_or(len));
+    }
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    let cursor_bar = || div().w(px(2.0)).h(line_height).bg(rgb(colors::CURSOR));
+    let segment = |text: String, selected: bool| {
+        div()
+            .when(selected, |el| el.bg(rgb(colors::SELECTION_BG)))
+            .child(text.replace('\t', &" ".repeat(tab_width)))
+    };
+
+    let mut children = Vec::new();
+    for window in cuts.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        if cursor == Some(start) {
+            children.push(cursor_bar());
+        }
+        let selected = selection.is_some_and(|(from, to)| start >= from && end <= to.unwrap_or(len));
+        children.push(segment(chars[start..end].iter().collect(), selected));
+    }
+    if cursor.map(|column| column.min(len)) == Some(len) {
+        children.push(cursor_bar());
+    }
+    // Show a selected line break as a selected space
+    if selection.is_some_and(|(_, end)| end.is_none()) {
+        children.push(segment(" ".to_string(), true));
+    }
+
+    div()
+        .h(line_height)
+        .flex()
+        .flex_row()
+        .items_center()
+        .w_full()
+        .font_family("monospace")
+        .text_sm()
+        .text_color(rgb(0xcccccc))
+        .when(cursor.is_some(), |el| el.bg(rgb(colors::CURRENT_LINE_BG)))
+        .children(children)
+}
+
 /// Render a line with syntax highlighting tokens
 fn render_highlighted_line(line: &str, tokens: &[Token], _font_size: f32) -> Div {
     let chars: Vec<char> = line.chars().collect();
@@ -330,6 +415,20 @@ mod tests {
         assert!(width_large > width);
     }
 
+    #[test]
+    fn test_selection_on_line() {
+        let config = TextViewConfig {
+            selection: Some((1, 4, 3, 2)),
+            ..Default::default()
+        };
+
+        assert_eq!(config.selection_on_line(0), None);
+        assert_eq!(config.selection_on_line(1), Some((4, None)));
+        assert_eq!(config.selection_on_line(2), Some((0, None)));
+        assert_eq!(config.selection_on_line(3), Some((0, Some(2))));
+        assert_eq!(config.selection_on_line(4), None);
+    }
+
     #[test]
     fn test_line_height() {
         let config = TextViewConfig {
@@ -1,29 +1,86 @@
 //! Diff Computation and Parsing
 //!
-//! Provides utilities for computing and parsing unified diffs.
+//! Provides utilities for computing, parsing and applying unified diffs,
+//! and for finding the changed words or characters within a changed line.
 
 use super::types::{DiffHunk, DiffLine, DiffLineType};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+use thiserror::Error;
+
+/// Unchanged lines shown around each change by default
+pub const DEFAULT_CONTEXT_LINES: usize = 3;
+
+/// Algorithm used to match the lines of two texts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiffAlgorithm {
+    /// Myers' O(ND) algorithm, giving the shortest edit script
+    #[default]
+    Myers,
+    /// Patience diff, anchored on lines that occur once on each side; keeps
+    /// moved blocks and repeated lines such as lone braces from being
+    /// matched out of place
+    Patience,
+}
+
+/// Options for computing diffs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Line matching algorithm
+    pub algorithm: DiffAlgorithm,
+    /// Unchanged lines to include around each change
+    pub context_lines: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            algorithm: DiffAlgorithm::default(),
+            context_lines: DEFAULT_CONTEXT_LINES,
+        }
+    }
+}
+
+impl DiffOptions {
+    /// Create options with the default algorithm and context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the line matching algorithm
+    pub fn with_algorithm(mut self, algorithm: DiffAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Set the number of context lines
+    pub fn with_context_lines(mut self, context_lines: usize) -> Self {
+        self.context_lines = context_lines;
+        self
+    }
+}
 
 /// Compute a unified diff between two strings
 pub fn compute_unified_diff(original: &str, modified: &str) -> String {
-    // Simple line-by-line diff implementation
-    // For production, consider using a proper diff library like `similar`
+    compute_unified_diff_with(original, modified, &DiffOptions::default())
+}
+
+/// Compute a unified diff between two strings with the given options
+pub fn compute_unified_diff_with(original: &str, modified: &str, options: &DiffOptions) -> String {
     let original_lines: Vec<&str> = original.lines().collect();
     let modified_lines: Vec<&str> = modified.lines().collect();
 
     let mut result = String::new();
 
-    // Use a simple LCS-based diff algorithm
-    let lcs = compute_lcs(&original_lines, &modified_lines);
-    let hunks = generate_hunks(&original_lines, &modified_lines, &lcs);
+    let matches = match_lines(&original_lines, &modified_lines, options.algorithm);
+    let hunks = generate_hunks(&original_lines, &modified_lines, &matches, options.context_lines);
 
     for hunk in hunks {
         result.push_str(&format!(
-            "@@ -{},{} +{},{} @@\n",
-            hunk.old_start + 1,
-            hunk.old_count,
-            hunk.new_start + 1,
-            hunk.new_count
+            "@@ -{} +{} @@\n",
+            format_range(hunk.old_start, hunk.old_count),
+            format_range(hunk.new_start, hunk.new_count)
         ));
 
         for line in &hunk.lines {
@@ -40,6 +97,15 @@ pub fn compute_unified_diff(original: &str, modified: &str) -> String {
     result
 }
 
+/// Format a hunk header range; an empty range names the line before it
+fn format_range(start: usize, count: usize) -> String {
+    if count == 0 {
+        format!("{},0", start)
+    } else {
+        format!("{},{}", start + 1, count)
+    }
+}
+
 /// Parse a unified diff string into hunks
 pub fn parse_hunks(diff: &str) -> Vec<DiffHunk> {
     let mut hunks = Vec::new();
@@ -67,6 +133,10 @@ pub fn parse_hunks(diff: &str) -> Vec<DiffHunk> {
                 });
             }
         } else if let Some(ref mut hunk) = current_hunk {
+            // "\ No newline at end of file" markers are not content
+            if line.starts_with('\\') {
+                continue;
+            }
             let (line_type, content) = if let Some(content) = line.strip_prefix('+') {
                 (DiffLineType::Addition, content)
             } else if let Some(content) = line.strip_prefix('-') {
@@ -134,60 +204,459 @@ fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize, usize)> {
 }
 
 /// Parse a range like "start,count" or just "start"
+///
+/// Starts are converted to 0-based indices. An empty range names the
+/// line *before* the change, so its start is already the index to insert at.
 fn parse_range(s: &str) -> Option<(usize, usize)> {
     let parts: Vec<&str> = s.split(',').collect();
-    let start = parts.first()?.parse::<usize>().ok()?.saturating_sub(1);
+    let start = parts.first()?.parse::<usize>().ok()?;
     let count = parts
         .get(1)
         .and_then(|s| s.parse::<usize>().ok())
         .unwrap_or(1);
+    let start = if count == 0 { start } else { start.saturating_sub(1) };
     Some((start, count))
 }
 
-/// Compute longest common subsequence indices
-fn compute_lcs<'a>(a: &[&'a str], b: &[&'a str]) -> Vec<(usize, usize)> {
-    let m = a.len();
-    let n = b.len();
+/// Hunks for one file of a (possibly multi-file) patch
+#[derive(Debug, Clone)]
+pub struct FilePatch {
+    /// Path before the change; None for new files or headerless patches
+    pub old_path: Option<String>,
+    /// Path after the change; None for deleted files or headerless patches
+    pub new_path: Option<String>,
+    /// Hunks to apply
+    pub hunks: Vec<DiffHunk>,
+}
+
+impl FilePatch {
+    /// Check whether the patch creates a new file
+    pub fn is_creation(&self) -> bool {
+        self.old_path.is_none() && self.new_path.is_some()
+    }
+
+    /// Check whether the patch deletes the file
+    pub fn is_deletion(&self) -> bool {
+        self.old_path.is_some() && self.new_path.is_none()
+    }
+}
+
+/// Split a unified diff into per-file hunks
+///
+/// File sections start at `--- `/`+++ ` header pairs (a `/dev/null` side
+/// means the file is created or deleted, and `a/`/`b/` prefixes are
+/// dropped). Other lines outside hunks, like `diff --git` and `index`,
+/// are ignored. A patch without headers yields one section without paths.
+pub fn parse_patch(patch: &str) -> Vec<FilePatch> {
+    let lines: Vec<&str> = patch.lines().collect();
+    let mut files = Vec::new();
+    let mut paths: (Option<String>, Option<String>) = (None, None);
+    let mut body = String::new();
+    // Lines left in the current hunk, so "--- " deletions are not headers
+    let mut remaining = (0usize, 0usize);
+
+    let mut flush = |paths: &(Option<String>, Option<String>), body: &mut String| {
+        let hunks = parse_hunks(body);
+        if !hunks.is_empty() {
+            files.push(FilePatch {
+                old_path: paths.0.clone(),
+                new_path: paths.1.clone(),
+                hunks,
+            });
+        }
+        body.clear();
+    };
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let in_hunk = remaining != (0, 0);
+
+        if !in_hunk
+            && line.starts_with("--- ")
+            && lines.get(i + 1).is_some_and(|next| next.starts_with("+++ "))
+        {
+            flush(&paths, &mut body);
+            paths = (parse_patch_path(&line[4..]), parse_patch_path(&lines[i + 1][4..]));
+            i += 2;
+            continue;
+        }
+
+        if line.starts_with("@@") {
+            if let Some((_, old_count, _, new_count)) = parse_hunk_header(line) {
+                remaining = (old_count, new_count);
+            }
+        } else if in_hunk {
+            match line.chars().next() {
+                Some('+') => remaining.1 = remaining.1.saturating_sub(1),
+                Some('-') => remaining.0 = remaining.0.saturating_sub(1),
+                Some('\\') => {}
+                _ => {
+                    remaining.0 = remaining.0.saturating_sub(1);
+                    remaining.1 = remaining.1.saturating_sub(1);
+                }
+            }
+        }
+        body.push_str(line);
+        body.push('\n');
+        i += 1;
+    }
+    flush(&paths, &mut body);
+
+    files
+}
+
+/// Parse the path from a `---`/`+++` header
+fn parse_patch_path(header: &str) -> Option<String> {
+    // Drop a trailing timestamp
+    let path = header.split('\t').next().unwrap_or_default().trim();
+    if path.is_empty() || path == "/dev/null" {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+/// Context lines a hunk may drop at each end to apply, like `patch -F2`
+pub const DEFAULT_FUZZ: usize = 2;
+
+/// Error applying hunks to content
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PatchError {
+    #[error("Hunk {hunk} does not match the content near line {line}")]
+    HunkMismatch {
+        /// 1-based hunk number
+        hunk: usize,
+        /// 1-based line the hunk expected to start at
+        line: usize,
+    },
+
+    #[error("Diff contains no hunks")]
+    NoHunks,
+}
+
+/// Apply hunks to content
+///
+/// Each hunk's context and deleted lines must match exactly. A hunk may
+/// sit at a different line than its header says (for example when lines
+/// were added above it); the match nearest the expected line is used.
+/// Line endings follow the original content.
+pub fn apply_hunks(original: &str, hunks: &[DiffHunk]) -> Result<String, PatchError> {
+    apply_hunks_with_fuzz(original, hunks, 0)
+}
+
+/// Apply hunks to content, tolerating stale context
+///
+/// A hunk that does not match is retried ignoring up to `fuzz` context
+/// lines at its start and end, one more line per attempt. Ignored context
+/// lines are left as they are in the content. Deleted lines must always
+/// match exactly.
+pub fn apply_hunks_with_fuzz(
+    original: &str,
+    hunks: &[DiffHunk],
+    fuzz: usize,
+) -> Result<String, PatchError> {
+    let newline = if original.contains("\r\n") { "\r\n" } else { "\n" };
+    let lines: Vec<&str> = original.lines().collect();
+    let mut result: Vec<&str> = Vec::with_capacity(lines.len());
+    let mut cursor = 0;
+    let mut offset = 0isize;
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let (hunk_lines, start) = (0..=fuzz)
+            .find_map(|level| {
+                let (skipped, hunk_lines) = trim_context(&hunk.lines, level)?;
+                let old = old_lines(hunk_lines);
+                let expected = (hunk.old_start + skipped) as isize + offset;
+                let expected = expected.max(cursor as isize) as usize;
+                find_hunk(&lines, &old, expected, cursor).map(|start| (hunk_lines, start))
+            })
+            .ok_or(PatchError::HunkMismatch {
+                hunk: index + 1,
+                line: hunk.old_start + 1,
+            })?;
+
+        result.extend_from_slice(&lines[cursor..start]);
+        result.extend(
+            hunk_lines
+                .iter()
+                .filter(|line| matches!(line.line_type, DiffLineType::Context | DiffLineType::Addition))
+                .map(|line| line.content.as_str()),
+        );
+        cursor = start + old_lines(hunk_lines).len();
+        offset = start as isize - hunk.old_start as isize;
+    }
+    result.extend_from_slice(&lines[cursor..]);
+
+    let mut patched = result.join(newline);
+    if !result.is_empty() && (original.is_empty() || original.ends_with('\n')) {
+        patched.push_str(newline);
+    }
+    Ok(patched)
+}
+
+/// Lines a hunk expects in the original content
+fn old_lines(lines: &[DiffLine]) -> Vec<&str> {
+    lines
+        .iter()
+        .filter(|line| matches!(line.line_type, DiffLineType::Context | DiffLineType::Deletion))
+        .map(|line| line.content.as_str())
+        .collect()
+}
 
-    // Build LCS table
-    let mut dp = vec![vec![0usize; n + 1]; m + 1];
+/// Drop up to `level` context lines from each end of a hunk
+///
+/// Returns the number of lines dropped from the start and the remaining
+/// lines, or None if the level drops nothing new or leaves no line to
+/// anchor the hunk on.
+fn trim_context(lines: &[DiffLine], level: usize) -> Option<(usize, &[DiffLine])> {
+    if level == 0 {
+        return Some((0, lines));
+    }
+    let is_context = |line: &&DiffLine| line.line_type == DiffLineType::Context;
+    let leading = lines.iter().take_while(is_context).count();
+    let trailing = lines.iter().rev().take_while(is_context).count();
+    // Each level must drop another line, or it repeats the previous attempt
+    if level > leading.max(trailing) || leading + trailing >= lines.len() {
+        return None;
+    }
+
+    let (head, tail) = (level.min(leading), level.min(trailing));
+    let trimmed = &lines[head..lines.len() - tail];
+    if old_lines(trimmed).is_empty() {
+        return None;
+    }
+    Some((head, trimmed))
+}
+
+/// Find where a hunk's old lines occur, nearest to the expected index
+fn find_hunk(lines: &[&str], old: &[&str], expected: usize, from: usize) -> Option<usize> {
+    if old.is_empty() {
+        return Some(expected.clamp(from, lines.len()));
+    }
+    if lines.len() < old.len() {
+        return None;
+    }
+
+    (from..=lines.len() - old.len())
+        .filter(|&start| lines[start..start + old.len()] == *old)
+        .min_by_key(|&start| start.abs_diff(expected))
+}
+
+/// Pairs of equal lines, in order, matched with the given algorithm
+pub fn match_lines<T: Eq + Hash>(a: &[T], b: &[T], algorithm: DiffAlgorithm) -> Vec<(usize, usize)> {
+    match algorithm {
+        DiffAlgorithm::Myers => compute_lcs(a, b),
+        DiffAlgorithm::Patience => compute_patience(a, b),
+    }
+}
 
-    for i in 1..=m {
-        for j in 1..=n {
-            if a[i - 1] == b[j - 1] {
-                dp[i][j] = dp[i - 1][j - 1] + 1;
+/// Compute longest common subsequence indices with Myers' O(ND) algorithm
+///
+/// Uses the linear-space variant, which splits the problem at the middle
+/// snake of the edit script, so memory stays proportional to the input.
+pub(crate) fn compute_lcs<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let mut matches = Vec::new();
+    myers(a, b, 0..a.len(), 0..b.len(), &mut matches);
+    matches
+}
+
+fn myers<T: PartialEq>(
+    a: &[T],
+    b: &[T],
+    mut old: Range<usize>,
+    mut new: Range<usize>,
+    matches: &mut Vec<(usize, usize)>,
+) {
+    let suffix = trim_common(a, b, &mut old, &mut new, matches);
+    if !old.is_empty() && !new.is_empty() {
+        let ((x, y), (u, v)) = middle_snake(&a[old.clone()], &b[new.clone()]);
+        myers(a, b, old.start..old.start + x, new.start..new.start + y, matches);
+        matches.extend((x..u).map(|i| (old.start + i, new.start + i - x + y)));
+        myers(a, b, old.start + u..old.end, new.start + v..new.end, matches);
+    }
+    matches.extend((0..suffix).map(|i| (old.end + i, new.end + i)));
+}
+
+/// Match the common prefix and cut the common suffix from the ranges
+///
+/// Returns the suffix length; its matches start at the new range ends.
+fn trim_common<T: PartialEq>(
+    a: &[T],
+    b: &[T],
+    old: &mut Range<usize>,
+    new: &mut Range<usize>,
+    matches: &mut Vec<(usize, usize)>,
+) -> usize {
+    while old.start < old.end && new.start < new.end && a[old.start] == b[new.start] {
+        matches.push((old.start, new.start));
+        old.start += 1;
+        new.start += 1;
+    }
+    let mut suffix = 0;
+    while old.len() > suffix && new.len() > suffix && a[old.end - suffix - 1] == b[new.end - suffix - 1] {
+        suffix += 1;
+    }
+    old.end -= suffix;
+    new.end -= suffix;
+    suffix
+}
+
+/// Find the middle snake of the shortest edit script between two sequences
+///
+/// Searches forwards from the start and backwards from the end until the
+/// paths overlap, and returns the start and end (x, y) of the diagonal run
+/// where they meet.
+fn middle_snake<T: PartialEq>(a: &[T], b: &[T]) -> ((usize, usize), (usize, usize)) {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    // Furthest x reached on each diagonal k = x - y, in each direction
+    let mut forward = vec![0isize; 2 * max as usize + 3];
+    let mut backward = vec![0isize; 2 * max as usize + 3];
+    let at = |k: isize| (k + max + 1) as usize;
+    let point = |x: isize, y: isize| (x as usize, y as usize);
+
+    for d in 0..=max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            let start = point(x, y);
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+            if odd && (delta - k).abs() < d && x + backward[at(delta - k)] >= n {
+                return (start, point(x, y));
+            }
+        }
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
             } else {
-                dp[i][j] = dp[i - 1][j].max(dp[i][j - 1]);
+                backward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            let end = point(n - x, m - y);
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+            if !odd && (delta - k).abs() <= d && x + forward[at(delta - k)] >= n {
+                return (point(n - x, m - y), end);
             }
         }
     }
+    unreachable!("forward and backward searches always meet")
+}
 
-    // Backtrack to find LCS
-    let mut result = Vec::new();
-    let mut i = m;
-    let mut j = n;
+/// Compute matching lines with patience diff
+///
+/// Lines that occur exactly once on each side anchor the match, keeping
+/// the longest run of anchors in the same order on both sides. The gaps
+/// between anchors are matched the same way, falling back to Myers where
+/// no unique lines are left.
+fn compute_patience<T: Eq + Hash>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let mut matches = Vec::new();
+    patience(a, b, 0..a.len(), 0..b.len(), &mut matches);
+    matches
+}
 
-    while i > 0 && j > 0 {
-        if a[i - 1] == b[j - 1] {
-            result.push((i - 1, j - 1));
-            i -= 1;
-            j -= 1;
-        } else if dp[i - 1][j] > dp[i][j - 1] {
-            i -= 1;
+fn patience<T: Eq + Hash>(
+    a: &[T],
+    b: &[T],
+    mut old: Range<usize>,
+    mut new: Range<usize>,
+    matches: &mut Vec<(usize, usize)>,
+) {
+    let suffix = trim_common(a, b, &mut old, &mut new, matches);
+    if !old.is_empty() && !new.is_empty() {
+        let anchors = unique_anchors(&a[old.clone()], &b[new.clone()]);
+        if anchors.is_empty() {
+            myers(a, b, old.clone(), new.clone(), matches);
         } else {
-            j -= 1;
+            let (mut x, mut y) = (old.start, new.start);
+            for (i, j) in anchors {
+                let (i, j) = (old.start + i, new.start + j);
+                patience(a, b, x..i, y..j, matches);
+                matches.push((i, j));
+                (x, y) = (i + 1, j + 1);
+            }
+            patience(a, b, x..old.end, y..new.end, matches);
         }
     }
+    matches.extend((0..suffix).map(|i| (old.end + i, new.end + i)));
+}
 
-    result.reverse();
-    result
+/// Longest in-order run of lines occurring once on each side
+fn unique_anchors<T: Eq + Hash>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    // Occurrences on each side, with the last index seen
+    let mut counts: HashMap<&T, (usize, usize, usize, usize)> = HashMap::new();
+    for (i, line) in a.iter().enumerate() {
+        let count = counts.entry(line).or_default();
+        count.0 += 1;
+        count.2 = i;
+    }
+    for (j, line) in b.iter().enumerate() {
+        if let Some(count) = counts.get_mut(line) {
+            count.1 += 1;
+            count.3 = j;
+        }
+    }
+
+    let mut unique: Vec<(usize, usize)> = counts
+        .into_values()
+        .filter(|&(in_a, in_b, _, _)| in_a == 1 && in_b == 1)
+        .map(|(_, _, i, j)| (i, j))
+        .collect();
+    unique.sort_unstable();
+    longest_increasing(&unique)
 }
 
-/// Generate diff hunks from LCS
+/// Longest subsequence of pairs increasing in their second index, by patience sorting
+fn longest_increasing(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // Last pair of the best run found for each length, and each pair's predecessor
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = vec![None; pairs.len()];
+    for (i, &(_, j)) in pairs.iter().enumerate() {
+        let length = tails.partition_point(|&tail| pairs[tail].1 < j);
+        if length > 0 {
+            previous[i] = Some(tails[length - 1]);
+        }
+        if length == tails.len() {
+            tails.push(i);
+        } else {
+            tails[length] = i;
+        }
+    }
+
+    let mut run = Vec::new();
+    let mut next = tails.last().copied();
+    while let Some(i) = next {
+        run.push(pairs[i]);
+        next = previous[i];
+    }
+    run.reverse();
+    run
+}
+
+/// Generate diff hunks from matched lines
 fn generate_hunks(
     original: &[&str],
     modified: &[&str],
     lcs: &[(usize, usize)],
+    context_lines: usize,
 ) -> Vec<DiffHunk> {
     let mut hunks = Vec::new();
     let mut current_lines = Vec::new();
@@ -200,8 +669,6 @@ fn generate_hunks(
     let mut hunk_new_start = 0usize;
     let mut in_hunk = false;
 
-    let context_lines = 3; // Lines of context around changes
-
     while old_idx < original.len() || new_idx < modified.len() {
         // Check if current position is in LCS (unchanged)
         let is_match = lcs_idx < lcs.len()
@@ -367,6 +834,122 @@ fn generate_hunks(
     hunks
 }
 
+/// Unit of intra-line change highlighting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InlineGranularity {
+    /// Words, runs of whitespace and single punctuation characters
+    #[default]
+    Word,
+    /// Single characters
+    Character,
+}
+
+/// Byte ranges that differ between a deleted line and the line added in its place
+///
+/// Returns the changed ranges of `old` and of `new`. Both are empty if the
+/// lines have nothing in common, as the whole line is then changed.
+pub fn inline_changes(
+    old: &str,
+    new: &str,
+    granularity: InlineGranularity,
+) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let old_tokens = tokenize(old, granularity);
+    let new_tokens = tokenize(new, granularity);
+    let old_words: Vec<&str> = old_tokens.iter().map(|range| &old[range.clone()]).collect();
+    let new_words: Vec<&str> = new_tokens.iter().map(|range| &new[range.clone()]).collect();
+
+    let matches = compute_lcs(&old_words, &new_words);
+    if matches.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    (
+        unmatched_ranges(&old_tokens, matches.iter().map(|&(i, _)| i)),
+        unmatched_ranges(&new_tokens, matches.iter().map(|&(_, j)| j)),
+    )
+}
+
+/// Index of the line paired with a changed line for intra-line highlighting
+///
+/// Within each run of deletions followed by additions, the n-th deletion is
+/// paired with the n-th addition.
+pub fn paired_line(lines: &[DiffLine], index: usize) -> Option<usize> {
+    let run_start = |end: usize, line_type: DiffLineType| {
+        lines[..end]
+            .iter()
+            .rposition(|line| line.line_type != line_type)
+            .map_or(0, |i| i + 1)
+    };
+    let run_end = |start: usize, line_type: DiffLineType| {
+        lines[start..]
+            .iter()
+            .position(|line| line.line_type != line_type)
+            .map_or(lines.len(), |i| start + i)
+    };
+
+    match lines.get(index)?.line_type {
+        DiffLineType::Deletion => {
+            let additions = run_end(index, DiffLineType::Deletion);
+            let partner = additions + index - run_start(index, DiffLineType::Deletion);
+            (partner < run_end(additions, DiffLineType::Addition)).then_some(partner)
+        }
+        DiffLineType::Addition => {
+            let additions = run_start(index, DiffLineType::Addition);
+            let deletions = run_start(additions, DiffLineType::Deletion);
+            let partner = deletions + index - additions;
+            (partner < additions).then_some(partner)
+        }
+        _ => None,
+    }
+}
+
+/// Split a line into the units compared by `inline_changes`
+fn tokenize(text: &str, granularity: InlineGranularity) -> Vec<Range<usize>> {
+    // Words and whitespace group into runs; punctuation stands alone
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Some(true)
+        } else if c.is_whitespace() {
+            Some(false)
+        } else {
+            None
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        if granularity == InlineGranularity::Word && class(c).is_some() {
+            while let Some(&(next_start, next)) = chars.peek() {
+                if class(next) != class(c) {
+                    break;
+                }
+                end = next_start + next.len_utf8();
+                chars.next();
+            }
+        }
+        tokens.push(start..end);
+    }
+    tokens
+}
+
+/// Merged ranges of the tokens not in `matched`
+fn unmatched_ranges(tokens: &[Range<usize>], matched: impl Iterator<Item = usize>) -> Vec<Range<usize>> {
+    let mut is_matched = vec![false; tokens.len()];
+    for index in matched {
+        is_matched[index] = true;
+    }
+
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (token, _) in tokens.iter().zip(is_matched).filter(|(_, matched)| !matched) {
+        match ranges.last_mut() {
+            Some(last) if last.end == token.start => last.end = token.end,
+            _ => ranges.push(token.clone()),
+        }
+    }
+    ranges
+}
+
 /// Get statistics about a diff
 pub fn diff_stats(hunks: &[DiffHunk]) -> DiffStats {
     let mut additions = 0;
@@ -416,6 +999,139 @@ mod tests {
         assert_eq!(lcs.len(), 3);
     }
 
+    /// Length of a longest common subsequence, by dynamic programming
+    fn lcs_length(a: &[u8], b: &[u8]) -> usize {
+        let mut dp = vec![vec![0; b.len() + 1]; a.len() + 1];
+        for i in 1..=a.len() {
+            for j in 1..=b.len() {
+                dp[i][j] = if a[i - 1] == b[j - 1] {
+                    dp[i - 1][j - 1] + 1
+                } else {
+                    dp[i - 1][j].max(dp[i][j - 1])
+                };
+            }
+        }
+        dp[a.len()][b.len()]
+    }
+
+    #[test]
+    fn test_myers_is_optimal() {
+        // Pseudo-random sequences over a small alphabet
+        let mut seed = 0x2545f491u32;
+        let mut sequence = |len: usize| -> Vec<u8> {
+            (0..len)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 17;
+                    seed ^= seed << 5;
+                    b'a' + (seed % 4) as u8
+                })
+                .collect()
+        };
+
+        for round in 0..200 {
+            let a = sequence(round % 23);
+            let b = sequence(round % 17);
+            for algorithm in [DiffAlgorithm::Myers, DiffAlgorithm::Patience] {
+                let matches = match_lines(&a, &b, algorithm);
+                assert!(matches.iter().all(|&(i, j)| a[i] == b[j]));
+                assert!(matches.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 < w[1].1));
+                if algorithm == DiffAlgorithm::Myers {
+                    assert_eq!(matches.len(), lcs_length(&a, &b), "{:?} vs {:?}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_patience_diff() {
+        // Myers matches the lone braces; patience keeps the functions whole
+        let original = "fn a() {\n    one();\n}\n\nfn b() {\n    two();\n}\n";
+        let modified = "fn b() {\n    two();\n}\n\nfn a() {\n    one();\n}\n";
+        let patience = compute_unified_diff_with(
+            original,
+            modified,
+            &DiffOptions::new().with_algorithm(DiffAlgorithm::Patience),
+        );
+
+        let hunks = parse_hunks(&patience);
+        assert_eq!(apply_hunks(original, &hunks).unwrap(), modified);
+        let stats = diff_stats(&hunks);
+        assert_eq!((stats.additions, stats.deletions), (4, 4));
+        assert!(patience.contains("-fn a() {\n-    one();\n-}\n"));
+    }
+
+    #[test]
+    fn test_context_lines() {
+        let original: String = (1..=20).map(|i| format!("line{}\n", i)).collect();
+        let modified = original.replace("line5\n", "five\n").replace("line15\n", "fifteen\n");
+
+        let diff = |context_lines| {
+            let options = DiffOptions::new().with_context_lines(context_lines);
+            parse_hunks(&compute_unified_diff_with(&original, &modified, &options))
+        };
+        assert_eq!(diff(0).len(), 2);
+        assert_eq!(diff(0)[0].lines.len(), 2);
+        assert_eq!(diff(1)[0].lines.len(), 4);
+        // Changes closer than twice the context share a hunk
+        assert_eq!(diff(5).len(), 1);
+
+        for context_lines in [0, 1, 3] {
+            assert_eq!(apply_hunks(&original, &diff(context_lines)).unwrap(), modified);
+        }
+        // Pure insertions without context name the line before them
+        let inserted = original.replace("line5\n", "line5\nnew\n");
+        let options = DiffOptions::new().with_context_lines(0);
+        let insertion = compute_unified_diff_with(&original, &inserted, &options);
+        assert!(insertion.starts_with("@@ -5,0 +6,1 @@"));
+        assert_eq!(apply_hunks(&original, &parse_hunks(&insertion)).unwrap(), inserted);
+    }
+
+    #[test]
+    fn test_large_diff() {
+        let original: String = (0..5000).map(|i| format!("let value_{} = {};\n", i, i)).collect();
+        let modified: String = (0..5000)
+            .filter(|i| i % 100 != 0)
+            .map(|i| format!("let value_{} = {};\n", i, if i % 7 == 0 { i * 2 } else { i }))
+            .collect();
+
+        let hunks = parse_hunks(&compute_unified_diff(&original, &modified));
+        assert_eq!(apply_hunks(&original, &hunks).unwrap(), modified);
+    }
+
+    #[test]
+    fn test_inline_changes() {
+        let (old, new) = inline_changes(
+            "let total = price * count;",
+            "let total = price * amount + tax;",
+            InlineGranularity::Word,
+        );
+        assert_eq!(old, vec![Range { start: 20, end: 25 }]);
+        assert_eq!(new, vec![Range { start: 20, end: 32 }]);
+
+        let (old, new) = inline_changes("colour", "color", InlineGranularity::Character);
+        assert_eq!(old, vec![Range { start: 4, end: 5 }]);
+        assert!(new.is_empty());
+
+        // Lines with nothing in common are not highlighted word by word
+        assert_eq!(
+            inline_changes("abc", "xyz", InlineGranularity::Word),
+            (Vec::new(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_paired_line() {
+        let hunks = parse_hunks("@@ -1,4 +1,3 @@\n a\n-b\n-c\n+B\n d\n+e\n");
+        let lines = &hunks[0].lines;
+
+        assert_eq!(paired_line(lines, 0), None);
+        assert_eq!(paired_line(lines, 1), Some(3));
+        assert_eq!(paired_line(lines, 2), None);
+        assert_eq!(paired_line(lines, 3), Some(1));
+        assert_eq!(paired_line(lines, 5), None);
+    }
+
     #[test]
     fn test_simple_diff() {
         let original = "line1\nline2\nline3";
@@ -442,6 +1158,102 @@ mod tests {
         assert_eq!(result, Some((0, 5, 0, 6)));
     }
 
+    #[test]
+    fn test_parse_insertion_range() {
+        // An empty range names the line before the insertion
+        assert_eq!(parse_hunk_header("@@ -5,0 +6,2 @@"), Some((5, 0, 5, 2)));
+        assert_eq!(parse_hunk_header("@@ -0,0 +1 @@"), Some((0, 0, 0, 1)));
+    }
+
+    #[test]
+    fn test_apply_hunks() {
+        let original = "a\nb\nc\nd\ne\n";
+        let diff = "@@ -2,2 +2,2 @@\n b\n-c\n+C\n@@ -5,0 +6,1 @@\n+f\n";
+
+        let patched = apply_hunks(original, &parse_hunks(diff)).unwrap();
+        assert_eq!(patched, "a\nb\nC\nd\ne\nf\n");
+    }
+
+    #[test]
+    fn test_apply_hunks_with_offset() {
+        // Two lines were added above the hunk since the diff was made
+        let original = "new1\nnew2\na\nb\nc\n";
+        let diff = "@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n";
+
+        let patched = apply_hunks(original, &parse_hunks(diff)).unwrap();
+        assert_eq!(patched, "new1\nnew2\na\nB\nc\n");
+    }
+
+    #[test]
+    fn test_apply_hunks_mismatch() {
+        let diff = "@@ -1,2 +1,2 @@\n x\n-y\n+z\n";
+        assert_eq!(
+            apply_hunks("a\nb\n", &parse_hunks(diff)),
+            Err(PatchError::HunkMismatch { hunk: 1, line: 1 })
+        );
+    }
+
+    #[test]
+    fn test_apply_hunks_with_fuzz() {
+        // The first and last context lines were edited since the diff was made
+        let original = "a\nB\nc\nd\nE\nf\n";
+        let diff = "@@ -2,4 +2,4 @@\n b\n c\n-d\n+D\n e\n";
+        let hunks = parse_hunks(diff);
+
+        assert!(apply_hunks(original, &hunks).is_err());
+        assert_eq!(
+            apply_hunks_with_fuzz(original, &hunks, DEFAULT_FUZZ).unwrap(),
+            "a\nB\nc\nD\nE\nf\n"
+        );
+
+        // Deleted lines never fuzz
+        let diff = "@@ -2,4 +2,4 @@\n b\n c\n-x\n+D\n e\n";
+        assert_eq!(
+            apply_hunks_with_fuzz(original, &parse_hunks(diff), DEFAULT_FUZZ),
+            Err(PatchError::HunkMismatch { hunk: 1, line: 2 })
+        );
+    }
+
+    #[test]
+    fn test_apply_computed_diff() {
+        let original = "one\ntwo\nthree\nfour\nfive\nsix\nseven\n";
+        let modified = "one\n2\nthree\nfour\nfive\nsix\nseven\neight\n";
+        let hunks = parse_hunks(&compute_unified_diff(original, modified));
+
+        assert_eq!(apply_hunks(original, &hunks).unwrap(), modified);
+    }
+
+    #[test]
+    fn test_parse_patch() {
+        let patch = "diff --git a/src/lib.rs b/src/lib.rs\n\
+            index 1234..5678 100644\n\
+            --- a/src/lib.rs\n\
+            +++ b/src/lib.rs\n\
+            @@ -1,2 +1,2 @@\n\
+            --- removed\n\
+            +++ added\n\
+             keep\n\
+            \\ No newline at end of file\n\
+            --- /dev/null\n\
+            +++ b/new.txt\t2024-01-01\n\
+            @@ -0,0 +1 @@\n\
+            +hello\n";
+
+        let files = parse_patch(patch);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].old_path.as_deref(), Some("src/lib.rs"));
+        assert_eq!(files[0].hunks[0].lines.len(), 3);
+        assert_eq!(files[0].hunks[0].lines[0].content, "-- removed");
+        assert!(files[1].is_creation());
+        assert_eq!(files[1].new_path.as_deref(), Some("new.txt"));
+        assert_eq!(apply_hunks("", &files[1].hunks).unwrap(), "hello\n");
+
+        // Bare hunks form a single section without paths
+        let files = parse_patch("@@ -1 +1 @@\n-a\n+b\n");
+        assert_eq!(files.len(), 1);
+        assert!(files[0].old_path.is_none() && files[0].new_path.is_none());
+    }
+
     #[test]
     fn test_diff_stats() {
         let hunks = vec![DiffHunk {
@@ -0,0 +1,160 @@
+//! Range-based text edits
+//!
+//! Applies the insert/replace/delete operations of partial ARTIFACT_UPDATE
+//! events to text content, addressing it by line and character column.
+
+use crate::protocol::{ArtifactEdit, TextPosition, TextRange};
+use thiserror::Error;
+
+/// Error applying edits to content
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EditError {
+    #[error("Edit {edit}: position {}:{} is outside the content", .position.line, .position.column)]
+    OutOfRange {
+        /// 1-based edit number
+        edit: usize,
+        position: TextPosition,
+    },
+
+    #[error("Edit {edit}: range ends before it starts")]
+    InvertedRange {
+        /// 1-based edit number
+        edit: usize,
+    },
+
+    #[error("Partial update contains no edits")]
+    NoEdits,
+}
+
+/// Apply edits in order, each to the content left by the previous one
+pub fn apply_edits(content: &str, edits: &[ArtifactEdit]) -> Result<String, EditError> {
+    if edits.is_empty() {
+        return Err(EditError::NoEdits);
+    }
+
+    let mut content = content.to_string();
+    for (index, edit) in edits.iter().enumerate() {
+        let edit_number = index + 1;
+        let resolve = |content: &str, position: TextPosition| {
+            byte_offset(content, position).ok_or(EditError::OutOfRange {
+                edit: edit_number,
+                position,
+            })
+        };
+        let resolve_range = |content: &str, range: &TextRange| {
+            let start = resolve(content, range.start)?;
+            let end = resolve(content, range.end)?;
+            if end < start {
+                return Err(EditError::InvertedRange { edit: edit_number });
+            }
+            Ok(start..end)
+        };
+
+        match edit {
+            ArtifactEdit::Replace { range, text } => {
+                let range = resolve_range(&content, range)?;
+                content.replace_range(range, text);
+            }
+            ArtifactEdit::Insert { position, text } => {
+                let offset = resolve(&content, *position)?;
+                content.insert_str(offset, text);
+            }
+            ArtifactEdit::Delete { range } => {
+                let range = resolve_range(&content, range)?;
+                content.replace_range(range, "");
+            }
+        }
+    }
+    Ok(content)
+}
+
+/// Byte offset of a position, if it lies within the content
+///
+/// A `\r` before a line's `\n` is not part of the line, so the end of a
+/// CRLF line is before the `\r`.
+pub fn byte_offset(content: &str, position: TextPosition) -> Option<usize> {
+    let line_start = match position.line {
+        0 => 0,
+        line => content.match_indices('\n').nth(line - 1)?.0 + 1,
+    };
+    let rest = &content[line_start..];
+    let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+    let line = line.strip_suffix('\r').unwrap_or(line);
+
+    line.char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(line.len()))
+        .nth(position.column)
+        .map(|offset| line_start + offset)
+}
+
+/// Position of a byte offset on a character boundary
+pub fn position_at(content: &str, offset: usize) -> TextPosition {
+    let before = &content[..offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    TextPosition::new(before.matches('\n').count(), before[line_start..].chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: (usize, usize), end: (usize, usize)) -> TextRange {
+        TextRange::new(TextPosition::new(start.0, start.1), TextPosition::new(end.0, end.1))
+    }
+
+    #[test]
+    fn test_byte_offset() {
+        let content = "héllo\r\nworld\n";
+        assert_eq!(byte_offset(content, TextPosition::new(0, 2)), Some(3));
+        assert_eq!(byte_offset(content, TextPosition::new(0, 5)), Some(6));
+        assert_eq!(byte_offset(content, TextPosition::new(0, 6)), None);
+        assert_eq!(byte_offset(content, TextPosition::new(1, 0)), Some(8));
+        // The empty line after the final newline
+        assert_eq!(byte_offset(content, TextPosition::new(2, 0)), Some(14));
+        assert_eq!(byte_offset(content, TextPosition::new(3, 0)), None);
+
+        assert_eq!(position_at(content, 3), TextPosition::new(0, 2));
+        assert_eq!(position_at(content, 8), TextPosition::new(1, 0));
+        assert_eq!(position_at(content, 14), TextPosition::new(2, 0));
+    }
+
+    #[test]
+    fn test_apply_edits() {
+        let content = "fn main() {\n    old();\n}\n";
+        let edits = [
+            ArtifactEdit::Replace {
+                range: range((1, 4), (1, 7)),
+                text: "new".to_string(),
+            },
+            ArtifactEdit::Insert {
+                position: TextPosition::new(0, 0),
+                text: "// entry\n".to_string(),
+            },
+            // Positions follow the previous edits
+            ArtifactEdit::Delete {
+                range: range((3, 0), (4, 0)),
+            },
+        ];
+
+        assert_eq!(apply_edits(content, &edits).unwrap(), "// entry\nfn main() {\n    new();\n");
+    }
+
+    #[test]
+    fn test_apply_edits_errors() {
+        let delete = |start, end| ArtifactEdit::Delete { range: range(start, end) };
+
+        assert_eq!(
+            apply_edits("one\ntwo", &[delete((0, 0), (0, 1)), delete((5, 0), (5, 1))]),
+            Err(EditError::OutOfRange {
+                edit: 2,
+                position: TextPosition::new(5, 0),
+            })
+        );
+        assert_eq!(
+            apply_edits("one\ntwo", &[delete((1, 0), (0, 2))]),
+            Err(EditError::InvertedRange { edit: 1 })
+        );
+        assert_eq!(apply_edits("one", &[]), Err(EditError::NoEdits));
+    }
+}
@@ -0,0 +1,297 @@
+//! Three-way merge
+//!
+//! Combines the user's edits and an agent update made against the same base
+//! content, line by line. Lines changed on one side only take that side's
+//! version; lines both sides changed differently become conflicts for the
+//! user to resolve.
+
+use super::diff::compute_lcs;
+
+/// Marker opening a conflict's user side in `Merge::marked_text`
+pub const MINE_MARKER: &str = "<<<<<<< mine";
+/// Marker between the two sides of a conflict
+pub const SEPARATOR_MARKER: &str = "=======";
+/// Marker closing a conflict's agent side
+pub const THEIRS_MARKER: &str = ">>>>>>> agent";
+
+/// How to resolve a conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Keep the user's lines
+    Mine,
+    /// Keep the agent's lines
+    Theirs,
+    /// Keep the user's lines followed by the agent's
+    Both,
+}
+
+/// Lines both sides changed differently
+///
+/// Lines keep their line endings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// Lines in the common base
+    pub base: Vec<String>,
+    /// The user's lines
+    pub mine: Vec<String>,
+    /// The agent's lines
+    pub theirs: Vec<String>,
+}
+
+impl Conflict {
+    /// Lines kept by a resolution
+    pub fn resolve(&self, resolution: Resolution) -> Vec<String> {
+        match resolution {
+            Resolution::Mine => self.mine.clone(),
+            Resolution::Theirs => self.theirs.clone(),
+            Resolution::Both => {
+                let mut lines = terminated(&self.mine);
+                lines.extend(self.theirs.iter().cloned());
+                lines
+            }
+        }
+    }
+}
+
+/// A region of the merged content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeChunk {
+    /// Lines taken from the base or from one side
+    Resolved(Vec<String>),
+    /// Lines waiting for the user to pick a side
+    Conflict(Conflict),
+}
+
+/// Result of a three-way merge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merge {
+    chunks: Vec<MergeChunk>,
+}
+
+impl Merge {
+    /// Merge the user's and the agent's versions of the base content
+    pub fn new(base: &str, mine: &str, theirs: &str) -> Self {
+        let base: Vec<&str> = base.split_inclusive('\n').collect();
+        let mine: Vec<&str> = mine.split_inclusive('\n').collect();
+        let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+
+        // Where each base line ended up on either side
+        let mine_map = base_matches(&base, &mine);
+        let theirs_map = base_matches(&base, &theirs);
+
+        let mut merge = Self { chunks: Vec::new() };
+        let (mut o, mut a, mut b) = (0, 0, 0);
+        loop {
+            // Lines unchanged on both sides
+            let start = o;
+            while o < base.len() && mine_map[o] == Some(a) && theirs_map[o] == Some(b) {
+                o += 1;
+                a += 1;
+                b += 1;
+            }
+            merge.push_resolved(&base[start..o]);
+
+            // The changed region runs up to the next base line both sides kept
+            let next = (o..base.len()).find_map(|i| Some((i, mine_map[i]?, theirs_map[i]?)));
+            let (o_end, a_end, b_end) = next.unwrap_or((base.len(), mine.len(), theirs.len()));
+            if (o_end, a_end, b_end) == (o, a, b) {
+                break;
+            }
+
+            let (base_lines, mine_lines, theirs_lines) =
+                (&base[o..o_end], &mine[a..a_end], &theirs[b..b_end]);
+            if mine_lines == base_lines {
+                merge.push_resolved(theirs_lines);
+            } else if theirs_lines == base_lines || mine_lines == theirs_lines {
+                merge.push_resolved(mine_lines);
+            } else {
+                merge.chunks.push(MergeChunk::Conflict(Conflict {
+                    base: to_owned(base_lines),
+                    mine: to_owned(mine_lines),
+                    theirs: to_owned(theirs_lines),
+                }));
+            }
+            (o, a, b) = (o_end, a_end, b_end);
+        }
+        merge
+    }
+
+    /// Get the merged regions in order
+    pub fn chunks(&self) -> &[MergeChunk] {
+        &self.chunks
+    }
+
+    /// Get the conflicts still to resolve, in order
+    pub fn conflicts(&self) -> impl Iterator<Item = &Conflict> {
+        self.chunks.iter().filter_map(|chunk| match chunk {
+            MergeChunk::Conflict(conflict) => Some(conflict),
+            MergeChunk::Resolved(_) => None,
+        })
+    }
+
+    /// Number of unresolved conflicts
+    pub fn conflict_count(&self) -> usize {
+        self.conflicts().count()
+    }
+
+    /// Check whether any conflicts are unresolved
+    pub fn has_conflicts(&self) -> bool {
+        self.conflicts().next().is_some()
+    }
+
+    /// Resolve the conflict at an index among the unresolved conflicts
+    ///
+    /// Returns false if there is no such conflict.
+    pub fn resolve(&mut self, index: usize, resolution: Resolution) -> bool {
+        let chunk = self
+            .chunks
+            .iter_mut()
+            .filter(|chunk| matches!(chunk, MergeChunk::Conflict(_)))
+            .nth(index);
+        let Some(chunk) = chunk else {
+            return false;
+        };
+        if let MergeChunk::Conflict(conflict) = chunk {
+            *chunk = MergeChunk::Resolved(conflict.resolve(resolution));
+        }
+        true
+    }
+
+    /// Resolve every remaining conflict the same way
+    pub fn resolve_all(&mut self, resolution: Resolution) {
+        while self.resolve(0, resolution) {}
+    }
+
+    /// Merged text, once every conflict is resolved
+    pub fn text(&self) -> Option<String> {
+        let mut text = String::new();
+        for chunk in &self.chunks {
+            match chunk {
+                MergeChunk::Resolved(lines) => text.extend(lines.iter().map(String::as_str)),
+                MergeChunk::Conflict(_) => return None,
+            }
+        }
+        Some(text)
+    }
+
+    /// Merged text with each conflict between `<<<<<<<`, `=======` and `>>>>>>>` markers
+    pub fn marked_text(&self) -> String {
+        let mut text = String::new();
+        for chunk in &self.chunks {
+            match chunk {
+                MergeChunk::Resolved(lines) => text.extend(lines.iter().map(String::as_str)),
+                MergeChunk::Conflict(conflict) => {
+                    if !text.is_empty() && !text.ends_with('\n') {
+                        text.push('\n');
+                    }
+                    for (marker, lines) in [
+                        (MINE_MARKER, &conflict.mine),
+                        (SEPARATOR_MARKER, &conflict.theirs),
+                    ] {
+                        text.push_str(marker);
+                        text.push('\n');
+                        text.extend(terminated(lines));
+                    }
+                    text.push_str(THEIRS_MARKER);
+                    text.push('\n');
+                }
+            }
+        }
+        text
+    }
+
+    /// Append lines, joining them to a preceding resolved region
+    fn push_resolved(&mut self, lines: &[&str]) {
+        if lines.is_empty() {
+            return;
+        }
+        match self.chunks.last_mut() {
+            Some(MergeChunk::Resolved(resolved)) => resolved.extend(to_owned(lines)),
+            _ => self.chunks.push(MergeChunk::Resolved(to_owned(lines))),
+        }
+    }
+}
+
+/// Index in `other` of each base line it kept
+fn base_matches(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; base.len()];
+    for (base_index, other_index) in compute_lcs(base, other) {
+        matches[base_index] = Some(other_index);
+    }
+    matches
+}
+
+fn to_owned(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+}
+
+/// Lines with a line ending added to the last one if it has none
+fn terminated(lines: &[String]) -> Vec<String> {
+    let mut lines = lines.to_vec();
+    if let Some(last) = lines.last_mut().filter(|last| !last.ends_with('\n')) {
+        last.push('\n');
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "fn main() {\n    let a = 1;\n    let b = 2;\n    println!(\"{}\", a + b);\n}\n";
+
+    #[test]
+    fn test_merge_without_conflicts() {
+        let mine = BASE.replace("let a = 1", "let a = 10");
+        let theirs = BASE.replace("println!", "// sum\n    println!");
+
+        let merge = Merge::new(BASE, &mine, &theirs);
+        assert!(!merge.has_conflicts());
+        assert_eq!(
+            merge.text().unwrap(),
+            "fn main() {\n    let a = 10;\n    let b = 2;\n    // sum\n    println!(\"{}\", a + b);\n}\n"
+        );
+
+        // Identical changes on both sides are taken once
+        let merge = Merge::new(BASE, &mine, &mine);
+        assert_eq!(merge.text().unwrap(), mine);
+    }
+
+    #[test]
+    fn test_conflict_resolution() {
+        let mine = BASE.replace("let b = 2", "let b = 3");
+        let theirs = BASE.replace("let b = 2", "let b = 4");
+
+        let mut merge = Merge::new(BASE, &mine, &theirs);
+        assert_eq!(merge.conflict_count(), 1);
+        assert_eq!(merge.text(), None);
+        assert_eq!(
+            merge.conflicts().next().unwrap(),
+            &Conflict {
+                base: vec!["    let b = 2;\n".to_string()],
+                mine: vec!["    let b = 3;\n".to_string()],
+                theirs: vec!["    let b = 4;\n".to_string()],
+            }
+        );
+        assert!(merge.marked_text().contains(
+            "<<<<<<< mine\n    let b = 3;\n=======\n    let b = 4;\n>>>>>>> agent\n"
+        ));
+
+        let mut both = merge.clone();
+        assert!(both.resolve(0, Resolution::Both));
+        assert!(both.text().unwrap().contains("let b = 3;\n    let b = 4;\n"));
+
+        assert!(!merge.resolve(1, Resolution::Mine));
+        assert!(merge.resolve(0, Resolution::Theirs));
+        assert_eq!(merge.text().unwrap(), theirs);
+    }
+
+    #[test]
+    fn test_conflict_at_end_without_newline() {
+        let mut merge = Merge::new("a\nb", "a\nmine", "a\ntheirs");
+        assert_eq!(merge.marked_text(), "a\n<<<<<<< mine\nmine\n=======\ntheirs\n>>>>>>> agent\n");
+
+        merge.resolve_all(Resolution::Both);
+        assert_eq!(merge.text().unwrap(), "a\nmine\ntheirs");
+    }
+}
@@ -3,17 +3,25 @@
 //! Implements the Stage pane for displaying and editing artifacts:
 //! - Code/text view with syntax highlighting
 //! - Diff view for comparing versions
-//! - Editable and read-only modes
+//! - Editable and read-only modes, with user edits saved back as diffs
+//! - Three-way merge of agent updates into edited artifacts
 //! - STATE_DELTA hydration
 //! - Caching and chunking for large artifacts
+//! - Live terminal sessions with ANSI colors
 
+pub mod ansi;
+pub mod buffer;
 pub mod cache;
 pub mod components;
 pub mod diff;
+pub mod edit;
+pub mod merge;
 pub mod state;
 pub mod syntax;
 pub mod types;
 
+pub use buffer::{EditBuffer, EditCommand, Motion};
 pub use components::*;
-pub use state::StageState;
+pub use merge::{Merge, MergeChunk, Resolution};
+pub use state::{ArtifactUpdateError, StageState};
 pub use types::*;
@@ -8,10 +8,39 @@
 
 use std::collections::HashMap;
 
+use thiserror::Error;
+
+use super::buffer::{EditBuffer, EditCommand};
 use super::cache::ArtifactCache;
+use super::diff::{apply_hunks, compute_unified_diff, parse_hunks};
+use super::diff::{DiffAlgorithm, DiffOptions, PatchError};
+use super::edit::EditError;
+use super::merge::{Merge, Resolution};
 use super::syntax::SyntaxHighlighter;
 use super::types::{Artifact, ArtifactContent, ArtifactId, DiffContent, ViewMode};
-use crate::protocol::{ArtifactOpen, ArtifactUpdate, StateDelta};
+use crate::protocol::{ArtifactOpen, ArtifactSave, ArtifactUpdate, StateDelta};
+
+/// Why an ARTIFACT_UPDATE could not be applied
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ArtifactUpdateError {
+    #[error("Unknown artifact: {0}")]
+    UnknownArtifact(String),
+
+    #[error("Unsupported change type: {0}")]
+    UnsupportedChangeType(String),
+
+    #[error("Stale update: made against version {base}, artifact is at version {current}")]
+    StaleVersion { base: u64, current: u64 },
+
+    #[error("Partial updates require a base version")]
+    MissingBaseVersion,
+
+    #[error("Failed to apply diff: {0}")]
+    Patch(#[from] PatchError),
+
+    #[error("Failed to apply edits: {0}")]
+    Edit(#[from] EditError),
+}
 
 /// State for the Stage pane
 #[derive(Debug)]
@@ -30,12 +59,18 @@ pub struct StageState {
     word_wrap: bool,
     /// Font size in pixels
     font_size: f32,
+    /// Algorithm and context for diff views
+    diff_options: DiffOptions,
     /// Syntax highlighters cache
     highlighters: HashMap<String, SyntaxHighlighter>,
     /// Content cache
     cache: ArtifactCache,
     /// Scroll position per artifact
     scroll_positions: HashMap<ArtifactId, f32>,
+    /// Edit buffers of artifacts the user has edited
+    buffers: HashMap<ArtifactId, EditBuffer>,
+    /// Agent updates merged into edited artifacts with conflicts left to resolve
+    merges: HashMap<ArtifactId, Merge>,
 }
 
 impl Default for StageState {
@@ -55,9 +90,12 @@ impl StageState {
             show_line_numbers: true,
             word_wrap: false,
             font_size: 13.0,
+            diff_options: DiffOptions::default(),
             highlighters: HashMap::new(),
             cache: ArtifactCache::new(),
             scroll_positions: HashMap::new(),
+            buffers: HashMap::new(),
+            merges: HashMap::new(),
         }
     }
 
@@ -84,6 +122,8 @@ impl StageState {
             self.artifact_order.push(event.id.clone());
         }
         self.artifacts.insert(event.id.clone(), artifact);
+        self.buffers.remove(&event.id);
+        self.merges.remove(&event.id);
 
         // Make it active
         self.active_artifact = Some(event.id.clone());
@@ -92,29 +132,79 @@ impl StageState {
     }
 
     /// Update an artifact from protocol event
-    pub fn update_artifact(&mut self, event: &ArtifactUpdate) -> bool {
-        if let Some(artifact) = self.artifacts.get_mut(&event.id) {
-            match event.change_type.as_str() {
-                "full_replace" => {
-                    artifact.update_content(&event.content);
-                }
-                "diff" | "patch" => {
-                    artifact.apply_diff(&event.content);
-                }
-                _ => {
-                    artifact.update_content(&event.content);
-                }
-            }
+    ///
+    /// Updates with a `base_version` other than the artifact's version are
+    /// rejected; applied updates advance the version. The artifact is left
+    /// unchanged if the update cannot be applied.
+    ///
+    /// If the user has unsaved edits, the update is applied to the
+    /// orchestrator's content and three-way merged with the edits. Conflicts
+    /// stay in the content as marked regions until resolved with
+    /// `resolve_conflict`.
+    pub fn update_artifact(&mut self, event: &ArtifactUpdate) -> Result<(), ArtifactUpdateError> {
+        let Some(artifact) = self.artifacts.get_mut(&event.id) else {
+            tracing::warn!("Update for unknown artifact: {}", event.id);
+            return Err(ArtifactUpdateError::UnknownArtifact(event.id.clone()));
+        };
 
-            // Invalidate cache for this artifact
-            self.cache.invalidate_artifact(&event.id);
+        if let Some(base) = event.base_version {
+            if base != artifact.version {
+                return Err(ArtifactUpdateError::StaleVersion {
+                    base,
+                    current: artifact.version,
+                });
+            }
+        }
 
-            tracing::debug!("Updated artifact: {}", event.id);
-            true
+        // Set the user's edits aside, taking their side of any open conflicts.
+        // Read-only content has no edits and no `server_content` to merge with.
+        let current = artifact.content_str().to_string();
+        let dirty = artifact.dirty;
+        let base = artifact.server_content.clone();
+        let mine = if !artifact.is_editable() {
+            None
+        } else if let Some(merge) = self.merges.get(&event.id) {
+            let mut merge = merge.clone();
+            merge.resolve_all(Resolution::Mine);
+            merge.text()
         } else {
-            tracing::warn!("Update for unknown artifact: {}", event.id);
-            false
+            dirty.then(|| current.clone())
+        };
+        if mine.is_some() {
+            artifact.edit_content(&base);
+        }
+
+        if let Err(e) = apply_update(artifact, event) {
+            artifact.edit_content(&current);
+            artifact.dirty = dirty;
+            return Err(e);
+        }
+        artifact.version += 1;
+        artifact.mark_synced();
+        self.buffers.remove(&event.id);
+        self.merges.remove(&event.id);
+
+        if let Some(mine) = mine {
+            let merge = Merge::new(&base, &mine, artifact.content_str());
+            match merge.text() {
+                Some(merged) => artifact.edit_content(&merged),
+                None => {
+                    tracing::info!(
+                        "Update to {} conflicts with unsaved edits ({} conflicts)",
+                        event.id,
+                        merge.conflict_count()
+                    );
+                    artifact.edit_content(&merge.marked_text());
+                    self.merges.insert(event.id.clone(), merge);
+                }
+            }
         }
+
+        // Invalidate cache for this artifact
+        self.cache.invalidate_artifact(&event.id);
+
+        tracing::debug!("Updated artifact: {}", event.id);
+        Ok(())
     }
 
     /// Close an artifact
@@ -122,6 +212,8 @@ impl StageState {
         if self.artifacts.remove(id).is_some() {
             self.artifact_order.retain(|i| i != id);
             self.scroll_positions.remove(id);
+            self.buffers.remove(id);
+            self.merges.remove(id);
             self.cache.invalidate_artifact(id);
 
             // Update active artifact if we closed the active one
@@ -142,6 +234,8 @@ impl StageState {
         self.artifact_order.clear();
         self.active_artifact = None;
         self.scroll_positions.clear();
+        self.buffers.clear();
+        self.merges.clear();
         self.cache.clear();
     }
 
@@ -252,6 +346,8 @@ impl StageState {
         // - "stage.show_line_numbers" - Update line numbers setting
         // - "stage.word_wrap" - Update word wrap setting
         // - "stage.font_size" - Update font size
+        // - "stage.diff_algorithm" - Update diff algorithm ("myers" or "patience")
+        // - "stage.diff_context_lines" - Update context lines around diff changes
 
         let parts: Vec<&str> = delta.path.split('.').collect();
 
@@ -260,6 +356,10 @@ impl StageState {
                 if let Some(content) = delta.new_value.as_str() {
                     if let Some(artifact) = self.artifacts.get_mut(*id) {
                         artifact.update_content(content);
+                        artifact.version += 1;
+                        artifact.mark_synced();
+                        self.buffers.remove(*id);
+                        self.merges.remove(*id);
                         self.cache.invalidate_artifact(id);
                         return true;
                     }
@@ -311,6 +411,21 @@ impl StageState {
                     return true;
                 }
             }
+            ["stage", "diff_algorithm"] => {
+                let algorithm = match delta.new_value.as_str() {
+                    Some("myers") => DiffAlgorithm::Myers,
+                    Some("patience") => DiffAlgorithm::Patience,
+                    _ => return false,
+                };
+                self.diff_options.algorithm = algorithm;
+                return true;
+            }
+            ["stage", "diff_context_lines"] => {
+                if let Some(lines) = delta.new_value.as_u64() {
+                    self.diff_options.context_lines = lines as usize;
+                    return true;
+                }
+            }
             _ => {
                 tracing::debug!("Unknown state delta path: {}", delta.path);
             }
@@ -366,6 +481,16 @@ impl StageState {
         self.font_size = size.clamp(8.0, 32.0);
     }
 
+    /// Get the diff algorithm and context
+    pub fn diff_options(&self) -> &DiffOptions {
+        &self.diff_options
+    }
+
+    /// Set the diff algorithm and context
+    pub fn set_diff_options(&mut self, options: DiffOptions) {
+        self.diff_options = options;
+    }
+
     // ==================== Syntax Highlighting ====================
 
     /// Get or create a syntax highlighter for a language
@@ -408,10 +533,16 @@ impl StageState {
 
     // ==================== Diff Support ====================
 
+    /// Get the diff between two versions of an artifact, computed once per change
+    pub fn artifact_diff(&mut self, artifact_id: &str, original: &str, modified: &str) -> &DiffContent {
+        self.cache.diff(artifact_id, original, modified, &self.diff_options)
+    }
+
     /// Create a diff view between two versions
     pub fn create_diff(&mut self, artifact_id: &str, old_content: &str, new_content: &str) {
         if let Some(artifact) = self.artifacts.get_mut(artifact_id) {
-            artifact.content = ArtifactContent::Diff(DiffContent::new(old_content, new_content));
+            let diff = DiffContent::with_options(old_content, new_content, &self.diff_options);
+            artifact.content = ArtifactContent::Diff(diff);
             self.view_mode = ViewMode::Unified;
             self.cache.invalidate_artifact(artifact_id);
         }
@@ -436,12 +567,108 @@ impl StageState {
             if !artifact.read_only {
                 artifact.update_content(new_content);
                 if let Some(id) = self.active_artifact.clone() {
+                    self.buffers.remove(&id);
+                    self.merges.remove(&id);
                     self.cache.invalidate_artifact(&id);
                 }
             }
         }
     }
 
+    /// Get the edit buffer of an artifact the user has edited
+    pub fn edit_buffer(&self, id: &str) -> Option<&EditBuffer> {
+        self.buffers.get(id)
+    }
+
+    /// Apply an edit command to the active artifact
+    ///
+    /// Returns false if there is no active artifact, it is not editable or
+    /// it has unresolved conflicts. `EditCommand::Save` is not handled here;
+    /// see `save_artifact`.
+    pub fn edit_active(&mut self, command: &EditCommand) -> bool {
+        let Some(id) = self.active_artifact.clone() else {
+            return false;
+        };
+        if self.merges.contains_key(&id) {
+            return false;
+        }
+        let Some(artifact) = self.artifacts.get_mut(&id).filter(|a| a.is_editable()) else {
+            return false;
+        };
+
+        let buffer = self
+            .buffers
+            .entry(id.clone())
+            .or_insert_with(|| EditBuffer::new(artifact.content_str()));
+        if buffer.apply(command) {
+            artifact.edit_content(buffer.text());
+            self.cache.invalidate_artifact(&id);
+        }
+        true
+    }
+
+    /// Collect the user's edits to an artifact for sending
+    ///
+    /// Returns None if the artifact has no unsaved changes or unresolved
+    /// conflicts. Otherwise the saved content becomes the artifact's next
+    /// version.
+    pub fn save_artifact(&mut self, id: &str) -> Option<ArtifactSave> {
+        if self.merges.contains_key(id) {
+            return None;
+        }
+        let artifact = self.artifacts.get_mut(id).filter(|a| a.dirty)?;
+        let content = artifact.content_str();
+        let hunks = compute_unified_diff(&artifact.server_content, content);
+        let diff = if hunks.is_empty() {
+            hunks
+        } else {
+            format!("--- a/{}\n+++ b/{}\n{}", id, id, hunks)
+        };
+        // Fall back to the full content when the diff loses a change, such as
+        // adding or removing only the trailing newline
+        let reproduces = apply_hunks(&artifact.server_content, &parse_hunks(&diff))
+            .is_ok_and(|patched| patched == content);
+        let save = ArtifactSave {
+            id: id.to_string(),
+            base_version: artifact.version,
+            content: (!reproduces).then(|| content.to_string()),
+            diff,
+        };
+        artifact.version += 1;
+        artifact.mark_synced();
+        Some(save)
+    }
+
+    /// Get the unresolved merge of an agent update into an edited artifact
+    pub fn merge(&self, id: &str) -> Option<&Merge> {
+        self.merges.get(id)
+    }
+
+    /// Resolve one conflict of an artifact's merge
+    ///
+    /// `index` counts unresolved conflicts only. Once the last conflict is
+    /// resolved the merged text replaces the marked content. Returns false
+    /// if there is no such conflict.
+    pub fn resolve_conflict(&mut self, id: &str, index: usize, resolution: Resolution) -> bool {
+        let (Some(merge), Some(artifact)) = (self.merges.get_mut(id), self.artifacts.get_mut(id)) else {
+            return false;
+        };
+        if !merge.resolve(index, resolution) {
+            return false;
+        }
+
+        match merge.text() {
+            Some(merged) => {
+                artifact.edit_content(&merged);
+                self.merges.remove(id);
+            }
+            None => artifact.edit_content(&merge.marked_text()),
+        }
+        self.buffers.remove(id);
+        self.cache.invalidate_artifact(id);
+        true
+    }
+
     /// Mark active artifact as saved
     pub fn mark_saved(&mut self) {
         if let Some(artifact) = self.active_artifact_mut() {
@@ -472,6 +699,23 @@ impl StageState {
     }
 }
 
+/// Apply an update's change to an artifact's content
+fn apply_update(artifact: &mut Artifact, event: &ArtifactUpdate) -> Result<(), ArtifactUpdateError> {
+    match event.change_type.as_str() {
+        "full_replace" => artifact.update_content(&event.content),
+        "diff" | "patch" => artifact.apply_diff(&event.content)?,
+        "partial" => {
+            if event.base_version.is_none() {
+                return Err(ArtifactUpdateError::MissingBaseVersion);
+            }
+            artifact.apply_edits(&event.edits)?
+        }
+        "append" => artifact.append_content(&event.content),
+        other => return Err(ArtifactUpdateError::UnsupportedChangeType(other.to_string())),
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -542,10 +786,78 @@ mod tests {
             id: "1".to_string(),
             content: "modified".to_string(),
             change_type: "full_replace".to_string(),
+            base_version: None,
+            edits: Vec::new(),
         };
 
-        assert!(state.update_artifact(&update));
+        assert!(state.update_artifact(&update).is_ok());
         assert_eq!(state.active_artifact().unwrap().content_str(), "modified");
+
+        let update = |change_type: &str, content: &str| ArtifactUpdate {
+            id: "1".to_string(),
+            content: content.to_string(),
+            change_type: change_type.to_string(),
+            base_version: None,
+            edits: Vec::new(),
+        };
+        assert!(state.update_artifact(&update("diff", "@@ -1 +1 @@\n-modified\n+patched\n")).is_ok());
+        assert_eq!(state.active_artifact().unwrap().content_str(), "patched");
+
+        assert!(matches!(
+            state.update_artifact(&update("diff", "@@ -1 +1 @@\n-stale\n+patched\n")),
+            Err(ArtifactUpdateError::Patch(_))
+        ));
+        assert_eq!(
+            state.update_artifact(&update("rewrite", "x")),
+            Err(ArtifactUpdateError::UnsupportedChangeType("rewrite".to_string()))
+        );
+        assert_eq!(state.active_artifact().unwrap().content_str(), "patched");
+    }
+
+    #[test]
+    fn test_partial_update_versioning() {
+        use crate::protocol::{ArtifactEdit, TextPosition};
+
+        let mut state = StageState::new();
+        state.open_artifact(&make_artifact_open("1", "test.rs", "let x = 1;\n"));
+        assert_eq!(state.get_artifact("1").unwrap().version, 0);
+
+        let insert = |base_version: Option<u64>, text: &str| ArtifactUpdate {
+            id: "1".to_string(),
+            content: String::new(),
+            change_type: "partial".to_string(),
+            base_version,
+            edits: vec![ArtifactEdit::Insert {
+                position: TextPosition::new(1, 0),
+                text: text.to_string(),
+            }],
+        };
+
+        assert!(state.update_artifact(&insert(Some(0), "let y = 2;\n")).is_ok());
+        assert!(state.update_artifact(&insert(Some(1), "let z = 3;\n")).is_ok());
+        let artifact = state.get_artifact("1").unwrap();
+        assert_eq!(artifact.content_str(), "let x = 1;\nlet z = 3;\nlet y = 2;\n");
+        assert_eq!(artifact.version, 2);
+
+        // Stale, unversioned and out-of-range edits are rejected without a version bump
+        assert_eq!(
+            state.update_artifact(&insert(Some(1), "stale")),
+            Err(ArtifactUpdateError::StaleVersion { base: 1, current: 2 })
+        );
+        assert_eq!(
+            state.update_artifact(&insert(None, "unversioned")),
+            Err(ArtifactUpdateError::MissingBaseVersion)
+        );
+        let mut out_of_range = insert(Some(2), "x");
+        out_of_range.edits = vec![ArtifactEdit::Insert {
+            position: TextPosition::new(9, 0),
+            text: "x".to_string(),
+        }];
+        assert!(matches!(
+            state.update_artifact(&out_of_range),
+            Err(ArtifactUpdateError::Edit(_))
+        ));
+        assert_eq!(state.get_artifact("1").unwrap().version, 2);
     }
 
     #[test]
@@ -563,6 +875,26 @@ mod tests {
         assert_eq!(state.get_artifact("1").unwrap().content_str(), "updated");
     }
 
+    #[test]
+    fn test_diff_options_delta() {
+        let mut state = StageState::new();
+        let delta = |path: &str, value: serde_json::Value| StateDelta {
+            path: path.to_string(),
+            old_value: None,
+            new_value: value,
+        };
+
+        assert!(state.apply_state_delta(&delta("stage.diff_algorithm", serde_json::json!("patience"))));
+        assert!(state.apply_state_delta(&delta("stage.diff_context_lines", serde_json::json!(1))));
+        assert!(!state.apply_state_delta(&delta("stage.diff_algorithm", serde_json::json!("lcs"))));
+        assert_eq!(
+            state.diff_options(),
+            &DiffOptions::new()
+                .with_algorithm(DiffAlgorithm::Patience)
+                .with_context_lines(1)
+        );
+    }
+
     #[test]
     fn test_view_mode_cycling() {
         let mut state = StageState::new();
@@ -575,6 +907,140 @@ mod tests {
         assert_eq!(state.view_mode(), ViewMode::Unified);
     }
 
+    #[test]
+    fn test_save_trailing_newline_change() {
+        use crate::stage::{EditCommand, Motion};
+
+        let mut state = StageState::new();
+        state.open_artifact(&make_artifact_open("1", "test.rs", "let x = 1;\n"));
+        state.edit_active(&EditCommand::Move { motion: Motion::DocumentEnd, extend: false });
+        state.edit_active(&EditCommand::Backspace);
+        assert_eq!(state.active_artifact().unwrap().content_str(), "let x = 1;");
+
+        // The line diff is empty, so the full content is sent
+        let save = state.save_artifact("1").unwrap();
+        assert_eq!(save.diff, "");
+        assert_eq!(save.content.as_deref(), Some("let x = 1;"));
+        assert_eq!(state.get_artifact("1").unwrap().server_content, "let x = 1;");
+    }
+
+    #[test]
+    fn test_edit_and_save() {
+        use crate::stage::{EditCommand, Motion};
+        use crate::stage::diff::{apply_hunks, parse_hunks};
+
+        let mut state = StageState::new();
+        state.open_artifact(&make_artifact_open("1", "test.rs", "let x = 1;\nlet y = 2;\n"));
+        assert!(state.save_artifact("1").is_none());
+
+        let commands = [
+            EditCommand::Move { motion: Motion::Down, extend: false },
+            EditCommand::Move { motion: Motion::LineEnd, extend: true },
+            EditCommand::Insert("let y = 3;".to_string()),
+        ];
+        for command in &commands {
+            assert!(state.edit_active(command));
+        }
+        assert_eq!(state.active_artifact().unwrap().content_str(), "let x = 1;\nlet y = 3;\n");
+        assert!(state.has_unsaved_changes());
+
+        // Undoing back to the server version is not a change
+        state.edit_active(&EditCommand::Undo);
+        assert!(!state.has_unsaved_changes());
+        state.edit_active(&EditCommand::Redo);
+
+        let save = state.save_artifact("1").unwrap();
+        assert_eq!(save.base_version, 0);
+        assert!(save.diff.starts_with("--- a/1\n+++ b/1\n@@"));
+        assert_eq!(save.content, None);
+        assert_eq!(
+            apply_hunks("let x = 1;\nlet y = 2;\n", &parse_hunks(&save.diff)).unwrap(),
+            "let x = 1;\nlet y = 3;\n"
+        );
+        let artifact = state.get_artifact("1").unwrap();
+        assert_eq!(artifact.version, 1);
+        assert!(!artifact.dirty);
+        assert_eq!(artifact.server_content, "let x = 1;\nlet y = 3;\n");
+        assert!(state.edit_buffer("1").unwrap().can_undo());
+
+        // Read-only artifacts are not editable
+        let mut read_only = make_artifact_open("2", "log.txt", "log");
+        read_only.read_only = true;
+        state.open_artifact(&read_only);
+        assert!(!state.edit_active(&EditCommand::Insert("x".to_string())));
+        assert!(state.edit_buffer("2").is_none());
+    }
+
+    #[test]
+    fn test_failed_update_keeps_read_only_artifact_clean() {
+        let mut state = StageState::new();
+        let mut open = make_artifact_open("1", "log.txt", "one\ntwo\n");
+        open.read_only = true;
+        state.open_artifact(&open);
+        let diff = |content: &str| ArtifactUpdate {
+            id: "1".to_string(),
+            content: content.to_string(),
+            change_type: "diff".to_string(),
+            base_version: None,
+            edits: Vec::new(),
+        };
+
+        let bad = diff("@@ -1,2 +1,2 @@\n-missing\n-lines\n+x\n+y\n");
+        assert!(state.update_artifact(&bad).is_err());
+        let artifact = state.get_artifact("1").unwrap();
+        assert_eq!(artifact.content_str(), "one\ntwo\n");
+        assert!(!artifact.dirty);
+
+        let good = diff("@@ -1,2 +1,2 @@\n one\n-two\n+three\n");
+        state.update_artifact(&good).unwrap();
+        let artifact = state.get_artifact("1").unwrap();
+        assert_eq!(artifact.content_str(), "one\nthree\n");
+        assert!(!artifact.dirty);
+        assert!(state.merge("1").is_none());
+        assert!(!state.has_unsaved_changes());
+    }
+
+    #[test]
+    fn test_update_merges_unsaved_edits() {
+        use crate::stage::{EditCommand, Motion, Resolution};
+
+        let mut state = StageState::new();
+        state.open_artifact(&make_artifact_open("1", "test.rs", "a\nb\nc\nd\n"));
+        let replace = |content: &str| ArtifactUpdate {
+            id: "1".to_string(),
+            content: content.to_string(),
+            change_type: "full_replace".to_string(),
+            base_version: None,
+            edits: Vec::new(),
+        };
+
+        // The user edits the first line, the agent the last
+        state.edit_active(&EditCommand::Insert("x".to_string()));
+        state.update_artifact(&replace("a\nb\nc\nz\n")).unwrap();
+        let artifact = state.get_artifact("1").unwrap();
+        assert_eq!(artifact.content_str(), "xa\nb\nc\nz\n");
+        assert_eq!(artifact.server_content, "a\nb\nc\nz\n");
+        assert!(artifact.dirty);
+        assert!(state.merge("1").is_none());
+
+        // Both change the third line
+        for _ in 0..2 {
+            state.edit_active(&EditCommand::Move { motion: Motion::Down, extend: false });
+        }
+        state.edit_active(&EditCommand::Insert("y".to_string()));
+        state.update_artifact(&replace("a\nb\nC\nz\n")).unwrap();
+        assert_eq!(state.merge("1").unwrap().conflict_count(), 1);
+        assert!(state.get_artifact("1").unwrap().content_str().contains("<<<<<<< mine\nyc\n=======\nC\n"));
+        assert!(!state.edit_active(&EditCommand::Insert("q".to_string())));
+        assert!(state.save_artifact("1").is_none());
+
+        assert!(!state.resolve_conflict("1", 1, Resolution::Mine));
+        assert!(state.resolve_conflict("1", 0, Resolution::Both));
+        assert!(state.merge("1").is_none());
+        assert_eq!(state.get_artifact("1").unwrap().content_str(), "xa\nb\nyc\nC\nz\n");
+        assert_eq!(state.save_artifact("1").unwrap().base_version, 2);
+    }
+
     #[test]
     fn test_dirty_tracking() {
         let mut state = StageState::new();
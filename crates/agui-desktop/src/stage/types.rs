@@ -2,12 +2,18 @@
 //!
 //! Data structures for artifacts displayed in the Stage pane.
 
+use super::diff::{apply_hunks_with_fuzz, parse_hunks, PatchError, DEFAULT_FUZZ};
+use super::edit::{apply_edits, EditError};
+use crate::protocol::ArtifactEdit;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Unique identifier for artifacts
 pub type ArtifactId = String;
 
+/// Maximum size of content built up by appending (256KB)
+pub const MAX_APPEND_BYTES: usize = 256 * 1024;
+
 /// An artifact displayed in the Stage
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Artifact {
@@ -29,6 +35,12 @@ pub struct Artifact {
     pub modified_at: chrono::DateTime<chrono::Utc>,
     /// Whether there are unsaved changes
     pub dirty: bool,
+    /// Number of orchestrator updates and saves since the artifact was opened
+    #[serde(default)]
+    pub version: u64,
+    /// Content at `version`, which user edits are diffed against
+    #[serde(default)]
+    pub server_content: String,
     /// Optional metadata
     #[serde(default)]
     pub metadata: HashMap<String, String>,
@@ -46,7 +58,7 @@ impl Artifact {
     ) -> Self {
         let now = chrono::Utc::now();
         let content_type_str = content_type.into();
-        Self {
+        let mut artifact = Self {
             id: id.into(),
             title: title.into(),
             content_type: ContentType::from_str(&content_type_str),
@@ -56,7 +68,41 @@ impl Artifact {
             opened_at: now,
             modified_at: now,
             dirty: false,
+            version: 0,
+            server_content: String::new(),
             metadata: HashMap::new(),
+        };
+        artifact.mark_synced();
+        artifact
+    }
+
+    /// Check whether the user can edit the content
+    pub fn is_editable(&self) -> bool {
+        !self.read_only
+            && self.content_type != ContentType::Terminal
+            && matches!(self.content, ArtifactContent::Text(_))
+    }
+
+    /// Record the content as the orchestrator's current version
+    ///
+    /// Clears the dirty flag. Read-only content is never diffed, so it is
+    /// not copied.
+    pub fn mark_synced(&mut self) {
+        if self.is_editable() {
+            self.server_content = self.content_str().to_string();
+        }
+        self.dirty = false;
+    }
+
+    /// Replace the content with the user's edits
+    ///
+    /// Unlike `update_content`, the previous content is kept for diff views.
+    /// The artifact is dirty while the content differs from `server_content`.
+    pub fn edit_content(&mut self, new_content: &str) {
+        if let ArtifactContent::Text(ref mut text) = self.content {
+            text.content = new_content.to_string();
+            self.dirty = text.content != self.server_content;
+            self.modified_at = chrono::Utc::now();
         }
     }
 
@@ -74,11 +120,59 @@ impl Artifact {
         }
     }
 
-    /// Apply a diff/patch to the content
-    pub fn apply_diff(&mut self, diff: &str) {
-        // For now, treat diff as content replacement
-        // TODO: Implement proper diff application
-        self.update_content(diff);
+    /// Append output to the content, e.g. for a live terminal
+    ///
+    /// The oldest output is dropped beyond `MAX_APPEND_BYTES`. Appending
+    /// does not record previous content or mark the artifact dirty.
+    pub fn append_content(&mut self, chunk: &str) {
+        if let ArtifactContent::Text(ref mut text) = self.content {
+            text.content.push_str(chunk);
+            if text.content.len() > MAX_APPEND_BYTES {
+                let mut cut = text.content.len() - MAX_APPEND_BYTES;
+                while !text.content.is_char_boundary(cut) {
+                    cut += 1;
+                }
+                text.content.drain(..cut);
+            }
+            self.modified_at = chrono::Utc::now();
+        }
+    }
+
+    /// Terminal size as (rows, cols) from the `rows`/`cols` metadata
+    pub fn terminal_size(&self) -> (usize, usize) {
+        let dimension = |key: &str, default| {
+            self.metadata
+                .get(key)
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        (
+            dimension("rows", crate::stage::ansi::DEFAULT_ROWS),
+            dimension("cols", crate::stage::ansi::DEFAULT_COLS),
+        )
+    }
+
+    /// Apply a unified diff to the content
+    ///
+    /// Hunks are matched with `DEFAULT_FUZZ`. If any hunk fails to apply
+    /// the content is left unchanged.
+    pub fn apply_diff(&mut self, diff: &str) -> Result<(), PatchError> {
+        let hunks = parse_hunks(diff);
+        if hunks.is_empty() {
+            return Err(PatchError::NoHunks);
+        }
+        let patched = apply_hunks_with_fuzz(self.content_str(), &hunks, DEFAULT_FUZZ)?;
+        self.update_content(patched);
+        Ok(())
+    }
+
+    /// Apply range edits to the content
+    ///
+    /// If any edit fails the content is left unchanged.
+    pub fn apply_edits(&mut self, edits: &[ArtifactEdit]) -> Result<(), EditError> {
+        let edited = apply_edits(self.content_str(), edits)?;
+        self.update_content(edited);
+        Ok(())
     }
 
     /// Get content as string
@@ -123,6 +217,8 @@ pub enum ContentType {
     Xml,
     /// Binary (show hex dump)
     Binary,
+    /// Terminal session output (ANSI escape sequences are interpreted)
+    Terminal,
 }
 
 impl ContentType {
@@ -137,6 +233,7 @@ impl ContentType {
             "toml" | "application/toml" => Self::Toml,
             "xml" | "html" | "text/xml" | "text/html" => Self::Xml,
             "binary" | "application/octet-stream" => Self::Binary,
+            "terminal" | "tty" | "pty" => Self::Terminal,
             _ => Self::Text,
         }
     }
@@ -152,6 +249,7 @@ impl ContentType {
             Self::Toml => "TOML",
             Self::Xml => "XML",
             Self::Binary => "Binary",
+            Self::Terminal => "Terminal",
         }
     }
 }
@@ -222,9 +320,18 @@ pub struct DiffContent {
 
 impl DiffContent {
     pub fn new(original: impl Into<String>, modified: impl Into<String>) -> Self {
+        Self::with_options(original, modified, &crate::stage::diff::DiffOptions::default())
+    }
+
+    /// Compute the diff with the given algorithm and context
+    pub fn with_options(
+        original: impl Into<String>,
+        modified: impl Into<String>,
+        options: &crate::stage::diff::DiffOptions,
+    ) -> Self {
         let original = original.into();
         let modified = modified.into();
-        let unified = crate::stage::diff::compute_unified_diff(&original, &modified);
+        let unified = crate::stage::diff::compute_unified_diff_with(&original, &modified, options);
         let hunks = crate::stage::diff::parse_hunks(&unified);
         Self {
             original,
@@ -339,6 +446,7 @@ mod tests {
         assert_eq!(ContentType::from_str("application/json"), ContentType::Json);
         assert_eq!(ContentType::from_str("markdown"), ContentType::Markdown);
         assert_eq!(ContentType::from_str("unknown"), ContentType::Text);
+        assert_eq!(ContentType::from_str("terminal"), ContentType::Terminal);
     }
 
     #[test]
@@ -354,6 +462,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_apply_diff() {
+        let mut artifact = Artifact::from_open("test", "test.txt", "a\nb\nc\n", "text", false, None);
+
+        artifact.apply_diff("@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n").unwrap();
+        assert_eq!(artifact.content_str(), "a\nB\nc\n");
+
+        // Failed patches leave the content alone
+        assert!(artifact.apply_diff("@@ -1,2 +1,2 @@\n x\n-y\n+z\n").is_err());
+        assert_eq!(artifact.apply_diff("not a diff"), Err(PatchError::NoHunks));
+        assert_eq!(artifact.content_str(), "a\nB\nc\n");
+    }
+
+    #[test]
+    fn test_append_content() {
+        let mut artifact = Artifact::from_open("term", "bash", "$ ", "terminal", true, None);
+
+        artifact.append_content("ls\r\n");
+        assert_eq!(artifact.content_str(), "$ ls\r\n");
+        assert!(!artifact.dirty);
+        if let ArtifactContent::Text(ref t) = artifact.content {
+            assert!(t.previous_content.is_none());
+        }
+
+        artifact.append_content(&"é".repeat(MAX_APPEND_BYTES));
+        assert!(artifact.content_str().len() <= MAX_APPEND_BYTES);
+        assert!(artifact.content_str().ends_with('é'));
+    }
+
     #[test]
     fn test_line_count() {
         let artifact = Artifact::from_open("test", "test.txt", "line1\nline2\nline3", "text", false, None);
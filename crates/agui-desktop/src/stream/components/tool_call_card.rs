@@ -6,7 +6,10 @@
 use gpui::{div, prelude::*, px, AnyElement, Context, Window};
 
 use super::colors;
-use crate::stream::types::{ToolCallBlock, ToolCallStatus};
+use crate::stream::types::{ToolCallBlock, ToolCallStatus, TOOL_OUTPUT_TAIL_LINES};
+
+/// Maximum files, matches or diff lines shown in a structured result
+pub const RESULT_VIEW_MAX_ITEMS: usize = 20;
 
 /// Render a tool call card
 pub fn render_tool_call_card<V: 'static>(
@@ -23,6 +26,7 @@ pub fn render_tool_call_card<V: 'static>(
     let expanded = tool_call.expanded;
     let progress = tool_call.progress;
     let duration_ms = tool_call.duration_ms;
+    let has_output = !tool_call.output.is_empty();
 
     div()
         .w_full()
@@ -106,6 +110,10 @@ pub fn render_tool_call_card<V: 'static>(
                 .when(status == ToolCallStatus::Running && progress.is_some(), |el| {
                     el.child(render_full_progress_bar(progress.unwrap_or(0)))
                 })
+                // Live output tail
+                .when(has_output, |el| el.child(render_output_log(tool_call)))
+                // File list, search matches or diff
+                .when_some(render_result_view(tool_call), |el, view| el.child(view))
                 // Parameters (when expanded)
                 .when(expanded, |el| {
                     el.child(
@@ -244,6 +252,159 @@ fn render_json_section(title: &str, value: &serde_json::Value) -> gpui::Div {
         )
 }
 
+/// Render the most recent output lines, following the tail as output grows
+pub fn render_output_log(tool_call: &ToolCallBlock) -> gpui::Div {
+    div()
+        .flex()
+        .flex_col()
+        .mx_3()
+        .mb_2()
+        .px_2()
+        .py_1()
+        .bg(colors::tool_param_bg())
+        .rounded_sm()
+        .children(tool_call.output_tail(TOOL_OUTPUT_TAIL_LINES).into_iter().map(|line| {
+            div()
+                .text_xs()
+                .font_family("monospace")
+                .text_color(colors::text_primary())
+                .child(line.to_string())
+        }))
+}
+
+/// Structured rendering of a tool result
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResultView {
+    /// Plain list of paths (`glob_files`)
+    Files(Vec<String>),
+    /// Matching lines grouped by file (`search_files`)
+    Matches(Vec<(String, Vec<(u64, String)>)>),
+    /// Per-file unified diffs (`apply_patch`)
+    Diffs(Vec<FileDiff>),
+}
+
+/// One file of a diff result
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDiff {
+    pub path: String,
+    pub status: String,
+    pub diff: String,
+}
+
+impl ResultView {
+    /// Recognize a result shape that has a structured rendering
+    ///
+    /// `matches` (objects with `path`, `line` and `text`) renders as
+    /// grouped matches, `files` objects carrying a `diff` as diffs, and
+    /// `files` strings as a file list.
+    pub fn from_result(result: &serde_json::Value) -> Option<Self> {
+        if let Some(matches) = result.get("matches").and_then(|m| m.as_array()) {
+            let mut groups: Vec<(String, Vec<(u64, String)>)> = Vec::new();
+            for m in matches {
+                let path = m.get("path")?.as_str()?.to_string();
+                let line = m.get("line")?.as_u64()?;
+                let text = m.get("text")?.as_str()?.to_string();
+                match groups.last_mut() {
+                    Some((last, lines)) if *last == path => lines.push((line, text)),
+                    _ => groups.push((path, vec![(line, text)])),
+                }
+            }
+            return Some(Self::Matches(groups));
+        }
+
+        let files = result.get("files")?.as_array()?;
+        if files.iter().all(|f| f.is_string()) {
+            return Some(Self::Files(
+                files.iter().filter_map(|f| f.as_str().map(str::to_string)).collect(),
+            ));
+        }
+        files
+            .iter()
+            .map(|f| {
+                Some(FileDiff {
+                    path: f.get("path")?.as_str()?.to_string(),
+                    status: f.get("status").and_then(|s| s.as_str()).unwrap_or("modified").to_string(),
+                    diff: f.get("diff")?.as_str()?.to_string(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Self::Diffs)
+    }
+}
+
+/// Render a completed tool call's result as a file list, matches or diff
+///
+/// Returns None when the result has no structured shape; the JSON result
+/// section still shows it when the card is expanded.
+pub fn render_result_view(tool_call: &ToolCallBlock) -> Option<gpui::Div> {
+    let view = ResultView::from_result(tool_call.result.as_ref()?)?;
+    let mut rows: Vec<gpui::Div> = Vec::new();
+
+    match view {
+        ResultView::Files(files) => {
+            if files.is_empty() {
+                rows.push(result_line("No files matched", colors::text_muted()));
+            }
+            for path in &files {
+                rows.push(result_line(path, colors::text_primary()));
+            }
+        }
+        ResultView::Matches(groups) => {
+            if groups.is_empty() {
+                rows.push(result_line("No matches", colors::text_muted()));
+            }
+            for (path, lines) in &groups {
+                rows.push(result_line(path, colors::tool_name()));
+                for (line, text) in lines {
+                    rows.push(result_line(&format!("  {:>4}: {}", line, text.trim_end()), colors::text_primary()));
+                }
+            }
+        }
+        ResultView::Diffs(files) => {
+            for file in &files {
+                rows.push(result_line(&format!("{} ({})", file.path, file.status), colors::tool_name()));
+                for line in file.diff.lines().filter(|l| !l.starts_with("---") && !l.starts_with("+++")) {
+                    let color = match line.chars().next() {
+                        Some('+') => colors::status_completed(),
+                        Some('-') => colors::status_failed(),
+                        Some('@') => colors::text_muted(),
+                        _ => colors::text_primary(),
+                    };
+                    rows.push(result_line(line, color));
+                }
+            }
+        }
+    }
+
+    let hidden = rows.len().saturating_sub(RESULT_VIEW_MAX_ITEMS);
+    rows.truncate(RESULT_VIEW_MAX_ITEMS);
+    if hidden > 0 {
+        rows.push(result_line(&format!("… {} more", hidden), colors::text_muted()));
+    }
+
+    Some(
+        div()
+            .flex()
+            .flex_col()
+            .mx_3()
+            .mb_2()
+            .px_2()
+            .py_1()
+            .bg(colors::tool_param_bg())
+            .rounded_sm()
+            .children(rows),
+    )
+}
+
+/// Render one monospace line of a structured result
+fn result_line(text: &str, color: gpui::Rgba) -> gpui::Div {
+    div()
+        .text_xs()
+        .font_family("monospace")
+        .text_color(color)
+        .child(text.to_string())
+}
+
 /// Render error section
 fn render_error_section(error: &str) -> gpui::Div {
     div()
@@ -301,6 +462,33 @@ mod tests {
         let _ = status_border_color(ToolCallStatus::Cancelled);
     }
 
+    #[test]
+    fn test_result_view_shapes() {
+        use serde_json::json;
+
+        let files = json!({"files": ["a.rs", "b.rs"], "truncated": false});
+        assert_eq!(
+            ResultView::from_result(&files),
+            Some(ResultView::Files(vec!["a.rs".to_string(), "b.rs".to_string()]))
+        );
+
+        let matches = json!({"matches": [
+            {"path": "a.rs", "line": 1, "text": "x"},
+            {"path": "a.rs", "line": 4, "text": "y"},
+            {"path": "b.rs", "line": 2, "text": "z"},
+        ], "files": ["a.rs", "b.rs"]});
+        let Some(ResultView::Matches(groups)) = ResultView::from_result(&matches) else {
+            panic!("expected matches");
+        };
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].1, vec![(1, "x".to_string()), (4, "y".to_string())]);
+
+        let diffs = json!({"files": [{"path": "a.rs", "status": "modified", "diff": "@@ -1 +1 @@\n-a\n+b\n"}]});
+        assert!(matches!(ResultView::from_result(&diffs), Some(ResultView::Diffs(d)) if d[0].path == "a.rs"));
+
+        assert_eq!(ResultView::from_result(&json!({"stdout": "hi"})), None);
+    }
+
     #[test]
     fn test_format_duration() {
         assert_eq!(format_duration(500), "500ms");
@@ -6,7 +6,9 @@
 //! - Scroll position for virtualization
 //! - Expansion state for accordions
 
-use super::types::{StreamContent, StreamItem, StreamItemId, ToolCallStatus};
+use super::types::{
+    ApprovalResolution, StreamContent, StreamItem, StreamItemId, ToolCallStatus,
+};
 use std::collections::HashMap;
 
 /// State for the stream timeline
@@ -66,6 +68,26 @@ impl StreamState {
         }
     }
 
+    /// Insert an item directly before another item
+    ///
+    /// Falls back to appending if the anchor item does not exist.
+    pub fn insert_before(&mut self, anchor_id: &str, item: StreamItem) {
+        let Some(&index) = self.item_index.get(anchor_id) else {
+            self.push(item);
+            return;
+        };
+
+        self.items.insert(index, item);
+        for (i, item) in self.items.iter().enumerate().skip(index) {
+            self.item_index.insert(item.id.clone(), i);
+        }
+        self.height_dirty = true;
+
+        if self.auto_scroll {
+            self.scroll_to_bottom();
+        }
+    }
+
     /// Add multiple items
     pub fn extend(&mut self, items: impl IntoIterator<Item = StreamItem>) {
         for item in items {
@@ -155,7 +177,10 @@ impl StreamState {
                 if tc.call_id == call_id {
                     tc.result = Some(result);
                     tc.error = error;
-                    tc.status = if tc.error.is_some() {
+                    // A cancelled call reports an error but keeps its status
+                    tc.status = if tc.status == ToolCallStatus::Cancelled {
+                        ToolCallStatus::Cancelled
+                    } else if tc.error.is_some() {
                         ToolCallStatus::Failed
                     } else {
                         ToolCallStatus::Completed
@@ -168,6 +193,111 @@ impl StreamState {
         false
     }
 
+    /// Append live output to a tool call
+    pub fn append_tool_output(&mut self, call_id: &str, chunk: &str) -> bool {
+        let Some(index) = self.items.iter().position(|item| {
+            matches!(&item.content, StreamContent::ToolCall(tc) if tc.call_id == call_id)
+        }) else {
+            return false;
+        };
+        if let StreamContent::ToolCall(tc) = &mut self.items[index].content {
+            tc.append_output(chunk);
+        }
+        self.content_grew(index);
+        true
+    }
+
+    /// Append streamed text to a user or agent message
+    pub fn append_message_delta(&mut self, id: &str, delta: &str) -> bool {
+        let Some(&index) = self.item_index.get(id) else {
+            return false;
+        };
+        match &mut self.items[index].content {
+            StreamContent::AgentMessage(msg) => msg.content.push_str(delta),
+            StreamContent::UserMessage(msg) => msg.content.push_str(delta),
+            _ => return false,
+        }
+        self.content_grew(index);
+        true
+    }
+
+    /// Append streamed text to a reasoning block
+    pub fn append_reasoning_delta(&mut self, id: &str, delta: &str) -> bool {
+        let Some(&index) = self.item_index.get(id) else {
+            return false;
+        };
+        match &mut self.items[index].content {
+            StreamContent::Reasoning(reasoning) => reasoning.content.push_str(delta),
+            _ => return false,
+        }
+        self.content_grew(index);
+        true
+    }
+
+    /// Mark a reasoning block as complete, stamping its duration
+    ///
+    /// Duration is measured from the item's timestamp to `ended_at`.
+    pub fn finish_reasoning(&mut self, id: &str, ended_at: chrono::DateTime<chrono::Utc>) -> bool {
+        let Some(item) = self.get_mut(id) else {
+            return false;
+        };
+        let elapsed = (ended_at - item.timestamp).num_milliseconds().max(0) as u64;
+        match &mut item.content {
+            StreamContent::Reasoning(reasoning) => {
+                reasoning.streaming = false;
+                reasoning.duration_ms = Some(elapsed);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Set the summary shown in a collapsed reasoning block
+    pub fn set_reasoning_summary(&mut self, id: &str, summary: impl Into<String>) -> bool {
+        match self.get_mut(id).map(|item| &mut item.content) {
+            Some(StreamContent::Reasoning(reasoning)) => {
+                reasoning.summary = Some(summary.into());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Mark a streamed agent message as complete
+    pub fn finish_message(&mut self, id: &str) -> bool {
+        match self.get_mut(id).map(|item| &mut item.content) {
+            Some(StreamContent::AgentMessage(msg)) => {
+                msg.streaming = false;
+                true
+            }
+            Some(StreamContent::UserMessage(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Resolve an approval gate with the chosen action
+    ///
+    /// Returns false if the item is not an approval gate, the action is
+    /// unknown, or the gate was already resolved.
+    pub fn resolve_approval(&mut self, id: &str, action_id: &str) -> bool {
+        if let Some(StreamContent::Approval(approval)) = self.get_mut(id).map(|i| &mut i.content) {
+            if approval.resolution.is_some()
+                || !approval.actions.iter().any(|a| a.id == action_id)
+            {
+                return false;
+            }
+            approval.resolution = Some(ApprovalResolution {
+                action_id: action_id.to_string(),
+                timestamp: chrono::Utc::now(),
+                comment: None,
+            });
+            self.height_dirty = true;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Get all items
     pub fn items(&self) -> &[StreamItem] {
         &self.items
@@ -339,6 +469,40 @@ impl StreamState {
 
     // Private helper methods
 
+    /// Refresh the height of the item at `index` after streamed content grew
+    ///
+    /// Keeps the viewport pinned to the bottom while auto-scroll is enabled.
+    fn content_grew(&mut self, index: usize) {
+        if !self.height_dirty {
+            self.update_item_height(index);
+        }
+        if self.auto_scroll {
+            self.scroll_to_bottom();
+        } else {
+            self.recalculate_heights_if_dirty();
+        }
+    }
+
+    /// Re-estimate one item's height and shift the offsets that follow it
+    ///
+    /// Streaming usually targets the last item, so this is O(1) per delta
+    /// instead of re-estimating every item in the stream.
+    fn update_item_height(&mut self, index: usize) {
+        let Some(&end) = self.height_cache.get(index) else {
+            self.height_dirty = true;
+            return;
+        };
+        let start = if index == 0 { 0.0 } else { self.height_cache[index - 1] };
+        let delta = self.items[index].estimated_height() - (end - start);
+        if delta == 0.0 {
+            return;
+        }
+        for offset in &mut self.height_cache[index..] {
+            *offset += delta;
+        }
+        self.total_height += delta;
+    }
+
     fn recalculate_heights_if_dirty(&mut self) {
         if !self.height_dirty {
             return;
@@ -471,6 +635,134 @@ mod tests {
         assert!(state.get("reason").unwrap().expanded);
     }
 
+    #[test]
+    fn test_message_deltas() {
+        use crate::stream::types::AgentMessage;
+
+        let mut state = StreamState::new();
+        state.push(StreamItem::new(
+            "msg",
+            StreamContent::AgentMessage(AgentMessage::new("agent", "").streaming()),
+        ));
+        let initial_height = state.total_height();
+
+        assert!(state.append_message_delta("msg", "Hello"));
+        assert!(state.append_message_delta("msg", &", world".repeat(40)));
+        assert!(!state.append_message_delta("missing", "x"));
+        assert!(state.total_height() > initial_height);
+
+        assert!(state.finish_message("msg"));
+        match &state.get("msg").unwrap().content {
+            StreamContent::AgentMessage(msg) => {
+                assert!(msg.content.starts_with("Hello, world"));
+                assert!(!msg.streaming);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_delta_updates_only_changed_height() {
+        use crate::stream::types::AgentMessage;
+
+        let mut state = StreamState::new();
+        state.push(make_item("1"));
+        state.push(StreamItem::new(
+            "msg",
+            StreamContent::AgentMessage(AgentMessage::new("agent", "").streaming()),
+        ));
+        state.push(make_item("3"));
+        state.set_scroll_offset(0.0);
+
+        assert!(state.append_message_delta("msg", &"word ".repeat(50)));
+        let incremental = (state.height_cache.clone(), state.total_height);
+
+        state.height_dirty = true;
+        state.recalculate_heights_if_dirty();
+        assert_eq!(incremental, (state.height_cache.clone(), state.total_height));
+    }
+
+    #[test]
+    fn test_insert_before() {
+        let mut state = StreamState::new();
+        state.push(make_item("1"));
+        state.push(make_item("3"));
+        state.insert_before("3", make_item("2"));
+        state.insert_before("missing", make_item("4"));
+
+        let ids: Vec<_> = state.items().iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2", "3", "4"]);
+        assert_eq!(state.get("3").unwrap().id, "3");
+        assert_eq!(state.get("4").unwrap().id, "4");
+    }
+
+    #[test]
+    fn test_reasoning_stream() {
+        use crate::stream::types::ReasoningBlock;
+
+        let mut state = StreamState::new();
+        let item = StreamItem::new(
+            "r1",
+            StreamContent::Reasoning(ReasoningBlock::new("").streaming()),
+        );
+        let started_at = item.timestamp;
+        state.push(item);
+
+        assert!(state.append_reasoning_delta("r1", "Consider "));
+        assert!(state.append_reasoning_delta("r1", "the cache"));
+        assert!(!state.append_message_delta("r1", "wrong kind"));
+        assert!(state.finish_reasoning("r1", started_at + chrono::Duration::milliseconds(1500)));
+        assert!(state.set_reasoning_summary("r1", "Cache analysis"));
+
+        match &state.get("r1").unwrap().content {
+            StreamContent::Reasoning(r) => {
+                assert_eq!(r.content, "Consider the cache");
+                assert_eq!(r.duration_ms, Some(1500));
+                assert_eq!(r.summary.as_deref(), Some("Cache analysis"));
+                assert!(!r.streaming);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_approval() {
+        use crate::stream::types::{ApprovalAction, ApprovalActionVariant, ApprovalBlock};
+
+        let mut state = StreamState::new();
+        state.push(StreamItem::new(
+            "gate",
+            StreamContent::Approval(ApprovalBlock {
+                title: "Run migration?".to_string(),
+                description: None,
+                content: None,
+                content_type: None,
+                actions: vec![ApprovalAction {
+                    id: "approve".to_string(),
+                    label: "Approve".to_string(),
+                    variant: ApprovalActionVariant::Primary,
+                    payload: None,
+                }],
+                resolution: None,
+                blocking: true,
+            }),
+        ));
+        state.push(make_item("msg"));
+
+        assert!(!state.resolve_approval("gate", "unknown"));
+        assert!(!state.resolve_approval("msg", "approve"));
+        assert!(state.resolve_approval("gate", "approve"));
+        // Already resolved
+        assert!(!state.resolve_approval("gate", "approve"));
+
+        match &state.get("gate").unwrap().content {
+            StreamContent::Approval(a) => {
+                assert_eq!(a.resolution.as_ref().unwrap().action_id, "approve")
+            }
+            _ => unreachable!(),
+        }
+    }
+
     #[test]
     fn test_clear() {
         let mut state = StreamState::new();
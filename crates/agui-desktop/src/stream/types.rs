@@ -2,6 +2,7 @@
 //!
 //! Defines the data structures for all timeline items.
 
+use crate::protocol::UserAction;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -49,8 +50,11 @@ impl StreamItem {
                 48.0
             }
             StreamContent::ToolCall(tc) => {
-                // Header + status + collapsed params
-                100.0 + if tc.expanded { 80.0 } else { 0.0 }
+                // Header + status + collapsed params + live output tail
+                let output_lines = tc.output_tail(TOOL_OUTPUT_TAIL_LINES).len();
+                100.0
+                    + if tc.expanded { 80.0 } else { 0.0 }
+                    + if output_lines > 0 { 16.0 + output_lines as f32 * 16.0 } else { 0.0 }
             }
             StreamContent::Plan(plan) => {
                 // Header + items
@@ -181,6 +185,9 @@ pub struct ReasoningBlock {
     /// Duration in milliseconds (how long this thinking took)
     #[serde(default)]
     pub duration_ms: Option<u64>,
+    /// Whether reasoning is still streaming
+    #[serde(default)]
+    pub streaming: bool,
 }
 
 impl ReasoningBlock {
@@ -190,6 +197,7 @@ impl ReasoningBlock {
             summary: None,
             expanded: false,
             duration_ms: None,
+            streaming: false,
         }
     }
 
@@ -197,6 +205,11 @@ impl ReasoningBlock {
         self.summary = Some(summary.into());
         self
     }
+
+    pub fn streaming(mut self) -> Self {
+        self.streaming = true;
+        self
+    }
 }
 
 /// Tool call block
@@ -225,6 +238,38 @@ pub struct ToolCallBlock {
     /// Whether params/result are expanded
     #[serde(default)]
     pub expanded: bool,
+    /// Live output (stdout and stderr interleaved), trimmed to the most recent text
+    #[serde(default)]
+    pub output: String,
+}
+
+/// Number of output lines shown on a tool call card
+pub const TOOL_OUTPUT_TAIL_LINES: usize = 12;
+
+/// Maximum bytes of live output kept per tool call
+pub const MAX_TOOL_OUTPUT_BYTES: usize = 64 * 1024;
+
+impl ToolCallBlock {
+    /// Append live output, dropping the oldest text beyond `MAX_TOOL_OUTPUT_BYTES`
+    pub fn append_output(&mut self, chunk: &str) {
+        self.output.push_str(chunk);
+        if self.output.len() > MAX_TOOL_OUTPUT_BYTES {
+            let mut start = self.output.len() - MAX_TOOL_OUTPUT_BYTES;
+            while !self.output.is_char_boundary(start) {
+                start += 1;
+            }
+            self.output.drain(..start);
+        }
+    }
+
+    /// Get the last `count` lines of output
+    ///
+    /// Walks the buffer from the end so the cost tracks `count`, not the output size.
+    pub fn output_tail(&self, count: usize) -> Vec<&str> {
+        let mut tail: Vec<&str> = self.output.lines().rev().take(count).collect();
+        tail.reverse();
+        tail
+    }
 }
 
 /// Tool call status
@@ -405,6 +450,20 @@ pub struct ApprovalAction {
     pub payload: Option<serde_json::Value>,
 }
 
+impl ApprovalAction {
+    /// Build the USER_ACTION sent when this action is clicked on the given gate
+    pub fn to_user_action(&self, gate_id: &str) -> UserAction {
+        UserAction {
+            action_type: "approval".to_string(),
+            component_id: gate_id.to_string(),
+            payload: serde_json::json!({
+                "action_id": self.id,
+                "payload": self.payload,
+            }),
+        }
+    }
+}
+
 /// Approval action variant
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
@@ -453,6 +512,24 @@ impl StatusBlock {
         }
     }
 
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            status_type: StatusType::Warning,
+            progress: None,
+            ephemeral: false,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            status_type: StatusType::Error,
+            progress: None,
+            ephemeral: false,
+        }
+    }
+
     pub fn progress(message: impl Into<String>, progress: u8) -> Self {
         Self {
             message: message.into(),
@@ -479,6 +556,31 @@ pub enum StatusType {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_tool_call_output() {
+        let mut tc = ToolCallBlock {
+            call_id: "tc".to_string(),
+            tool_name: "run_command".to_string(),
+            parameters: serde_json::json!({}),
+            status: ToolCallStatus::Running,
+            result: None,
+            error: None,
+            duration_ms: None,
+            progress: None,
+            expanded: false,
+            output: String::new(),
+        };
+
+        tc.append_output("one\ntwo\n");
+        tc.append_output("three\n");
+        assert_eq!(tc.output_tail(2), vec!["two", "three"]);
+        assert_eq!(tc.output_tail(10).len(), 3);
+
+        tc.append_output(&"é".repeat(MAX_TOOL_OUTPUT_BYTES));
+        assert!(tc.output.len() <= MAX_TOOL_OUTPUT_BYTES);
+        assert!(tc.output.ends_with('é'));
+    }
+
     #[test]
     fn test_user_message() {
         let msg = UserMessage::new("Hello, world!").with_sender("Alice");
@@ -523,6 +625,22 @@ mod tests {
         assert!(ToolCallStatus::Failed.is_terminal());
     }
 
+    #[test]
+    fn test_approval_user_action() {
+        let action = ApprovalAction {
+            id: "approve".to_string(),
+            label: "Approve".to_string(),
+            variant: ApprovalActionVariant::Primary,
+            payload: Some(serde_json::json!({"scope": "once"})),
+        };
+
+        let user_action = action.to_user_action("gate-1");
+        assert_eq!(user_action.action_type, "approval");
+        assert_eq!(user_action.component_id, "gate-1");
+        assert_eq!(user_action.payload["action_id"], "approve");
+        assert_eq!(user_action.payload["payload"]["scope"], "once");
+    }
+
     #[test]
     fn test_estimated_height() {
         let item = StreamItem::new(
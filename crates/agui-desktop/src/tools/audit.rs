@@ -0,0 +1,306 @@
+//! Tool execution audit log
+//!
+//! Appends one JSON record per finished tool call to a JSONL file: the
+//! original request, who approved it, the validated parameters, timing,
+//! the final status and a SHA-256 hash of the result. Records are never
+//! rewritten; `AuditLog::query` reads them back through an `AuditFilter`.
+
+use crate::protocol::{ToolCallRequest, ToolCallResult, ToolCallState};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// Approver recorded for calls the policy allowed without asking
+pub const POLICY_APPROVER: &str = "policy";
+
+/// A finished tool call
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditRecord {
+    /// The request as received from the orchestrator
+    pub request: ToolCallRequest,
+    /// Who approved the call (`"policy"` for unattended calls), if anyone
+    pub approved_by: Option<String>,
+    /// Parameters after defaults and validation; absent if validation failed
+    pub parameters: Option<Value>,
+    /// When the call started running, or was refused
+    pub started_at: DateTime<Utc>,
+    /// When the call finished
+    pub finished_at: DateTime<Utc>,
+    /// Final status: COMPLETED, FAILED or CANCELLED
+    pub status: ToolCallState,
+    /// Error message for failed or cancelled calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Hex SHA-256 of the result's JSON text
+    pub result_sha256: String,
+}
+
+impl AuditRecord {
+    /// Record a call that finished now with the given result
+    pub fn new(
+        request: &ToolCallRequest,
+        approved_by: Option<String>,
+        parameters: Option<Value>,
+        started_at: DateTime<Utc>,
+        status: ToolCallState,
+        result: &ToolCallResult,
+    ) -> Self {
+        Self {
+            request: request.clone(),
+            approved_by,
+            parameters,
+            started_at,
+            finished_at: Utc::now(),
+            status,
+            error: result.error.clone(),
+            result_sha256: hash_result(&result.result),
+        }
+    }
+}
+
+/// Hex SHA-256 of a result's JSON text
+pub fn hash_result(result: &Value) -> String {
+    format!("{:x}", Sha256::digest(result.to_string().as_bytes()))
+}
+
+/// Selects audit records; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub tool_name: Option<String>,
+    pub agent_id: Option<String>,
+    pub status: Option<ToolCallState>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl AuditFilter {
+    /// Create a filter matching every record
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match calls to this tool
+    pub fn with_tool(mut self, tool_name: impl Into<String>) -> Self {
+        self.tool_name = Some(tool_name.into());
+        self
+    }
+
+    /// Only match calls from this agent
+    pub fn with_agent(mut self, agent_id: impl Into<String>) -> Self {
+        self.agent_id = Some(agent_id.into());
+        self
+    }
+
+    /// Only match calls that ended with this status
+    pub fn with_status(mut self, status: ToolCallState) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Only match calls started at or after this time
+    pub fn since(mut self, time: DateTime<Utc>) -> Self {
+        self.since = Some(time);
+        self
+    }
+
+    /// Only match calls started before this time
+    pub fn until(mut self, time: DateTime<Utc>) -> Self {
+        self.until = Some(time);
+        self
+    }
+
+    /// Check whether a record passes the filter
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        self.tool_name.as_ref().is_none_or(|tool| *tool == record.request.tool_name)
+            && self.agent_id.as_ref().is_none_or(|agent| *agent == record.request.agent_id)
+            && self.status.as_ref().is_none_or(|status| *status == record.status)
+            && self.since.is_none_or(|since| record.started_at >= since)
+            && self.until.is_none_or(|until| record.started_at < until)
+    }
+}
+
+/// Append-only JSONL log of tool calls
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    /// Serializes appends from concurrent calls
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    /// Create a log writing to a file, created on the first append
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Get the log file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a record as a single line
+    pub fn append(&self, record: &AuditRecord) -> Result<(), AuditError> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().unwrap();
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Read every readable record, oldest first
+    pub fn records(&self) -> Result<Vec<AuditRecord>, AuditError> {
+        Ok(self.query(&AuditFilter::new())?.records)
+    }
+
+    /// Read the records matching a filter, oldest first
+    ///
+    /// Lines that do not parse, such as a record cut short by a crash, are
+    /// skipped and reported in `AuditQuery::invalid_lines`.
+    pub fn query(&self, filter: &AuditFilter) -> Result<AuditQuery, AuditError> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(AuditQuery::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut result = AuditQuery::default();
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<AuditRecord>(line) {
+                Ok(record) if filter.matches(&record) => result.records.push(record),
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!("Skipping invalid audit record on line {}: {}", index + 1, e);
+                    result.invalid_lines.push(index + 1);
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Result of reading the audit log
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditQuery {
+    /// Matching records, oldest first
+    pub records: Vec<AuditRecord>,
+    /// 1-based numbers of lines that could not be parsed
+    pub invalid_lines: Vec<usize>,
+}
+
+/// Errors writing or reading the audit log
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde_json::json;
+
+    fn record(tool_name: &str, agent_id: &str, status: ToolCallState) -> AuditRecord {
+        let request = ToolCallRequest {
+            id: "tc_1".to_string(),
+            tool_name: tool_name.to_string(),
+            parameters: json!({"path": "a.txt"}),
+            agent_id: agent_id.to_string(),
+        };
+        let result = ToolCallResult {
+            id: "tc_1".to_string(),
+            result: json!({"ok": true}),
+            error: None,
+        };
+        AuditRecord::new(
+            &request,
+            Some(POLICY_APPROVER.to_string()),
+            Some(request.parameters.clone()),
+            Utc::now(),
+            status,
+            &result,
+        )
+    }
+
+    #[test]
+    fn test_append_and_read() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let log = AuditLog::new(temp_dir.path().join("logs").join("audit.jsonl"));
+        assert!(log.records().unwrap().is_empty());
+
+        let first = record("write_file", "agent_1", ToolCallState::Completed);
+        log.append(&first).unwrap();
+        log.append(&record("run_command", "agent_2", ToolCallState::Failed)).unwrap();
+
+        let text = std::fs::read_to_string(log.path()).unwrap();
+        assert_eq!(text.lines().count(), 2);
+
+        let records = log.records().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0], first);
+        assert_eq!(records[0].result_sha256, hash_result(&json!({"ok": true})));
+        assert_eq!(records[0].result_sha256.len(), 64);
+    }
+
+    #[test]
+    fn test_query_filter() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let log = AuditLog::new(temp_dir.path().join("audit.jsonl"));
+        log.append(&record("write_file", "agent_1", ToolCallState::Completed)).unwrap();
+        log.append(&record("run_command", "agent_1", ToolCallState::Failed)).unwrap();
+        log.append(&record("run_command", "agent_2", ToolCallState::Completed)).unwrap();
+
+        let query = |filter: AuditFilter| log.query(&filter).unwrap().records.len();
+        assert_eq!(query(AuditFilter::new()), 3);
+        assert_eq!(query(AuditFilter::new().with_tool("run_command")), 2);
+        assert_eq!(query(AuditFilter::new().with_tool("run_command").with_agent("agent_1")), 1);
+        assert_eq!(query(AuditFilter::new().with_status(ToolCallState::Failed)), 1);
+        assert_eq!(query(AuditFilter::new().since(Utc::now() - Duration::hours(1))), 3);
+        assert_eq!(query(AuditFilter::new().until(Utc::now() - Duration::hours(1))), 0);
+    }
+
+    #[test]
+    fn test_invalid_record() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let log = AuditLog::new(temp_dir.path().join("audit.jsonl"));
+        log.append(&record("write_file", "agent_1", ToolCallState::Completed)).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(log.path())
+            .unwrap()
+            .write_all(b"{not json\n")
+            .unwrap();
+        log.append(&record("run_command", "agent_1", ToolCallState::Completed)).unwrap();
+        // A crash mid-append leaves a truncated last line
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(log.path())
+            .unwrap()
+            .write_all(b"{\"request\":")
+            .unwrap();
+
+        let result = log.query(&AuditFilter::new()).unwrap();
+        assert_eq!(result.records.len(), 2);
+        assert_eq!(result.invalid_lines, vec![2, 4]);
+    }
+}
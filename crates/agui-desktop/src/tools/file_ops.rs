@@ -1,25 +1,52 @@
 //! File operation tools
 //!
 //! Provides tools for reading, writing, listing, and checking files.
-
-use super::{Tool, ToolError, ToolResult};
+//! `read_file` reads bounded ranges and returns binary content encoded.
+//! Each tool resolves paths through a `Sandbox`, which may confine it to
+//! the workspace roots.
+
+use super::sandbox::Sandbox;
+use super::{run_blocking, Tool, ToolContext, ToolError, ToolResult};
+use async_trait::async_trait;
+use crate::protocol::ArtifactOpen;
+use base64::prelude::{Engine, BASE64_STANDARD};
 use serde_json::json;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::fs;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
 
+/// Default cap on content returned by `read_file` (1MB)
+const DEFAULT_MAX_READ_BYTES: u64 = 1024 * 1024;
+/// Bytes inspected to decide whether a file is binary
+const BINARY_SNIFF_BYTES: usize = 8192;
+/// Cap on bytes returned as a hex dump preview
+const HEX_PREVIEW_BYTES: usize = 4096;
+/// Cap on bytes shown when a binary result opens as an artifact
+const ARTIFACT_HEX_BYTES: usize = 64 * 1024;
+
 /// Read file contents
-#[derive(Debug, Clone, Copy)]
-pub struct ReadFile;
+#[derive(Debug, Clone, Default)]
+pub struct ReadFile {
+    sandbox: Sandbox,
+}
 
+impl ReadFile {
+    /// Create the tool confined to a sandbox
+    pub fn new(sandbox: Sandbox) -> Self {
+        Self { sandbox }
+    }
+}
+
+#[async_trait]
 impl Tool for ReadFile {
     fn name(&self) -> &str {
         "read_file"
     }
 
     fn description(&self) -> &str {
-        "Read the contents of a file"
+        "Read the contents of a file, optionally a range of lines or bytes; binary files are returned as base64 or a hex preview"
     }
 
     fn parameters_schema(&self) -> &super::schema::ParameterSchema {
@@ -31,31 +58,327 @@ impl Tool for ReadFile {
                 super::schema::ParameterSchema::string()
                     .with_description("Path to the file to read"),
             );
+            props.insert(
+                "offset".to_string(),
+                super::schema::ParameterSchema::integer()
+                    .with_description("First line (0-based) or byte to read, depending on 'unit'")
+                    .with_minimum(0.0)
+                    .with_default(json!(0)),
+            );
+            props.insert(
+                "limit".to_string(),
+                super::schema::ParameterSchema::integer()
+                    .with_description("Maximum number of lines or bytes to read")
+                    .with_minimum(1.0),
+            );
+            props.insert(
+                "unit".to_string(),
+                super::schema::ParameterSchema::string()
+                    .with_description("Whether offset and limit count lines or bytes; binary files always use bytes")
+                    .with_enum(vec![json!("lines"), json!("bytes")])
+                    .with_default(json!("lines")),
+            );
+            props.insert(
+                "max_bytes".to_string(),
+                super::schema::ParameterSchema::integer()
+                    .with_description("Maximum bytes of content to return; longer reads are truncated")
+                    .with_minimum(1.0)
+                    .with_default(json!(DEFAULT_MAX_READ_BYTES)),
+            );
+            props.insert(
+                "binary_encoding".to_string(),
+                super::schema::ParameterSchema::string()
+                    .with_description("How to return binary content: base64, or a hex dump preview")
+                    .with_enum(vec![json!("base64"), json!("hex")])
+                    .with_default(json!("base64")),
+            );
             super::schema::ParameterSchema::object(props)
                 .with_required(vec!["path".to_string()])
         })
     }
 
-    fn execute(&self, params: &Value) -> ToolResult {
+    async fn execute(&self, params: &Value, _context: &ToolContext) -> ToolResult {
+        run_blocking(self, params, Self::run).await
+    }
+}
+
+impl ReadFile {
+    fn run(&self, params: &Value) -> ToolResult {
         let path = params
             .get("path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| ToolError::ExecutionFailed("Missing 'path' parameter".to_string()))?;
+        let offset = params.get("offset").and_then(|v| v.as_u64()).unwrap_or(0);
+        let limit = params.get("limit").and_then(|v| v.as_u64());
+        let unit = params.get("unit").and_then(|v| v.as_str()).unwrap_or("lines");
+        let max_bytes = params
+            .get("max_bytes")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_MAX_READ_BYTES);
+        let binary_encoding = params
+            .get("binary_encoding")
+            .and_then(|v| v.as_str())
+            .unwrap_or("base64");
+
+        let resolved = self.sandbox.resolve(path)?;
+        let read_error = |e: std::io::Error| ToolError::ExecutionFailed(format!("Failed to read file: {}", e));
+        let mut file = File::open(&resolved).map_err(read_error)?;
+        let size = file.metadata().map_err(read_error)?.len();
+        let guessed_mime = mime_guess::from_path(&resolved).first_raw();
+
+        if is_binary(&mut file).map_err(read_error)? {
+            let max_bytes = if binary_encoding == "hex" {
+                max_bytes.min(HEX_PREVIEW_BYTES as u64)
+            } else {
+                max_bytes
+            };
+            let range = read_bytes(&mut file, offset, limit, max_bytes).map_err(read_error)?;
+            let content = if binary_encoding == "hex" {
+                hex_dump(&range.bytes, offset)
+            } else {
+                BASE64_STANDARD.encode(&range.bytes)
+            };
+            let bytes_read = range.bytes.len() as u64;
+
+            return Ok(json!({
+                "path": path,
+                "content": content,
+                "encoding": binary_encoding,
+                "mime_type": guessed_mime.unwrap_or("application/octet-stream"),
+                "binary": true,
+                "size": size,
+                "unit": "bytes",
+                "offset": offset,
+                "bytes_read": bytes_read,
+                "truncated": range.truncated,
+                "next_offset": (offset + bytes_read < size).then_some(offset + bytes_read),
+            }));
+        }
 
-        let content = fs::read_to_string(path)
-            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to read file: {}", e)))?;
-
-        Ok(json!({
+        let mut result = json!({
             "path": path,
-            "content": content,
-        }))
+            "encoding": "utf-8",
+            "mime_type": guessed_mime.unwrap_or("text/plain"),
+            "binary": false,
+            "size": size,
+            "unit": unit,
+            "offset": offset,
+        });
+        if unit == "bytes" {
+            let mut range = read_bytes(&mut file, offset, limit, max_bytes).map_err(read_error)?;
+            // Do not split a character at the end of the range
+            if let Err(e) = std::str::from_utf8(&range.bytes) {
+                if e.error_len().is_none() {
+                    range.bytes.truncate(e.valid_up_to());
+                }
+            }
+            let bytes_read = range.bytes.len() as u64;
+            result["content"] = json!(String::from_utf8_lossy(&range.bytes));
+            result["bytes_read"] = json!(bytes_read);
+            result["truncated"] = json!(range.truncated);
+            result["next_offset"] = json!((offset + bytes_read < size).then_some(offset + bytes_read));
+        } else {
+            let lines = read_lines(file, offset, limit, max_bytes).map_err(read_error)?;
+            let lines_read = lines.lines as u64;
+            result["content"] = json!(lines.content);
+            result["lines_read"] = json!(lines_read);
+            result["bytes_read"] = json!(lines.content.len());
+            result["truncated"] = json!(lines.truncated);
+            result["next_offset"] = json!(lines.more.then_some(offset + lines_read));
+        }
+
+        Ok(result)
+    }
+}
+
+/// Check whether a file looks binary, leaving the cursor at the start
+///
+/// A file is binary when its first `BINARY_SNIFF_BYTES` contain a NUL byte
+/// or are not valid UTF-8.
+fn is_binary(file: &mut File) -> std::io::Result<bool> {
+    let mut sniff = Vec::with_capacity(BINARY_SNIFF_BYTES);
+    file.by_ref().take(BINARY_SNIFF_BYTES as u64).read_to_end(&mut sniff)?;
+    file.seek(SeekFrom::Start(0))?;
+
+    if sniff.contains(&0) {
+        return Ok(true);
+    }
+    // A character cut off by the sniff window is not an error
+    Ok(std::str::from_utf8(&sniff).is_err_and(|e| e.error_len().is_some()))
+}
+
+/// Bytes read from a file range
+struct ByteRange {
+    bytes: Vec<u8>,
+    /// Whether `max_bytes` cut the requested range short
+    truncated: bool,
+}
+
+/// Read up to `limit` bytes from `offset`, capped at `max_bytes`
+fn read_bytes(file: &mut File, offset: u64, limit: Option<u64>, max_bytes: u64) -> std::io::Result<ByteRange> {
+    let size = file.metadata()?.len();
+    let available = size.saturating_sub(offset);
+    let wanted = limit.map_or(available, |limit| limit.min(available));
+    let take = wanted.min(max_bytes);
+
+    let mut bytes = Vec::with_capacity(take as usize);
+    file.seek(SeekFrom::Start(offset))?;
+    file.take(take).read_to_end(&mut bytes)?;
+    Ok(ByteRange {
+        bytes,
+        truncated: wanted > max_bytes,
+    })
+}
+
+/// Lines read from a text file
+struct LineRange {
+    content: String,
+    lines: usize,
+    /// Whether `max_bytes` cut the requested lines short
+    truncated: bool,
+    /// Whether the file has lines after the returned ones
+    more: bool,
+}
+
+/// Read up to `limit` lines after skipping `offset`, capped at `max_bytes`
+///
+/// Only whole lines are returned unless the first line alone exceeds
+/// `max_bytes`, in which case it is cut at a character boundary. Memory use
+/// stays within `max_bytes` even for files without newlines.
+fn read_lines(file: impl Read, offset: u64, limit: Option<u64>, max_bytes: u64) -> std::io::Result<LineRange> {
+    let mut reader = BufReader::new(file);
+    skip_lines(&mut reader, offset)?;
+
+    let mut line = Vec::new();
+    let mut content = Vec::new();
+    let mut lines = 0;
+    let mut truncated = false;
+    while limit.is_none_or(|limit| (lines as u64) < limit) {
+        line.clear();
+        // One byte past the budget is enough to tell the line does not fit
+        let budget = max_bytes - content.len() as u64 + 1;
+        if reader.by_ref().take(budget).read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        if (content.len() + line.len()) as u64 > max_bytes {
+            if lines == 0 {
+                let cut = String::from_utf8_lossy(&line[..max_bytes as usize]).into_owned();
+                let cut = cut.trim_end_matches(char::REPLACEMENT_CHARACTER);
+                content.extend_from_slice(cut.as_bytes());
+                lines = 1;
+            }
+            truncated = true;
+            break;
+        }
+        content.extend_from_slice(&line);
+        lines += 1;
     }
+    let more = truncated || !reader.fill_buf()?.is_empty();
+
+    Ok(LineRange {
+        content: String::from_utf8_lossy(&content).into_owned(),
+        lines,
+        truncated,
+        more,
+    })
+}
+
+/// Skip `count` lines, consuming the reader's buffer a chunk at a time
+fn skip_lines(reader: &mut impl BufRead, count: u64) -> std::io::Result<()> {
+    let mut remaining = count;
+    while remaining > 0 {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        let consumed = match buf.iter().position(|&b| b == b'\n') {
+            Some(end) => {
+                remaining -= 1;
+                end + 1
+            }
+            None => buf.len(),
+        };
+        reader.consume(consumed);
+    }
+    Ok(())
+}
+
+/// Format bytes as a hex dump with offsets and an ASCII column
+pub fn hex_dump(bytes: &[u8], start_offset: u64) -> String {
+    let mut out = String::new();
+    for (index, chunk) in bytes.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+            .collect();
+        out.push_str(&format!(
+            "{:08x}  {:<47}  |{}|\n",
+            start_offset + (index * 16) as u64,
+            hex.join(" "),
+            ascii
+        ));
+    }
+    out
+}
+
+/// Build an artifact for a `read_file` result, to open it in the Stage
+///
+/// Text opens as code with the file extension as the language hint.
+/// Binary content opens as `ContentType::Binary`, shown as a hex dump.
+pub fn read_result_artifact(id: &str, result: &Value) -> Option<ArtifactOpen> {
+    let path = result.get("path")?.as_str()?;
+    let content = result.get("content")?.as_str()?;
+    let title = Path::new(path)
+        .file_name()
+        .map_or_else(|| path.to_string(), |name| name.to_string_lossy().into_owned());
+
+    if result.get("binary").and_then(|v| v.as_bool()).unwrap_or(false) {
+        let offset = result.get("offset").and_then(|v| v.as_u64()).unwrap_or(0);
+        let dump = match result.get("encoding").and_then(|v| v.as_str()) {
+            Some("hex") => content.to_string(),
+            _ => {
+                let bytes = BASE64_STANDARD.decode(content).ok()?;
+                hex_dump(&bytes[..bytes.len().min(ARTIFACT_HEX_BYTES)], offset)
+            }
+        };
+        return Some(ArtifactOpen {
+            id: id.to_string(),
+            title,
+            content: dump,
+            content_type: "binary".to_string(),
+            read_only: true,
+            language: None,
+        });
+    }
+
+    let language = Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    Some(ArtifactOpen {
+        id: id.to_string(),
+        title,
+        content: content.to_string(),
+        content_type: if language.is_some() { "code" } else { "text" }.to_string(),
+        read_only: true,
+        language,
+    })
 }
 
 /// Write content to a file
-#[derive(Debug, Clone, Copy)]
-pub struct WriteFile;
+#[derive(Debug, Clone, Default)]
+pub struct WriteFile {
+    sandbox: Sandbox,
+}
+
+impl WriteFile {
+    /// Create the tool confined to a sandbox
+    pub fn new(sandbox: Sandbox) -> Self {
+        Self { sandbox }
+    }
+}
 
+#[async_trait]
 impl Tool for WriteFile {
     fn name(&self) -> &str {
         "write_file"
@@ -84,7 +407,13 @@ impl Tool for WriteFile {
         })
     }
 
-    fn execute(&self, params: &Value) -> ToolResult {
+    async fn execute(&self, params: &Value, _context: &ToolContext) -> ToolResult {
+        run_blocking(self, params, Self::run).await
+    }
+}
+
+impl WriteFile {
+    fn run(&self, params: &Value) -> ToolResult {
         let path = params
             .get("path")
             .and_then(|v| v.as_str())
@@ -95,13 +424,15 @@ impl Tool for WriteFile {
             .and_then(|v| v.as_str())
             .ok_or_else(|| ToolError::ExecutionFailed("Missing 'content' parameter".to_string()))?;
 
+        let resolved = self.sandbox.resolve(path)?;
+
         // Create parent directories if they don't exist
-        if let Some(parent) = Path::new(path).parent() {
+        if let Some(parent) = resolved.parent() {
             fs::create_dir_all(parent)
                 .map_err(|e| ToolError::ExecutionFailed(format!("Failed to create directories: {}", e)))?;
         }
 
-        fs::write(path, content)
+        fs::write(&resolved, content)
             .map_err(|e| ToolError::ExecutionFailed(format!("Failed to write file: {}", e)))?;
 
         Ok(json!({
@@ -113,9 +444,19 @@ impl Tool for WriteFile {
 }
 
 /// List directory contents
-#[derive(Debug, Clone, Copy)]
-pub struct ListDirectory;
+#[derive(Debug, Clone, Default)]
+pub struct ListDirectory {
+    sandbox: Sandbox,
+}
 
+impl ListDirectory {
+    /// Create the tool confined to a sandbox
+    pub fn new(sandbox: Sandbox) -> Self {
+        Self { sandbox }
+    }
+}
+
+#[async_trait]
 impl Tool for ListDirectory {
     fn name(&self) -> &str {
         "list_directory"
@@ -145,7 +486,13 @@ impl Tool for ListDirectory {
         })
     }
 
-    fn execute(&self, params: &Value) -> ToolResult {
+    async fn execute(&self, params: &Value, _context: &ToolContext) -> ToolResult {
+        run_blocking(self, params, Self::run).await
+    }
+}
+
+impl ListDirectory {
+    fn run(&self, params: &Value) -> ToolResult {
         let path = params
             .get("path")
             .and_then(|v| v.as_str())
@@ -156,10 +503,11 @@ impl Tool for ListDirectory {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
+        let resolved = self.sandbox.resolve(path)?;
         let entries = if recursive {
-            list_recursive(path)?
+            list_recursive(&resolved, &self.sandbox)?
         } else {
-            list_flat(path)?
+            list_flat(&resolved, &self.sandbox)?
         };
 
         Ok(json!({
@@ -170,9 +518,19 @@ impl Tool for ListDirectory {
 }
 
 /// Check if a file or directory exists
-#[derive(Debug, Clone, Copy)]
-pub struct FileExists;
+#[derive(Debug, Clone, Default)]
+pub struct FileExists {
+    sandbox: Sandbox,
+}
+
+impl FileExists {
+    /// Create the tool confined to a sandbox
+    pub fn new(sandbox: Sandbox) -> Self {
+        Self { sandbox }
+    }
+}
 
+#[async_trait]
 impl Tool for FileExists {
     fn name(&self) -> &str {
         "file_exists"
@@ -196,14 +554,20 @@ impl Tool for FileExists {
         })
     }
 
-    fn execute(&self, params: &Value) -> ToolResult {
+    async fn execute(&self, params: &Value, _context: &ToolContext) -> ToolResult {
+        run_blocking(self, params, Self::run).await
+    }
+}
+
+impl FileExists {
+    fn run(&self, params: &Value) -> ToolResult {
         let path = params
             .get("path")
             .and_then(|v| v.as_str())
             .ok_or_else(|| ToolError::ExecutionFailed("Missing 'path' parameter".to_string()))?;
 
-        let path_obj = Path::new(path);
-        let exists = path_obj.exists();
+        let resolved = self.sandbox.resolve(path)?;
+        let exists = resolved.exists();
 
         let mut result = json!({
             "path": path,
@@ -211,7 +575,7 @@ impl Tool for FileExists {
         });
 
         if exists {
-            let metadata = fs::metadata(path)
+            let metadata = fs::metadata(&resolved)
                 .map_err(|e| ToolError::ExecutionFailed(format!("Failed to get metadata: {}", e)))?;
 
             result["is_file"] = json!(metadata.is_file());
@@ -225,10 +589,13 @@ impl Tool for FileExists {
 }
 
 /// List directory entries (non-recursive)
-fn list_flat(path: &str) -> Result<Vec<Value>, ToolError> {
+///
+/// Entries resolving outside the sandbox (e.g. escaping symlinks) are skipped.
+fn list_flat(path: &Path, sandbox: &Sandbox) -> Result<Vec<Value>, ToolError> {
     let entries = fs::read_dir(path)
         .map_err(|e| ToolError::ExecutionFailed(format!("Failed to read directory: {}", e)))?
         .filter_map(|entry| entry.ok())
+        .filter(|entry| sandbox.allows_existing(&entry.path()))
         .map(|entry| {
             let path = entry.path();
             let metadata = entry.metadata().ok();
@@ -253,10 +620,12 @@ fn list_flat(path: &str) -> Result<Vec<Value>, ToolError> {
 }
 
 /// List directory entries recursively
-fn list_recursive(path: &str) -> Result<Vec<Value>, ToolError> {
+///
+/// Entries resolving outside the sandbox are neither listed nor descended into.
+fn list_recursive(path: &Path, sandbox: &Sandbox) -> Result<Vec<Value>, ToolError> {
     let mut entries = Vec::new();
 
-    fn visit(path: &Path, entries: &mut Vec<Value>) -> std::io::Result<()> {
+    fn visit(path: &Path, sandbox: &Sandbox, entries: &mut Vec<Value>) -> std::io::Result<()> {
         let entry = path.metadata()?;
         let mut obj = json!({
             "name": path.file_name().unwrap_or_default().to_string_lossy(),
@@ -272,15 +641,17 @@ fn list_recursive(path: &str) -> Result<Vec<Value>, ToolError> {
 
         if path.is_dir() {
             for sub_entry in fs::read_dir(path)? {
-                let sub_entry = sub_entry?;
-                visit(&sub_entry.path(), entries)?;
+                let sub_path = sub_entry?.path();
+                if sandbox.allows_existing(&sub_path) {
+                    visit(&sub_path, sandbox, entries)?;
+                }
             }
         }
 
         Ok(())
     }
 
-    visit(Path::new(path), &mut entries)
+    visit(path, sandbox, &mut entries)
         .map_err(|e| ToolError::ExecutionFailed(format!("Failed to traverse directory: {}", e)))?;
 
     Ok(entries)
@@ -300,8 +671,8 @@ mod tests {
         let mut file = File::create(&file_path).unwrap();
         writeln!(file, "Hello, World!").unwrap();
 
-        let tool = ReadFile;
-        let result = tool.execute(&json!({
+        let tool = ReadFile::default();
+        let result = tool.run(&json!({
             "path": file_path.to_string_lossy(),
         }));
 
@@ -310,13 +681,99 @@ mod tests {
         assert_eq!(result["content"], "Hello, World!\n");
     }
 
+    #[test]
+    fn test_read_file_ranges() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("lines.txt");
+        fs::write(&file_path, "one\ntwo\nthree\nfour\n").unwrap();
+        let path = file_path.to_string_lossy();
+        let tool = ReadFile::default();
+
+        let result = tool.run(&json!({"path": path, "offset": 1, "limit": 2})).unwrap();
+        assert_eq!(result["content"], "two\nthree\n");
+        assert_eq!(result["lines_read"], 2);
+        assert_eq!(result["next_offset"], 3);
+        assert_eq!(result["encoding"], "utf-8");
+        assert_eq!(result["mime_type"], "text/plain");
+
+        let result = tool.run(&json!({"path": path, "offset": 3})).unwrap();
+        assert_eq!(result["content"], "four\n");
+        assert_eq!(result["next_offset"], Value::Null);
+
+        let result = tool
+            .run(&json!({"path": path, "unit": "bytes", "offset": 4, "limit": 3}))
+            .unwrap();
+        assert_eq!(result["content"], "two");
+        assert_eq!(result["next_offset"], 7);
+
+        // Whole lines only, stopping before max_bytes is exceeded
+        let result = tool.run(&json!({"path": path, "max_bytes": 10})).unwrap();
+        assert_eq!(result["content"], "one\ntwo\n");
+        assert_eq!(result["truncated"], true);
+        assert_eq!(result["next_offset"], 2);
+    }
+
+    #[test]
+    fn test_read_lines_bounded_without_newlines() {
+        // An endless line only terminates if every read is bounded
+        let range = read_lines(std::io::repeat(b'x'), 0, None, 16).unwrap();
+        assert_eq!(range.content, "x".repeat(16));
+        assert!(range.truncated);
+
+        let long_lines = std::io::repeat(b'x')
+            .take(1 << 20)
+            .chain(&b"\nshort\n"[..]);
+        let range = read_lines(long_lines, 1, None, 16).unwrap();
+        assert_eq!(range.content, "short\n");
+        assert!(!range.more);
+    }
+
+    #[test]
+    fn test_read_file_binary() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("image.png");
+        let bytes = [0x89, b'P', b'N', b'G', 0x00, 0xff, 0x10];
+        fs::write(&file_path, bytes).unwrap();
+        let path = file_path.to_string_lossy();
+        let tool = ReadFile::default();
+
+        let result = tool.run(&json!({"path": path})).unwrap();
+        assert_eq!(result["binary"], true);
+        assert_eq!(result["encoding"], "base64");
+        assert_eq!(result["mime_type"], "image/png");
+        assert_eq!(
+            BASE64_STANDARD.decode(result["content"].as_str().unwrap()).unwrap(),
+            bytes
+        );
+
+        let result = tool
+            .run(&json!({"path": path, "binary_encoding": "hex", "offset": 1, "limit": 3}))
+            .unwrap();
+        assert_eq!(result["content"], format!("00000001  {:<47}  |PNG|\n", "50 4e 47"));
+        assert_eq!(result["next_offset"], 4);
+
+        let artifact = read_result_artifact("a1", &tool.run(&json!({"path": path})).unwrap()).unwrap();
+        assert_eq!(artifact.content_type, "binary");
+        assert_eq!(artifact.title, "image.png");
+        assert!(artifact.content.starts_with("00000000  89 50 4e 47 00 ff 10"));
+    }
+
+    #[test]
+    fn test_read_result_artifact_text() {
+        let result = json!({"path": "src/main.rs", "content": "fn main() {}", "binary": false});
+        let artifact = read_result_artifact("a1", &result).unwrap();
+        assert_eq!(artifact.content_type, "code");
+        assert_eq!(artifact.language.as_deref(), Some("rs"));
+        assert_eq!(artifact.content, "fn main() {}");
+    }
+
     #[test]
     fn test_write_file() {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("test.txt");
 
-        let tool = WriteFile;
-        let result = tool.execute(&json!({
+        let tool = WriteFile::default();
+        let result = tool.run(&json!({
             "path": file_path.to_string_lossy(),
             "content": "Test content",
         }));
@@ -332,8 +789,8 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("nested/dir/test.txt");
 
-        let tool = WriteFile;
-        let result = tool.execute(&json!({
+        let tool = WriteFile::default();
+        let result = tool.run(&json!({
             "path": file_path.to_string_lossy(),
             "content": "Test",
         }));
@@ -349,8 +806,8 @@ mod tests {
         File::create(temp_dir.path().join("file2.txt")).unwrap();
         fs::create_dir(temp_dir.path().join("subdir")).unwrap();
 
-        let tool = ListDirectory;
-        let result = tool.execute(&json!({
+        let tool = ListDirectory::default();
+        let result = tool.run(&json!({
             "path": temp_dir.path().to_string_lossy(),
         }));
 
@@ -366,20 +823,64 @@ mod tests {
         let file_path = temp_dir.path().join("test.txt");
         File::create(&file_path).unwrap();
 
-        let tool = FileExists;
+        let tool = FileExists::default();
 
         // Existing file
-        let result = tool.execute(&json!({
+        let result = tool.run(&json!({
             "path": file_path.to_string_lossy(),
         }));
         assert!(result.is_ok());
         assert_eq!(result.unwrap()["exists"], true);
 
         // Non-existing file
-        let result = tool.execute(&json!({
+        let result = tool.run(&json!({
             "path": temp_dir.path().join("nonexistent.txt").to_string_lossy(),
         }));
         assert!(result.is_ok());
         assert_eq!(result.unwrap()["exists"], false);
     }
+
+    #[test]
+    fn test_sandboxed_tools() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("project");
+        fs::create_dir(&root).unwrap();
+        fs::write(temp_dir.path().join("secret.txt"), "secret").unwrap();
+        let sandbox = Sandbox::new([&root]).unwrap();
+
+        let read = ReadFile::new(sandbox.clone());
+        assert!(matches!(
+            read.run(&json!({"path": "../secret.txt"})),
+            Err(ToolError::PathViolation(_))
+        ));
+
+        // Writes may not create directories outside the roots
+        let write = WriteFile::new(sandbox.clone());
+        let outside = temp_dir.path().join("other/new.txt");
+        assert!(matches!(
+            write.run(&json!({"path": outside.to_string_lossy(), "content": "x"})),
+            Err(ToolError::PathViolation(_))
+        ));
+        assert!(!outside.parent().unwrap().exists());
+
+        // Relative paths resolve against the first root
+        assert!(write.run(&json!({"path": "notes/a.txt", "content": "x"})).is_ok());
+        assert!(root.join("notes/a.txt").exists());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(temp_dir.path(), root.join("escape")).unwrap();
+            let list = ListDirectory::new(sandbox);
+            let result = list.run(&json!({"path": ".", "recursive": true})).unwrap();
+            let names: Vec<_> = result["entries"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|e| e["name"].as_str().unwrap().to_string())
+                .collect();
+            assert!(names.contains(&"a.txt".to_string()));
+            assert!(!names.contains(&"escape".to_string()));
+            assert!(!names.contains(&"secret.txt".to_string()));
+        }
+    }
 }
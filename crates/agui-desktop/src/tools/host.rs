@@ -0,0 +1,705 @@
+//! Local tool execution host
+//!
+//! Runs TOOL_CALL_REQUESTs from the orchestrator against a `ToolRegistry`
+//! off the UI thread, reporting progress as TOOL_CALL_STATUS and
+//! TOOL_CALL_RESULT events. Every call is checked against a `ToolPolicy`
+//! first; calls needing approval wait on an approval gate in the stream.
+//! In-flight calls can be cancelled, which kills any spawned processes.
+//! Finished, refused and cancelled calls are recorded in an `AuditLog`
+//! when one is set.
+
+use super::audit::{AuditLog, AuditRecord, POLICY_APPROVER};
+use super::policy::{PolicyLevel, PolicyRule, ToolPolicy};
+use super::{ToolContext, ToolError, ToolRegistry};
+use crate::protocol::{
+    Event, ToolCallOutput, ToolCallRequest, ToolCallResult, ToolCallState, ToolCallStatus,
+};
+use crate::stream::{
+    ApprovalAction, ApprovalActionVariant, ApprovalBlock, StreamContent, StreamItem,
+};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio_util::sync::CancellationToken;
+
+/// Prefix for approval gate stream item IDs created by the host
+const APPROVAL_ID_PREFIX: &str = "tool-approval-";
+
+/// How a submitted tool call was handled
+#[derive(Debug, Clone)]
+pub enum Submission {
+    /// The call was allowed and is running
+    Started,
+    /// The call was refused by policy
+    Denied,
+    /// The call waits for the user; insert this approval gate into the stream
+    NeedsApproval(Box<StreamItem>),
+}
+
+/// Executes tool calls and collects the resulting protocol events
+///
+/// Emitted events should be sent to the orchestrator and applied to the
+/// local stream so the tool call card mirrors execution.
+pub struct ToolHost {
+    registry: Arc<ToolRegistry>,
+    policy: ToolPolicy,
+    /// Calls waiting for approval, keyed by approval item ID
+    pending: HashMap<String, ToolCallRequest>,
+    /// Cancellation tokens of running calls, keyed by call ID
+    running: Arc<Mutex<HashMap<String, CancellationToken>>>,
+    runtime: Handle,
+    events_tx: UnboundedSender<Event>,
+    events: UnboundedReceiver<Event>,
+    /// Log every finished call is appended to
+    audit: Option<Arc<AuditLog>>,
+    /// User recorded as approving calls allowed from an approval gate
+    user: String,
+}
+
+impl ToolHost {
+    /// Create a host executing tools on the given runtime
+    ///
+    /// Uses `ToolPolicy::standard()` until a policy is set.
+    pub fn new(registry: ToolRegistry, runtime: &Handle) -> Self {
+        let (events_tx, events) = mpsc::unbounded_channel();
+        Self {
+            registry: Arc::new(registry),
+            policy: ToolPolicy::standard(),
+            pending: HashMap::new(),
+            running: Arc::new(Mutex::new(HashMap::new())),
+            runtime: runtime.clone(),
+            events_tx,
+            events,
+            audit: None,
+            user: local_user(),
+        }
+    }
+
+    /// Set the execution policy
+    pub fn with_policy(mut self, policy: ToolPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Record every finished call in an audit log
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(Arc::new(audit));
+        self
+    }
+
+    /// Set the user recorded as approving gated calls
+    ///
+    /// Defaults to the local account name.
+    pub fn with_user(mut self, user: impl Into<String>) -> Self {
+        self.user = user.into();
+        self
+    }
+
+    /// Get the tool registry
+    pub fn registry(&self) -> &ToolRegistry {
+        &self.registry
+    }
+
+    /// Get the execution policy
+    pub fn policy(&self) -> &ToolPolicy {
+        &self.policy
+    }
+
+    /// Check a tool call against the policy and run it if allowed
+    pub fn submit(&mut self, request: &ToolCallRequest) -> Submission {
+        match self.policy.evaluate(&request.tool_name, &request.parameters) {
+            PolicyLevel::Allow => {
+                self.execute(request, POLICY_APPROVER.to_string());
+                Submission::Started
+            }
+            PolicyLevel::Deny => {
+                self.deny(request);
+                Submission::Denied
+            }
+            PolicyLevel::Ask => {
+                let item_id = Self::approval_item_id(&request.id);
+                let item = StreamItem::new(
+                    item_id.clone(),
+                    StreamContent::Approval(approval_block(request)),
+                );
+                let _ = self.events_tx.send(status_event(
+                    &request.id,
+                    ToolCallState::Pending,
+                    None,
+                    Some("Waiting for approval".to_string()),
+                ));
+                self.pending.insert(item_id, request.clone());
+                Submission::NeedsApproval(Box::new(item))
+            }
+        }
+    }
+
+    /// Get the ID of the approval gate created for a tool call
+    pub fn approval_item_id(call_id: &str) -> String {
+        format!("{}{}", APPROVAL_ID_PREFIX, call_id)
+    }
+
+    /// Check whether an approval gate belongs to a pending tool call
+    pub fn is_pending_approval(&self, item_id: &str) -> bool {
+        self.pending.contains_key(item_id)
+    }
+
+    /// Apply the user's choice on a tool approval gate
+    ///
+    /// Returns false if the gate does not belong to a pending call.
+    pub fn resolve_approval(&mut self, item_id: &str, action_id: &str) -> bool {
+        let Some(request) = self.pending.remove(item_id) else {
+            return false;
+        };
+
+        let remembered = match action_id {
+            "always_allow" => Some(PolicyLevel::Allow),
+            "always_deny" => Some(PolicyLevel::Deny),
+            _ => None,
+        };
+        if let Some(level) = remembered {
+            match PolicyRule::for_call(&request.tool_name, &request.parameters, level) {
+                Some(rule) => {
+                    if let Err(e) = self.policy.remember(rule) {
+                        tracing::warn!("Failed to save tool policy: {}", e);
+                    }
+                }
+                None => tracing::warn!(
+                    "Not remembering decision for {}: arguments contain '*'",
+                    request.tool_name
+                ),
+            }
+        }
+
+        match action_id {
+            "allow" | "always_allow" => self.execute(&request, self.user.clone()),
+            _ => self.deny(&request),
+        }
+        true
+    }
+
+    /// Cancel a running call or one waiting for approval
+    ///
+    /// A running call reports Cancelled with its partial output once the
+    /// tool stops. Returns false if the call is unknown or already finished.
+    pub fn cancel(&mut self, call_id: &str) -> bool {
+        if let Some(token) = self.running.lock().unwrap().get(call_id) {
+            token.cancel();
+            return true;
+        }
+
+        let Some(request) = self.pending.remove(&Self::approval_item_id(call_id)) else {
+            return false;
+        };
+        self.finish_unstarted(&request, ToolError::Cancelled { partial: Value::Null });
+        true
+    }
+
+    /// Check whether a call is currently executing
+    pub fn is_running(&self, call_id: &str) -> bool {
+        self.running.lock().unwrap().contains_key(call_id)
+    }
+
+    /// Report a call as refused
+    fn deny(&self, request: &ToolCallRequest) {
+        let error = ToolError::ExecutionFailed("Denied by tool policy".to_string());
+        self.finish_unstarted(request, error);
+    }
+
+    /// Report a call that ended without running
+    fn finish_unstarted(&self, request: &ToolCallRequest, error: ToolError) {
+        let parameters = self.registry.prepare(&request.tool_name, &request.parameters).ok();
+        let events = completion_events(&request.id, Err(error));
+        audit(self.audit.as_deref(), request, None, parameters, Utc::now(), &events);
+        for event in events {
+            let _ = self.events_tx.send(event);
+        }
+    }
+
+    /// Start executing a tool call request, bypassing the policy
+    ///
+    /// Returns immediately; status and result events are emitted as the
+    /// call progresses.
+    fn execute(&self, request: &ToolCallRequest, approved_by: String) {
+        let registry = Arc::clone(&self.registry);
+        let events = self.events_tx.clone();
+        let request = request.clone();
+        let running = Arc::clone(&self.running);
+        let audit_log = self.audit.clone();
+        let token = CancellationToken::new();
+        running.lock().unwrap().insert(request.id.clone(), token.clone());
+
+        self.runtime.spawn(async move {
+            let id = request.id.clone();
+
+            // Calls over the registry's limits stay PENDING until a slot frees
+            let started_at = Arc::new(Mutex::new(None));
+            let start_time = Arc::clone(&started_at);
+            let start_events = events.clone();
+            let start_id = id.clone();
+            // Forward live output as TOOL_CALL_OUTPUT events
+            let output_events = events.clone();
+            let output_id = id.clone();
+            let context = ToolContext::new()
+                .with_on_start(move || {
+                    *start_time.lock().unwrap() = Some(Utc::now());
+                    let _ = start_events.send(status_event(
+                        &start_id,
+                        ToolCallState::Running,
+                        None,
+                        None,
+                    ));
+                })
+                .with_output(move |stream, chunk| {
+                    let _ = output_events.send(Event::ToolCallOutput(ToolCallOutput {
+                        id: output_id.clone(),
+                        stream,
+                        chunk: chunk.to_string(),
+                    }));
+                })
+                .with_cancellation(token);
+
+            let outcome = registry
+                .execute_with_context(&request.tool_name, &request.parameters, &context)
+                .await;
+
+            running.lock().unwrap().remove(&id);
+            let completion = completion_events(&id, outcome);
+            // Calls that never got a slot count as starting when they ended
+            let started_at = started_at.lock().unwrap().unwrap_or_else(Utc::now);
+            let parameters = registry.prepare(&request.tool_name, &request.parameters).ok();
+            audit(
+                audit_log.as_deref(),
+                &request,
+                Some(approved_by),
+                parameters,
+                started_at,
+                &completion,
+            );
+            for event in completion {
+                let _ = events.send(event);
+            }
+        });
+    }
+
+    /// Try to receive the next emitted event without blocking
+    pub fn try_recv(&mut self) -> Option<Event> {
+        self.events.try_recv().ok()
+    }
+
+    /// Wait for the next emitted event
+    pub async fn recv(&mut self) -> Option<Event> {
+        self.events.recv().await
+    }
+
+    /// Drain all pending events
+    pub fn drain(&mut self) -> Vec<Event> {
+        std::iter::from_fn(|| self.try_recv()).collect()
+    }
+}
+
+/// Append a finished call to the audit log, if there is one
+fn audit(
+    log: Option<&AuditLog>,
+    request: &ToolCallRequest,
+    approved_by: Option<String>,
+    parameters: Option<Value>,
+    started_at: DateTime<Utc>,
+    completion: &[Event; 2],
+) {
+    let (Some(log), [Event::ToolCallStatus(status), Event::ToolCallResult(result)]) =
+        (log, completion)
+    else {
+        return;
+    };
+    let record = AuditRecord::new(
+        request,
+        approved_by,
+        parameters,
+        started_at,
+        status.status.clone(),
+        result,
+    );
+    if let Err(e) = log.append(&record) {
+        tracing::error!("Failed to write tool audit log {}: {}", log.path().display(), e);
+    }
+}
+
+/// Name of the local account, recorded as the approver of gated calls
+fn local_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Approval gate asking to run a tool call
+fn approval_block(request: &ToolCallRequest) -> ApprovalBlock {
+    let action = |id: &str, label: String, variant| ApprovalAction {
+        id: id.to_string(),
+        label,
+        variant,
+        payload: None,
+    };
+
+    ApprovalBlock {
+        title: format!("Run {}?", request.tool_name),
+        description: Some(format!("Agent {} wants to run a local tool", request.agent_id)),
+        content: serde_json::to_string_pretty(&request.parameters).ok(),
+        content_type: Some("json".to_string()),
+        actions: vec![
+            action("allow", "Allow once".to_string(), ApprovalActionVariant::Primary),
+            action(
+                "always_allow",
+                "Always allow these arguments".to_string(),
+                ApprovalActionVariant::Secondary,
+            ),
+            action("deny", "Deny".to_string(), ApprovalActionVariant::Destructive),
+            action(
+                "always_deny",
+                "Never allow these arguments".to_string(),
+                ApprovalActionVariant::Destructive,
+            ),
+        ],
+        resolution: None,
+        blocking: true,
+    }
+}
+
+fn status_event(
+    id: &str,
+    status: ToolCallState,
+    progress: Option<u8>,
+    message: Option<String>,
+) -> Event {
+    Event::ToolCallStatus(ToolCallStatus {
+        id: id.to_string(),
+        status,
+        progress,
+        message,
+    })
+}
+
+/// Final status and result events for a finished call
+fn completion_events(id: &str, outcome: Result<Value, ToolError>) -> [Event; 2] {
+    match outcome {
+        Ok(result) => [
+            status_event(id, ToolCallState::Completed, Some(100), None),
+            Event::ToolCallResult(ToolCallResult {
+                id: id.to_string(),
+                result,
+                error: None,
+            }),
+        ],
+        Err(ToolError::Cancelled { partial }) => [
+            status_event(id, ToolCallState::Cancelled, None, None),
+            Event::ToolCallResult(ToolCallResult {
+                id: id.to_string(),
+                result: partial,
+                error: Some(ToolError::Cancelled { partial: Value::Null }.to_string()),
+            }),
+        ],
+        Err(error) => {
+            let message = error.to_string();
+            // Structured violations let the model correct its arguments
+            let result = match error {
+                ToolError::ValidationError(error) => json!({ "violations": error.violations }),
+                _ => Value::Null,
+            };
+            [
+                status_event(id, ToolCallState::Failed, None, Some(message.clone())),
+                Event::ToolCallResult(ToolCallResult {
+                    id: id.to_string(),
+                    result,
+                    error: Some(message),
+                }),
+            ]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::create_standard_registry;
+    use crate::protocol::OutputStream;
+    use crate::tools::policy::PolicyRule;
+    use crate::tools::AuditLog;
+    use serde_json::json;
+    use std::time::Duration;
+
+    async fn next_event(host: &mut ToolHost) -> Event {
+        tokio::time::timeout(Duration::from_secs(5), host.recv())
+            .await
+            .expect("timed out waiting for tool event")
+            .expect("host closed")
+    }
+
+    fn request(tool_name: &str, parameters: Value) -> ToolCallRequest {
+        ToolCallRequest {
+            id: "tc_1".to_string(),
+            tool_name: tool_name.to_string(),
+            parameters,
+            agent_id: "agent_1".to_string(),
+        }
+    }
+
+    fn allow_all() -> ToolPolicy {
+        ToolPolicy::new(PolicyLevel::Allow)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_success() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("notes.txt");
+        std::fs::write(&path, "hello").unwrap();
+
+        let mut host = ToolHost::new(create_standard_registry(), &Handle::current());
+        let submission = host.submit(&request("read_file", json!({"path": path.to_string_lossy()})));
+        assert!(matches!(submission, Submission::Started));
+
+        assert!(matches!(
+            next_event(&mut host).await,
+            Event::ToolCallStatus(s) if s.status == ToolCallState::Running
+        ));
+        assert!(matches!(
+            next_event(&mut host).await,
+            Event::ToolCallStatus(s) if s.status == ToolCallState::Completed
+        ));
+        match next_event(&mut host).await {
+            Event::ToolCallResult(r) => {
+                assert_eq!(r.id, "tc_1");
+                assert_eq!(r.result["content"], "hello");
+                assert!(r.error.is_none());
+            }
+            other => panic!("Expected result, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_invalid_parameters() {
+        let mut host = ToolHost::new(create_standard_registry(), &Handle::current());
+        host.submit(&request("read_file", json!({"path": 42})));
+
+        // Invalid calls fail before they start running
+        assert!(matches!(
+            next_event(&mut host).await,
+            Event::ToolCallStatus(s) if s.status == ToolCallState::Failed
+        ));
+        match next_event(&mut host).await {
+            Event::ToolCallResult(r) => {
+                assert!(r.error.unwrap().starts_with("Parameter validation failed"));
+                assert_eq!(r.result["violations"][0]["path"], "/path");
+                assert_eq!(r.result["violations"][0]["keyword"], "type");
+            }
+            other => panic!("Expected result, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_shell_command() {
+        let mut host =
+            ToolHost::new(create_standard_registry(), &Handle::current()).with_policy(allow_all());
+        host.submit(&request("run_command", json!({"command": "echo", "args": ["hi"]})));
+
+        next_event(&mut host).await;
+        assert!(matches!(
+            next_event(&mut host).await,
+            Event::ToolCallOutput(o) if o.stream == OutputStream::Stdout && o.chunk == "hi\n"
+        ));
+        next_event(&mut host).await;
+        assert!(matches!(
+            next_event(&mut host).await,
+            Event::ToolCallResult(r) if r.result["stdout"] == "hi"
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_unknown_tool() {
+        let mut host = ToolHost::new(ToolRegistry::new(), &Handle::current()).with_policy(allow_all());
+        host.submit(&request("missing", json!({})));
+
+        next_event(&mut host).await;
+        assert!(matches!(
+            next_event(&mut host).await,
+            Event::ToolCallResult(r) if r.error.as_deref() == Some("Tool not found: missing")
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_denied_by_policy() {
+        let policy = ToolPolicy::new(PolicyLevel::Allow)
+            .with_rule(PolicyRule::new("run_command", PolicyLevel::Deny).with_arg("command", "rm"));
+        let mut host = ToolHost::new(create_standard_registry(), &Handle::current()).with_policy(policy);
+
+        let submission = host.submit(&request("run_command", json!({"command": "rm", "args": ["-rf", "/"]})));
+        assert!(matches!(submission, Submission::Denied));
+        assert!(matches!(
+            host.try_recv(),
+            Some(Event::ToolCallStatus(s)) if s.status == ToolCallState::Failed
+        ));
+        assert!(matches!(
+            host.try_recv(),
+            Some(Event::ToolCallResult(r)) if r.error.as_deref() == Some("Execution failed: Denied by tool policy")
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_ask_gates_execution() {
+        let mut host = ToolHost::new(create_standard_registry(), &Handle::current());
+        let call = request("run_command", json!({"command": "echo", "args": ["ok"]}));
+
+        let Submission::NeedsApproval(item) = host.submit(&call) else {
+            panic!("Expected approval gate");
+        };
+        assert_eq!(item.id, "tool-approval-tc_1");
+        assert!(matches!(&item.content, StreamContent::Approval(a) if a.actions.len() == 4));
+        assert!(host.is_pending_approval(&item.id));
+
+        // Only the pending status is emitted until the gate is resolved
+        assert!(matches!(
+            host.try_recv(),
+            Some(Event::ToolCallStatus(s)) if s.status == ToolCallState::Pending
+        ));
+        assert!(host.try_recv().is_none());
+
+        assert!(!host.resolve_approval("unknown", "allow"));
+        assert!(host.resolve_approval(&item.id, "allow"));
+        assert!(!host.is_pending_approval(&item.id));
+
+        next_event(&mut host).await;
+        next_event(&mut host).await;
+        next_event(&mut host).await;
+        assert!(matches!(
+            next_event(&mut host).await,
+            Event::ToolCallResult(r) if r.result["stdout"] == "ok"
+        ));
+
+        // "Allow once" is not remembered
+        assert_eq!(
+            host.policy().evaluate("run_command", &call.parameters),
+            PolicyLevel::Ask
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cancel_running_call() {
+        let mut host =
+            ToolHost::new(create_standard_registry(), &Handle::current()).with_policy(allow_all());
+        host.submit(&request(
+            "run_command",
+            json!({"command": "sh", "args": ["-c", "echo partial; sleep 30"]}),
+        ));
+
+        assert!(host.is_running("tc_1"));
+        next_event(&mut host).await;
+        assert!(matches!(next_event(&mut host).await, Event::ToolCallOutput(_)));
+        assert!(host.cancel("tc_1"));
+
+        assert!(matches!(
+            next_event(&mut host).await,
+            Event::ToolCallStatus(s) if s.status == ToolCallState::Cancelled
+        ));
+        match next_event(&mut host).await {
+            Event::ToolCallResult(r) => {
+                assert_eq!(r.result["stdout"], "partial");
+                assert_eq!(r.error.as_deref(), Some("Cancelled"));
+            }
+            other => panic!("Expected result, got {:?}", other),
+        }
+        assert!(!host.is_running("tc_1"));
+        assert!(!host.cancel("tc_1"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cancel_pending_approval() {
+        let mut host = ToolHost::new(create_standard_registry(), &Handle::current());
+        let Submission::NeedsApproval(item) =
+            host.submit(&request("run_command", json!({"command": "echo"})))
+        else {
+            panic!("Expected approval gate");
+        };
+        host.drain();
+
+        assert!(host.cancel("tc_1"));
+        assert!(!host.is_pending_approval(&item.id));
+        assert!(matches!(
+            host.try_recv(),
+            Some(Event::ToolCallStatus(s)) if s.status == ToolCallState::Cancelled
+        ));
+        assert!(matches!(host.try_recv(), Some(Event::ToolCallResult(_))));
+        assert!(!host.resolve_approval(&item.id, "allow"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_remembered_decision() {
+        let mut host = ToolHost::new(create_standard_registry(), &Handle::current());
+        let call = request("write_file", json!({"path": "/nonexistent/x", "content": ""}));
+
+        let Submission::NeedsApproval(item) = host.submit(&call) else {
+            panic!("Expected approval gate");
+        };
+        assert!(host.resolve_approval(&item.id, "always_deny"));
+        host.drain();
+
+        assert!(matches!(host.submit(&call), Submission::Denied));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_audit_log() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("audit.jsonl");
+        let policy = ToolPolicy::new(PolicyLevel::Ask)
+            .with_rule(PolicyRule::new("file_exists", PolicyLevel::Allow))
+            .with_rule(PolicyRule::new("write_file", PolicyLevel::Deny));
+        let mut host = ToolHost::new(create_standard_registry(), &Handle::current())
+            .with_policy(policy)
+            .with_audit(AuditLog::new(&log_path))
+            .with_user("alice");
+
+        // Allowed by policy
+        host.submit(&request("file_exists", json!({"path": "/nonexistent"})));
+        for _ in 0..3 {
+            next_event(&mut host).await;
+        }
+        // Refused by policy
+        host.submit(&request("write_file", json!({"path": "/nonexistent/x", "content": ""})));
+        host.drain();
+        // Approved by the user
+        let Submission::NeedsApproval(item) =
+            host.submit(&request("run_command", json!({"command": "echo"})))
+        else {
+            panic!("Expected approval gate");
+        };
+        host.resolve_approval(&item.id, "allow");
+        loop {
+            if let Event::ToolCallResult(_) = next_event(&mut host).await {
+                break;
+            }
+        }
+
+        let records = AuditLog::new(&log_path).records().unwrap();
+        assert_eq!(records.len(), 3);
+
+        assert_eq!(records[0].request.tool_name, "file_exists");
+        assert_eq!(records[0].approved_by.as_deref(), Some("policy"));
+        assert_eq!(records[0].status, ToolCallState::Completed);
+        assert_eq!(
+            records[0].result_sha256,
+            crate::tools::audit::hash_result(&json!({"path": "/nonexistent", "exists": false}))
+        );
+        assert!(records[0].finished_at >= records[0].started_at);
+
+        assert_eq!(records[1].approved_by, None);
+        assert_eq!(records[1].status, ToolCallState::Failed);
+        assert_eq!(records[1].error.as_deref(), Some("Execution failed: Denied by tool policy"));
+
+        // Validated parameters include schema defaults
+        assert_eq!(records[2].approved_by.as_deref(), Some("alice"));
+        assert_eq!(records[2].request.parameters, json!({"command": "echo"}));
+        assert_ne!(records[2].parameters, Some(records[2].request.parameters.clone()));
+    }
+}
@@ -0,0 +1,513 @@
+//! Tool manifests
+//!
+//! Converts tool metadata to and from the tool-definition shapes used by
+//! LLM APIs, and to the TOOL_MANIFEST protocol event. Imported tools can be
+//! rendered as parameter forms so the user can invoke them from the UI.
+
+use super::schema::{ParameterSchema, SchemaError, SchemaType};
+use super::ToolMetadata;
+use crate::protocol::{ToolDefinition, ToolManifest};
+use crate::renderer::schema::{
+    CardProps, Component, FormProps, InputType, SelectOption, SelectProps, SliderProps,
+    TextAreaProps, TextInputProps, ToggleProps,
+};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Prefix of the form submit action that invokes a tool (`invoke_tool:<name>`)
+pub const INVOKE_TOOL_ACTION: &str = "invoke_tool";
+
+/// Tool definition formats of LLM APIs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolFormat {
+    /// `{"type": "function", "function": {name, description, parameters}}`
+    OpenAi,
+    /// `{name, description, input_schema}`
+    Anthropic,
+}
+
+/// Errors importing tool definitions
+#[derive(Debug, Error)]
+pub enum ManifestError {
+    #[error("Expected a list of tool definitions")]
+    NotAList,
+
+    #[error("Tool definition is missing '{0}'")]
+    MissingField(String),
+
+    #[error("Invalid schema for tool '{tool}': {source}")]
+    InvalidSchema {
+        tool: String,
+        #[source]
+        source: SchemaError,
+    },
+}
+
+impl ToolMetadata {
+    /// Export as a tool definition in an LLM API format
+    pub fn export(&self, format: ToolFormat) -> Value {
+        let parameters = self.parameters_schema.to_json();
+        match format {
+            ToolFormat::OpenAi => json!({
+                "type": "function",
+                "function": {
+                    "name": self.name,
+                    "description": self.description,
+                    "parameters": parameters,
+                },
+            }),
+            ToolFormat::Anthropic => json!({
+                "name": self.name,
+                "description": self.description,
+                "input_schema": parameters,
+            }),
+        }
+    }
+
+    /// Import a tool definition, detecting its format
+    ///
+    /// Accepts OpenAI definitions (nested under `function` or flat with
+    /// `parameters`), Anthropic definitions (`input_schema`), and the
+    /// protocol's own `ToolDefinition` shape.
+    pub fn import(definition: &Value) -> Result<Self, ManifestError> {
+        let definition = definition.get("function").unwrap_or(definition);
+        let name = definition
+            .get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ManifestError::MissingField("name".to_string()))?;
+        let description = definition
+            .get("description")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        let parameters = definition
+            .get("parameters")
+            .or_else(|| definition.get("input_schema"))
+            .filter(|v| !v.is_null());
+
+        let parameters_schema = match parameters {
+            Some(schema) => ParameterSchema::from_json(schema).map_err(|source| {
+                ManifestError::InvalidSchema {
+                    tool: name.to_string(),
+                    source,
+                }
+            })?,
+            None => ParameterSchema::object(HashMap::new()),
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            parameters_schema,
+        })
+    }
+
+    /// Convert to the protocol's tool definition
+    pub fn to_definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            parameters: self.parameters_schema.to_json(),
+        }
+    }
+}
+
+impl TryFrom<&ToolDefinition> for ToolMetadata {
+    type Error = ManifestError;
+
+    fn try_from(definition: &ToolDefinition) -> Result<Self, Self::Error> {
+        let parameters_schema = if definition.parameters.is_null() {
+            ParameterSchema::object(HashMap::new())
+        } else {
+            ParameterSchema::from_json(&definition.parameters).map_err(|source| {
+                ManifestError::InvalidSchema {
+                    tool: definition.name.clone(),
+                    source,
+                }
+            })?
+        };
+
+        Ok(Self {
+            name: definition.name.clone(),
+            description: definition.description.clone(),
+            parameters_schema,
+        })
+    }
+}
+
+/// Import a list of tool definitions in any supported format
+///
+/// Accepts a bare array or an object with a `tools` array.
+pub fn import_tools(definitions: &Value) -> Result<Vec<ToolMetadata>, ManifestError> {
+    definitions
+        .as_array()
+        .or_else(|| definitions.get("tools").and_then(|v| v.as_array()))
+        .ok_or(ManifestError::NotAList)?
+        .iter()
+        .map(ToolMetadata::import)
+        .collect()
+}
+
+/// Import the tools declared in a TOOL_MANIFEST
+pub fn import_manifest(manifest: &ToolManifest) -> Result<Vec<ToolMetadata>, ManifestError> {
+    manifest.tools.iter().map(ToolMetadata::try_from).collect()
+}
+
+/// Build a manifest from tool metadata, sorted by name
+pub fn build_manifest(mut tools: Vec<ToolMetadata>) -> ToolManifest {
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    ToolManifest {
+        tools: tools.iter().map(ToolMetadata::to_definition).collect(),
+    }
+}
+
+/// Build a card with a form for a tool's parameters
+///
+/// The form submits `invoke_tool:<name>`; `form_parameters` turns the
+/// submitted values back into tool parameters.
+pub fn parameter_form(tool: &ToolMetadata) -> Component {
+    let schema = &tool.parameters_schema;
+    let required = schema.required.clone().unwrap_or_default();
+
+    // Required parameters first, in declared order, then the rest by name
+    let mut names: Vec<&String> = schema.properties.iter().flat_map(|p| p.keys()).collect();
+    names.sort_by_key(|name| {
+        (
+            required.iter().position(|r| r == *name).unwrap_or(usize::MAX),
+            name.as_str(),
+        )
+    });
+
+    let children = names
+        .into_iter()
+        .filter_map(|name| {
+            let property = schema.properties.as_ref()?.get(name)?;
+            Some(parameter_field(name, property, required.contains(name)))
+        })
+        .collect();
+
+    Component::Card(CardProps {
+        id: tool.name.clone(),
+        content: Box::new(Component::Form(FormProps {
+            id: "parameters".to_string(),
+            children,
+            submit_action: format!("{}:{}", INVOKE_TOOL_ACTION, tool.name),
+            cancel_action: None,
+            submit_label: Some("Run".to_string()),
+            cancel_label: None,
+        })),
+        title: Some(tool.name.clone()),
+        subtitle: Some(tool.description.clone()).filter(|d| !d.is_empty()),
+        header: None,
+        footer: None,
+        padding: None,
+    })
+}
+
+/// Pick an input component for one parameter
+fn parameter_field(name: &str, schema: &ParameterSchema, required: bool) -> Component {
+    let id = name.to_string();
+    let label = Some(name.to_string());
+    let placeholder = schema.description.clone();
+    let default = schema.default.as_ref();
+
+    if let Some(values) = &schema.enum_values {
+        return Component::Select(SelectProps {
+            id,
+            label,
+            options: values
+                .iter()
+                .map(|v| {
+                    let value = display_value(v);
+                    SelectOption {
+                        label: value.clone(),
+                        value,
+                        disabled: false,
+                    }
+                })
+                .collect(),
+            value: default.map(display_value),
+            placeholder,
+            disabled: false,
+            required,
+            multi: false,
+            error: None,
+        });
+    }
+
+    match schema.schema_type {
+        Some(SchemaType::Boolean) => Component::Toggle(ToggleProps {
+            id,
+            label,
+            checked: default.and_then(|v| v.as_bool()).unwrap_or(false),
+            disabled: false,
+        }),
+        Some(SchemaType::Number | SchemaType::Integer)
+            if schema.minimum.is_some() && schema.maximum.is_some() =>
+        {
+            let min = schema.minimum.unwrap_or_default();
+            let max = schema.maximum.unwrap_or_default();
+            let step = if schema.schema_type == Some(SchemaType::Integer) {
+                1.0
+            } else {
+                (max - min) / 100.0
+            };
+            Component::Slider(SliderProps {
+                id,
+                label,
+                min,
+                max,
+                value: default.and_then(|v| v.as_f64()).unwrap_or(min),
+                step,
+                disabled: false,
+                show_value: true,
+            })
+        }
+        Some(SchemaType::String | SchemaType::Number | SchemaType::Integer) => {
+            let input_type = match (schema.schema_type, schema.format.as_deref()) {
+                (Some(SchemaType::String), Some("email")) => InputType::Email,
+                (Some(SchemaType::String), Some("uri")) => InputType::Url,
+                (Some(SchemaType::String), _) => InputType::Text,
+                _ => InputType::Number,
+            };
+            Component::TextInput(TextInputProps {
+                id,
+                label,
+                placeholder,
+                value: default.map(display_value).unwrap_or_default(),
+                disabled: false,
+                required,
+                input_type,
+                error: None,
+            })
+        }
+        // Arrays, objects and composite schemas are entered as JSON
+        _ => Component::TextArea(TextAreaProps {
+            id,
+            label,
+            placeholder: Some(placeholder.unwrap_or_else(|| "JSON value".to_string())),
+            value: default.map(|v| v.to_string()).unwrap_or_default(),
+            disabled: false,
+            required,
+            rows: 4,
+            error: None,
+        }),
+    }
+}
+
+/// Convert submitted form values to tool parameters
+///
+/// Keys may carry the form's index prefix (`0.path`); values are coerced to
+/// the parameter's schema type. Empty fields are left out so defaults apply,
+/// and values that do not parse are passed through for validation to report.
+pub fn form_parameters(tool: &ToolMetadata, values: &Map<String, Value>) -> Value {
+    let properties = tool.parameters_schema.properties.as_ref();
+    let mut parameters = Map::new();
+
+    for (key, value) in values {
+        let name = key.rsplit_once('.').map_or(key.as_str(), |(_, name)| name);
+        let schema = properties.and_then(|p| p.get(name));
+        if let Some(value) = coerce_value(value, schema) {
+            parameters.insert(name.to_string(), value);
+        }
+    }
+
+    Value::Object(parameters)
+}
+
+/// Coerce a form value to a parameter's schema type
+fn coerce_value(value: &Value, schema: Option<&ParameterSchema>) -> Option<Value> {
+    let text = match value {
+        Value::Null => return None,
+        Value::String(s) if s.trim().is_empty() => return None,
+        Value::String(s) => s.trim(),
+        Value::Number(n) => {
+            let whole = n.as_f64().filter(|f| f.fract() == 0.0);
+            return Some(match (schema.and_then(|s| s.schema_type), whole) {
+                (Some(SchemaType::Integer), Some(f)) => json!(f as i64),
+                _ => value.clone(),
+            });
+        }
+        _ => return Some(value.clone()),
+    };
+
+    let parsed = schema.and_then(|schema| match schema.schema_type {
+        Some(SchemaType::String) => None,
+        Some(SchemaType::Integer) => text.parse::<i64>().ok().map(Value::from),
+        Some(SchemaType::Number) => text.parse::<f64>().ok().map(Value::from),
+        Some(SchemaType::Boolean) => text.parse::<bool>().ok().map(Value::from),
+        // Arrays, objects, enums and composite schemas take JSON
+        _ => serde_json::from_str(text).ok(),
+    });
+    Some(parsed.unwrap_or_else(|| Value::String(text.to_string())))
+}
+
+/// Show a JSON value in a form field
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::create_standard_registry;
+
+    fn sample_tool() -> ToolMetadata {
+        let mut props = HashMap::new();
+        props.insert(
+            "path".to_string(),
+            ParameterSchema::string().with_description("File to read"),
+        );
+        props.insert(
+            "limit".to_string(),
+            ParameterSchema::integer().with_minimum(1.0).with_maximum(100.0),
+        );
+        props.insert("offset".to_string(), ParameterSchema::integer());
+        props.insert(
+            "mode".to_string(),
+            ParameterSchema::string().with_enum(vec![json!("fast"), json!("full")]),
+        );
+        props.insert("verbose".to_string(), ParameterSchema::boolean());
+        props.insert("tags".to_string(), ParameterSchema::array(ParameterSchema::string()));
+        ToolMetadata {
+            name: "read".to_string(),
+            description: "Read a file".to_string(),
+            parameters_schema: ParameterSchema::object(props)
+                .with_required(vec!["path".to_string()]),
+        }
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let tool = sample_tool();
+
+        let openai = tool.export(ToolFormat::OpenAi);
+        assert_eq!(openai["type"], "function");
+        assert_eq!(openai["function"]["name"], "read");
+        assert_eq!(openai["function"]["parameters"]["required"], json!(["path"]));
+
+        let anthropic = tool.export(ToolFormat::Anthropic);
+        assert_eq!(anthropic["input_schema"]["properties"]["limit"]["type"], "integer");
+
+        for exported in [openai, anthropic, json!(tool.to_definition())] {
+            let imported = ToolMetadata::import(&exported).unwrap();
+            assert_eq!(imported.name, "read");
+            assert_eq!(imported.description, "Read a file");
+            assert_eq!(imported.parameters_schema, tool.parameters_schema);
+        }
+    }
+
+    #[test]
+    fn test_import_tools() {
+        let tools = import_tools(&json!({"tools": [
+            {"name": "ping"},
+            {"type": "function", "function": {"name": "echo", "parameters": {
+                "type": "object",
+                "properties": {"text": {"type": "string"}}
+            }}}
+        ]}))
+        .unwrap();
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools[0].parameters_schema.schema_type, Some(SchemaType::Object));
+        assert!(tools[1].parameters_schema.properties.as_ref().unwrap().contains_key("text"));
+
+        assert!(matches!(import_tools(&json!({})), Err(ManifestError::NotAList)));
+        assert!(matches!(
+            import_tools(&json!([{"description": "nameless"}])),
+            Err(ManifestError::MissingField(_))
+        ));
+        assert!(matches!(
+            import_tools(&json!([{"name": "bad", "parameters": {"type": "tuple"}}])),
+            Err(ManifestError::InvalidSchema { .. })
+        ));
+    }
+
+    #[test]
+    fn test_registry_manifest() {
+        let registry = create_standard_registry();
+        let manifest = registry.manifest();
+        let names: Vec<_> = manifest.tools.iter().map(|t| t.name.as_str()).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+        assert!(names.contains(&"read_file"));
+
+        let imported = import_manifest(&manifest).unwrap();
+        let read_file = registry.get("read_file").unwrap();
+        let round_tripped = imported.iter().find(|t| t.name == "read_file").unwrap();
+        assert_eq!(&round_tripped.parameters_schema, read_file.parameters_schema());
+
+        let exported = registry.export_tools(ToolFormat::Anthropic);
+        assert_eq!(exported.len(), names.len());
+        assert!(exported.iter().all(|t| t["input_schema"]["type"] == "object"));
+    }
+
+    #[test]
+    fn test_parameter_form() {
+        let Component::Card(card) = parameter_form(&sample_tool()) else {
+            panic!("expected a card");
+        };
+        assert_eq!(card.title.as_deref(), Some("read"));
+        let Component::Form(form) = *card.content else {
+            panic!("expected a form");
+        };
+        assert_eq!(form.submit_action, "invoke_tool:read");
+
+        let kinds: Vec<_> = form
+            .children
+            .iter()
+            .map(|c| match c {
+                Component::TextInput(p) => format!("{} text {:?} {}", p.id, p.input_type, p.required),
+                Component::Slider(p) => format!("{} slider {}-{}", p.id, p.min, p.max),
+                Component::Select(p) => format!("{} select {}", p.id, p.options.len()),
+                Component::Toggle(p) => format!("{} toggle", p.id),
+                Component::TextArea(p) => format!("{} json", p.id),
+                other => format!("{:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "path text Text true",
+                "limit slider 1-100",
+                "mode select 2",
+                "offset text Number false",
+                "tags json",
+                "verbose toggle",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_form_parameters() {
+        let tool = sample_tool();
+        let values = json!({
+            "0.path": "src/main.rs",
+            "1.limit": 10.0,
+            "3.offset": " 20 ",
+            "4.tags": "[\"a\", \"b\"]",
+            "5.verbose": true,
+            "2.mode": "",
+            "unknown": "x",
+        });
+
+        let params = form_parameters(&tool, values.as_object().unwrap());
+        assert_eq!(
+            params,
+            json!({
+                "path": "src/main.rs",
+                "limit": 10,
+                "offset": 20,
+                "tags": ["a", "b"],
+                "verbose": true,
+                "unknown": "x",
+            })
+        );
+
+        let invalid = form_parameters(&tool, json!({"offset": "soon"}).as_object().unwrap());
+        assert_eq!(invalid, json!({"offset": "soon"}));
+    }
+}
@@ -2,20 +2,51 @@
 //!
 //! Provides a trait-based system for defining and executing tools that
 //! can be invoked by agents. Tools include file operations and shell commands.
+//!
+//! Tools are async. The registry runs them with a bounded number of calls
+//! in flight, plus optional per-tool limits; calls over a limit wait in
+//! FIFO order. Tools that block on I/O run their work through
+//! `run_blocking` so they never stall the async workers.
 
 pub mod schema;
+pub mod audit;
 pub mod file_ops;
+pub mod host;
+pub mod manifest;
+pub mod patch;
+pub mod plugin;
+pub mod policy;
+pub mod sandbox;
+pub mod search;
 pub mod shell;
-
+pub mod terminal;
+
+pub use audit::{AuditError, AuditFilter, AuditLog, AuditQuery, AuditRecord};
+pub use host::{Submission, ToolHost};
+pub use manifest::{ManifestError, ToolFormat};
+pub use plugin::{register_plugins, PluginError, PluginTool};
+pub use policy::{PolicyLevel, PolicyRule, ToolPolicy};
+pub use sandbox::Sandbox;
+pub use terminal::{TerminalEvent, TerminalManager};
+
+use crate::protocol::{OutputStream, ToolManifest};
+use async_trait::async_trait;
 use schema::{ParameterSchema, ValidationError};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
 use thiserror::Error;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio_util::sync::CancellationToken;
+
+/// Default number of tool calls the registry runs at once
+pub const DEFAULT_MAX_CONCURRENT_CALLS: usize = 8;
 
 /// Result type for tool execution
 pub type ToolResult = Result<Value, ToolError>;
 
 /// Tool trait that all executable tools must implement
+#[async_trait]
 pub trait Tool: Send + Sync {
     /// Unique name/identifier for this tool
     fn name(&self) -> &str;
@@ -26,8 +57,98 @@ pub trait Tool: Send + Sync {
     /// JSON Schema defining the parameters this tool accepts
     fn parameters_schema(&self) -> &ParameterSchema;
 
-    /// Execute the tool with the given parameters
-    fn execute(&self, params: &Value) -> ToolResult;
+    /// Execute the tool, reporting incremental output through the context
+    async fn execute(&self, params: &Value, context: &ToolContext) -> ToolResult;
+
+    /// Most calls of this tool that may run at once (None for no limit)
+    fn max_concurrency(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Run a tool's blocking work on the blocking thread pool
+pub(crate) async fn run_blocking<T, F>(tool: &T, params: &Value, work: F) -> ToolResult
+where
+    T: Clone + Send + 'static,
+    F: FnOnce(&T, &Value) -> ToolResult + Send + 'static,
+{
+    let tool = tool.clone();
+    let params = params.clone();
+    tokio::task::spawn_blocking(move || work(&tool, &params))
+        .await
+        .unwrap_or_else(|e| Err(ToolError::ExecutionFailed(format!("Tool panicked: {}", e))))
+}
+
+/// Callback receiving incremental tool output
+type OutputSink = Arc<dyn Fn(OutputStream, &str) + Send + Sync>;
+
+/// Callback run when a queued call starts executing
+type StartHook = Arc<dyn Fn() + Send + Sync>;
+
+/// Per-call execution context passed to tools
+#[derive(Clone, Default)]
+pub struct ToolContext {
+    output: Option<OutputSink>,
+    on_start: Option<StartHook>,
+    cancel: CancellationToken,
+}
+
+impl ToolContext {
+    /// Create a context that discards output
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forward incremental output to a callback
+    pub fn with_output(mut self, sink: impl Fn(OutputStream, &str) + Send + Sync + 'static) -> Self {
+        self.output = Some(Arc::new(sink));
+        self
+    }
+
+    /// Run a callback once the call leaves the queue and starts
+    pub fn with_on_start(mut self, hook: impl Fn() + Send + Sync + 'static) -> Self {
+        self.on_start = Some(Arc::new(hook));
+        self
+    }
+
+    /// Use a token the caller can trigger to cancel the call
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Report a chunk of output
+    pub fn emit(&self, stream: OutputStream, chunk: &str) {
+        if let Some(sink) = &self.output {
+            sink(stream, chunk);
+        }
+    }
+
+    /// Report that the call has started
+    fn start(&self) {
+        if let Some(hook) = &self.on_start {
+            hook();
+        }
+    }
+
+    /// Check whether the call has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Wait until the call is cancelled
+    pub async fn cancelled(&self) {
+        self.cancel.cancelled().await
+    }
+}
+
+impl std::fmt::Debug for ToolContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolContext")
+            .field("output", &self.output.is_some())
+            .field("cancelled", &self.cancel.is_cancelled())
+            .finish()
+    }
 }
 
 /// Errors that can occur during tool execution
@@ -48,13 +169,25 @@ pub enum ToolError {
     #[error("Timeout exceeded")]
     Timeout,
 
+    /// Cancelled by the user or orchestrator, with any output produced so far
+    #[error("Cancelled")]
+    Cancelled { partial: Value },
+
     #[error("Tool not found: {0}")]
     NotFound(String),
+
+    #[error("Path '{0}' is outside the workspace roots")]
+    PathViolation(String),
 }
 
-/// Registry for looking up tools by name
+/// Registry for looking up and executing tools by name
 pub struct ToolRegistry {
     tools: HashMap<String, Box<dyn Tool>>,
+    /// Slots shared by all calls
+    slots: Semaphore,
+    max_concurrent: usize,
+    /// Slots of tools with their own limit
+    tool_slots: HashMap<String, Semaphore>,
 }
 
 impl ToolRegistry {
@@ -62,12 +195,41 @@ impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            slots: Semaphore::new(DEFAULT_MAX_CONCURRENT_CALLS),
+            max_concurrent: DEFAULT_MAX_CONCURRENT_CALLS,
+            tool_slots: HashMap::new(),
         }
     }
 
+    /// Set how many calls may run at once across all tools
+    pub fn with_max_concurrency(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent.max(1);
+        self.slots = Semaphore::new(self.max_concurrent);
+        self
+    }
+
+    /// Limit how many calls of one tool may run at once
+    ///
+    /// Overrides the tool's own `max_concurrency`.
+    pub fn set_tool_concurrency(&mut self, name: &str, max_concurrent: usize) {
+        self.tool_slots
+            .insert(name.to_string(), Semaphore::new(max_concurrent.max(1)));
+    }
+
+    /// Get how many calls may run at once across all tools
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrent
+    }
+
     /// Register a tool
     pub fn register(&mut self, tool: Box<dyn Tool>) {
         let name = tool.name().to_string();
+        match tool.max_concurrency() {
+            Some(limit) => self.set_tool_concurrency(&name, limit),
+            None => {
+                self.tool_slots.remove(&name);
+            }
+        }
         self.tools.insert(name, tool);
     }
 
@@ -77,14 +239,44 @@ impl ToolRegistry {
     }
 
     /// Execute a tool by name with the given parameters
-    pub fn execute(&self, name: &str, params: &Value) -> ToolResult {
+    pub async fn execute(&self, name: &str, params: &Value) -> ToolResult {
+        self.execute_with_context(name, params, &ToolContext::new()).await
+    }
+
+    /// Execute a tool by name, reporting incremental output through the context
+    ///
+    /// Invalid calls fail before queueing. A call waiting for a slot can be
+    /// cancelled through the context.
+    pub async fn execute_with_context(
+        &self,
+        name: &str,
+        params: &Value,
+        context: &ToolContext,
+    ) -> ToolResult {
         let tool = self.get(name).ok_or_else(|| ToolError::NotFound(name.to_string()))?;
+        let params = self.prepare(name, params)?;
 
-        // Validate parameters against schema
-        tool.parameters_schema().validate(params)?;
+        // Take the tool's own slot first so it never holds a shared one while waiting
+        let _tool_slot = match self.tool_slots.get(name) {
+            Some(slots) => Some(acquire(slots, context).await?),
+            None => None,
+        };
+        let _slot = acquire(&self.slots, context).await?;
 
         // Execute the tool
-        tool.execute(params)
+        context.start();
+        tool.execute(&params, context).await
+    }
+
+    /// Fill in schema defaults and validate parameters for a tool
+    ///
+    /// Returns the parameters the tool would be executed with.
+    pub fn prepare(&self, name: &str, params: &Value) -> Result<Value, ToolError> {
+        let tool = self.get(name).ok_or_else(|| ToolError::NotFound(name.to_string()))?;
+        let schema = tool.parameters_schema();
+        let params = schema.apply_defaults(params);
+        schema.validate(&params)?;
+        Ok(params)
     }
 
     /// List all registered tool names
@@ -103,6 +295,18 @@ impl ToolRegistry {
             })
             .collect()
     }
+
+    /// Export all tools in an LLM API format, sorted by name
+    pub fn export_tools(&self, format: ToolFormat) -> Vec<Value> {
+        let mut tools = self.tool_metadata();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        tools.iter().map(|tool| tool.export(format)).collect()
+    }
+
+    /// Build the TOOL_MANIFEST advertising all tools
+    pub fn manifest(&self) -> ToolManifest {
+        manifest::build_manifest(self.tool_metadata())
+    }
 }
 
 impl Default for ToolRegistry {
@@ -111,6 +315,20 @@ impl Default for ToolRegistry {
     }
 }
 
+/// Wait for an execution slot, unless the call is cancelled first
+async fn acquire<'a>(
+    slots: &'a Semaphore,
+    context: &ToolContext,
+) -> Result<SemaphorePermit<'a>, ToolError> {
+    // Check cancellation first so an already cancelled call never takes a free slot
+    tokio::select! {
+        biased;
+        _ = context.cancelled() => Err(ToolError::Cancelled { partial: Value::Null }),
+        permit = slots.acquire() => permit
+            .map_err(|_| ToolError::ExecutionFailed("Tool executor closed".to_string())),
+    }
+}
+
 /// Metadata about a tool
 #[derive(Debug, Clone)]
 pub struct ToolMetadata {
@@ -120,14 +338,26 @@ pub struct ToolMetadata {
 }
 
 /// Create a new registry with all standard tools registered
+///
+/// File tools may access any path; see `create_sandboxed_registry`.
 pub fn create_standard_registry() -> ToolRegistry {
+    create_sandboxed_registry(Sandbox::unrestricted())
+}
+
+/// Create a registry with file tools confined to a sandbox
+pub fn create_sandboxed_registry(sandbox: Sandbox) -> ToolRegistry {
     let mut registry = ToolRegistry::new();
 
     // Register file operation tools
-    registry.register(Box::new(file_ops::ReadFile));
-    registry.register(Box::new(file_ops::WriteFile));
-    registry.register(Box::new(file_ops::ListDirectory));
-    registry.register(Box::new(file_ops::FileExists));
+    registry.register(Box::new(file_ops::ReadFile::new(sandbox.clone())));
+    registry.register(Box::new(file_ops::WriteFile::new(sandbox.clone())));
+    registry.register(Box::new(file_ops::ListDirectory::new(sandbox.clone())));
+    registry.register(Box::new(file_ops::FileExists::new(sandbox.clone())));
+
+    // Register search and patch tools
+    registry.register(Box::new(search::SearchFiles::new(sandbox.clone())));
+    registry.register(Box::new(search::GlobFiles::new(sandbox.clone())));
+    registry.register(Box::new(patch::ApplyPatch::new(sandbox)));
 
     // Register shell tool
     registry.register(Box::new(shell::RunCommand));
@@ -139,8 +369,8 @@ pub fn create_standard_registry() -> ToolRegistry {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_registry_basic_operations() {
+    #[tokio::test]
+    async fn test_registry_basic_operations() {
         let mut registry = ToolRegistry::new();
 
         // Register a simple test tool
@@ -156,38 +386,48 @@ mod tests {
         assert_eq!(tool.unwrap().name(), "mock_test");
 
         // Execute tool
-        let result = registry.execute(
-            "mock_test",
-            &serde_json::json!({"value": "test"}),
-        );
+        let result = registry
+            .execute("mock_test", &serde_json::json!({"value": "test"}))
+            .await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), serde_json::json!("processed: test".to_string()));
     }
 
-    #[test]
-    fn test_registry_not_found() {
+    #[tokio::test]
+    async fn test_registry_not_found() {
         let registry = ToolRegistry::new();
-        let result = registry.execute("nonexistent", &serde_json::json!({}));
+        let result = registry.execute("nonexistent", &serde_json::json!({})).await;
         assert!(matches!(result, Err(ToolError::NotFound(_))));
     }
 
-    #[test]
-    fn test_registry_parameter_validation() {
+    #[tokio::test]
+    async fn test_registry_parameter_validation() {
         let mut registry = ToolRegistry::new();
         registry.register(Box::new(MockTestTool));
 
         // Missing required parameter
-        let result = registry.execute("mock_test", &serde_json::json!({}));
+        let result = registry.execute("mock_test", &serde_json::json!({})).await;
         assert!(matches!(result, Err(ToolError::ValidationError(_))));
 
         // Wrong parameter type
-        let result = registry.execute(
-            "mock_test",
-            &serde_json::json!({"value": 123}),
-        );
+        let result = registry
+            .execute("mock_test", &serde_json::json!({"value": 123}))
+            .await;
         assert!(matches!(result, Err(ToolError::ValidationError(_))));
     }
 
+    #[tokio::test]
+    async fn test_registry_applies_defaults() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(EchoTool));
+
+        let result = registry
+            .execute("echo", &serde_json::json!({"value": "x"}))
+            .await
+            .unwrap();
+        assert_eq!(result, serde_json::json!({"value": "x", "count": 1}));
+    }
+
     #[test]
     fn test_standard_registry() {
         let registry = create_standard_registry();
@@ -198,11 +438,80 @@ mod tests {
         assert!(tools.contains(&"list_directory".to_string()));
         assert!(tools.contains(&"file_exists".to_string()));
         assert!(tools.contains(&"run_command".to_string()));
+        assert!(tools.contains(&"search_files".to_string()));
+        assert!(tools.contains(&"glob_files".to_string()));
+        assert!(tools.contains(&"apply_patch".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_registry_concurrency_limits() {
+        let tracker = Arc::new(SlowTool::default());
+        let mut registry = ToolRegistry::new().with_max_concurrency(3);
+        registry.register(Box::new(Arc::clone(&tracker)));
+
+        let params = serde_json::json!({});
+        let calls = (0..6).map(|_| registry.execute("slow", &params));
+        let results = futures::future::join_all(calls).await;
+        assert!(results.iter().all(|r| r.is_ok()));
+        assert_eq!(tracker.peak(), 3);
+
+        // A per-tool limit queues calls below the shared limit
+        let tracker = Arc::new(SlowTool::default());
+        let mut registry = ToolRegistry::new().with_max_concurrency(3);
+        registry.register(Box::new(Arc::clone(&tracker)));
+        registry.set_tool_concurrency("slow", 1);
+
+        let calls = (0..3).map(|_| registry.execute("slow", &params));
+        futures::future::join_all(calls).await;
+        assert_eq!(tracker.peak(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_registry_queued_call_cancel() {
+        let mut registry = ToolRegistry::new().with_max_concurrency(1);
+        registry.register(Box::new(Arc::new(SlowTool::default())));
+
+        let started = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = Arc::clone(&started);
+        let token = CancellationToken::new();
+        let context = ToolContext::new()
+            .with_cancellation(token.clone())
+            .with_on_start(move || flag.store(true, std::sync::atomic::Ordering::SeqCst));
+
+        let params = serde_json::json!({});
+        let running = registry.execute("slow", &params);
+        let queued = async {
+            token.cancel();
+            registry.execute_with_context("slow", &params, &context).await
+        };
+        let (running, queued) = tokio::join!(running, queued);
+
+        assert!(running.is_ok());
+        assert!(matches!(queued, Err(ToolError::Cancelled { .. })));
+        assert!(!started.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_registry_cancelled_call_never_starts() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Box::new(EchoTool));
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let context = ToolContext::new().with_cancellation(token);
+        // Slots are free, but the cancellation must still win every time
+        for _ in 0..32 {
+            let result = registry
+                .execute_with_context("echo", &serde_json::json!({"value": "x"}), &context)
+                .await;
+            assert!(matches!(result, Err(ToolError::Cancelled { .. })));
+        }
     }
 
     /// Mock tool for testing
     struct MockTestTool;
 
+    #[async_trait]
     impl Tool for MockTestTool {
         fn name(&self) -> &str {
             "mock_test"
@@ -222,7 +531,7 @@ mod tests {
             })
         }
 
-        fn execute(&self, params: &Value) -> ToolResult {
+        async fn execute(&self, params: &Value, _context: &ToolContext) -> ToolResult {
             let value = params
                 .get("value")
                 .and_then(|v| v.as_str())
@@ -231,4 +540,73 @@ mod tests {
             Ok(serde_json::json!(format!("processed: {}", value)))
         }
     }
+
+    /// Mock tool returning its normalized parameters
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Returns its parameters"
+        }
+
+        fn parameters_schema(&self) -> &ParameterSchema {
+            static SCHEMA: std::sync::OnceLock<ParameterSchema> = std::sync::OnceLock::new();
+            SCHEMA.get_or_init(|| {
+                let mut props = std::collections::HashMap::new();
+                props.insert("value".to_string(), ParameterSchema::string());
+                props.insert(
+                    "count".to_string(),
+                    ParameterSchema::integer().with_default(serde_json::json!(1)),
+                );
+                ParameterSchema::object(props)
+            })
+        }
+
+        async fn execute(&self, params: &Value, _context: &ToolContext) -> ToolResult {
+            Ok(params.clone())
+        }
+    }
+
+    /// Mock tool that sleeps briefly, tracking how many calls overlap
+    #[derive(Default)]
+    struct SlowTool {
+        active: std::sync::atomic::AtomicUsize,
+        peak: std::sync::atomic::AtomicUsize,
+    }
+
+    impl SlowTool {
+        fn peak(&self) -> usize {
+            self.peak.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl Tool for Arc<SlowTool> {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn description(&self) -> &str {
+            "Sleeps for a moment"
+        }
+
+        fn parameters_schema(&self) -> &ParameterSchema {
+            static SCHEMA: std::sync::OnceLock<ParameterSchema> = std::sync::OnceLock::new();
+            SCHEMA.get_or_init(|| ParameterSchema::object(HashMap::new()))
+        }
+
+        async fn execute(&self, _params: &Value, _context: &ToolContext) -> ToolResult {
+            use std::sync::atomic::Ordering;
+            let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(active, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            Ok(Value::Null)
+        }
+    }
 }
@@ -0,0 +1,398 @@
+//! Patch application tool
+//!
+//! Provides `apply_patch`, which applies a unified diff to files in the
+//! workspace using the same hunk model as the Stage diff view
+//! (`stage::diff`). Every file is patched in memory first, so a hunk that
+//! does not match leaves the workspace untouched. Each section reads its
+//! old path and writes its new path, so renames work, and a file may only
+//! appear in one section.
+
+use super::sandbox::Sandbox;
+use super::{run_blocking, Tool, ToolContext, ToolError, ToolResult};
+use async_trait::async_trait;
+use crate::stage::diff::{apply_hunks, compute_unified_diff, diff_stats, parse_patch};
+use serde_json::json;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+/// Apply a unified diff to workspace files
+#[derive(Debug, Clone, Default)]
+pub struct ApplyPatch {
+    sandbox: Sandbox,
+}
+
+impl ApplyPatch {
+    /// Create the tool confined to a sandbox
+    pub fn new(sandbox: Sandbox) -> Self {
+        Self { sandbox }
+    }
+}
+
+/// A file change computed from the patch, not yet written
+struct PendingChange {
+    path: String,
+    /// Path the file had before the patch, when it is renamed
+    old_path: Option<String>,
+    /// File the original content was read from; None for new files
+    source: Option<PathBuf>,
+    /// File the new content is written to; None for deletions
+    target: Option<PathBuf>,
+    content: String,
+    original: String,
+}
+
+#[async_trait]
+impl Tool for ApplyPatch {
+    fn name(&self) -> &str {
+        "apply_patch"
+    }
+
+    fn description(&self) -> &str {
+        "Apply a unified diff to one or more files; nothing is written unless every hunk applies"
+    }
+
+    fn parameters_schema(&self) -> &super::schema::ParameterSchema {
+        static SCHEMA: std::sync::OnceLock<super::schema::ParameterSchema> = std::sync::OnceLock::new();
+        SCHEMA.get_or_init(|| {
+            let mut props = HashMap::new();
+            props.insert(
+                "patch".to_string(),
+                super::schema::ParameterSchema::string()
+                    .with_description("Unified diff with `---`/`+++` file headers and `@@` hunks")
+                    .with_min_length(1),
+            );
+            props.insert(
+                "path".to_string(),
+                super::schema::ParameterSchema::string()
+                    .with_description("File to patch when the diff has no file headers"),
+            );
+            props.insert(
+                "dry_run".to_string(),
+                super::schema::ParameterSchema::boolean()
+                    .with_description("Check that the patch applies without writing")
+                    .with_default(json!(false)),
+            );
+            super::schema::ParameterSchema::object(props)
+                .with_required(vec!["patch".to_string()])
+        })
+    }
+
+    async fn execute(&self, params: &Value, _context: &ToolContext) -> ToolResult {
+        run_blocking(self, params, Self::run).await
+    }
+
+    /// Patches run one at a time so they never interleave on the same files
+    fn max_concurrency(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+impl ApplyPatch {
+    fn run(&self, params: &Value) -> ToolResult {
+        let patch = params
+            .get("patch")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::ExecutionFailed("Missing 'patch' parameter".to_string()))?;
+        let path_override = params.get("path").and_then(|v| v.as_str());
+        let dry_run = params
+            .get("dry_run")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let file_patches = parse_patch(patch);
+        if file_patches.is_empty() {
+            return Err(ToolError::ExecutionFailed("Patch contains no hunks".to_string()));
+        }
+        if path_override.is_some() && file_patches.len() > 1 {
+            return Err(ToolError::ExecutionFailed(
+                "'path' can only be used with a single-file patch".to_string(),
+            ));
+        }
+
+        let mut changes = Vec::with_capacity(file_patches.len());
+        let mut touched = HashSet::new();
+        for file_patch in &file_patches {
+            // Renames read the old path and write the new one
+            let source_path = if file_patch.is_creation() {
+                None
+            } else {
+                path_override
+                    .map(str::to_string)
+                    .or_else(|| file_patch.old_path.clone())
+                    .or_else(|| file_patch.new_path.clone())
+            };
+            let target_path = if file_patch.is_deletion() {
+                None
+            } else {
+                path_override
+                    .map(str::to_string)
+                    .or_else(|| file_patch.new_path.clone())
+                    .or_else(|| file_patch.old_path.clone())
+            };
+            let path = target_path.clone().or_else(|| source_path.clone()).ok_or_else(|| {
+                ToolError::ExecutionFailed("Patch has no file headers; pass 'path'".to_string())
+            })?;
+
+            let source = source_path.as_deref().map(|p| self.sandbox.resolve(p)).transpose()?;
+            let target = target_path.as_deref().map(|p| self.sandbox.resolve(p)).transpose()?;
+
+            // Every section starts from the file on disk, so a second section
+            // for the same file would silently discard the first
+            for resolved in source.iter().chain(target.iter().filter(|t| Some(*t) != source.as_ref())) {
+                if !touched.insert(resolved.clone()) {
+                    return Err(ToolError::ExecutionFailed(format!(
+                        "{}: patch changes this file more than once",
+                        path
+                    )));
+                }
+            }
+
+            let original = match &source {
+                Some(source) => fs::read_to_string(source).map_err(|e| {
+                    ToolError::ExecutionFailed(format!(
+                        "{}: {}",
+                        source_path.as_deref().unwrap_or_default(),
+                        e
+                    ))
+                })?,
+                None => String::new(),
+            };
+            if let Some(target) = &target {
+                if Some(target) != source.as_ref() && target.exists() {
+                    return Err(ToolError::ExecutionFailed(format!("{}: file already exists", path)));
+                }
+            }
+
+            let content = apply_hunks(&original, &file_patch.hunks)
+                .map_err(|e| ToolError::ExecutionFailed(format!("{}: {}", path, e)))?;
+            if target.is_none() && !content.is_empty() {
+                return Err(ToolError::ExecutionFailed(format!(
+                    "{}: deletion patch does not remove the whole file",
+                    path
+                )));
+            }
+
+            let old_path = match (&source, &target) {
+                (Some(s), Some(t)) if s != t => source_path,
+                _ => None,
+            };
+            changes.push(PendingChange {
+                path,
+                old_path,
+                source,
+                target,
+                content,
+                original,
+            });
+        }
+
+        let mut files = Vec::with_capacity(changes.len());
+        for (change, file_patch) in changes.iter().zip(&file_patches) {
+            if !dry_run {
+                if let Some(target) = &change.target {
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent).map_err(|e| {
+                            ToolError::ExecutionFailed(format!("Failed to create directories: {}", e))
+                        })?;
+                    }
+                    fs::write(target, &change.content).map_err(|e| {
+                        ToolError::ExecutionFailed(format!("Failed to write {}: {}", change.path, e))
+                    })?;
+                }
+                if let Some(source) = &change.source {
+                    if change.target.as_ref() != Some(source) {
+                        fs::remove_file(source).map_err(|e| {
+                            ToolError::ExecutionFailed(format!("Failed to delete {}: {}", change.path, e))
+                        })?;
+                    }
+                }
+            }
+
+            let status = if file_patch.is_creation() {
+                "created"
+            } else if file_patch.is_deletion() {
+                "deleted"
+            } else if change.old_path.is_some() {
+                "renamed"
+            } else {
+                "modified"
+            };
+            let stats = diff_stats(&file_patch.hunks);
+            let mut file = json!({
+                "path": change.path,
+                "status": status,
+                "additions": stats.additions,
+                "deletions": stats.deletions,
+                "diff": compute_unified_diff(&change.original, &change.content),
+            });
+            if let Some(old_path) = &change.old_path {
+                file["old_path"] = json!(old_path);
+            }
+            files.push(file);
+        }
+
+        Ok(json!({
+            "files": files,
+            "dry_run": dry_run,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace() -> (TempDir, ApplyPatch) {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        fs::write(temp_dir.path().join("old.txt"), "gone\n").unwrap();
+        let tool = ApplyPatch::new(Sandbox::new([temp_dir.path()]).unwrap());
+        (temp_dir, tool)
+    }
+
+    #[test]
+    fn test_apply_patch_multiple_files() {
+        let (temp_dir, tool) = workspace();
+        let patch = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,3 @@
+ one
+-two
++TWO
+ three
+--- /dev/null
++++ b/new/b.txt
+@@ -0,0 +1,1 @@
++hello
+--- a/old.txt
++++ /dev/null
+@@ -1,1 +0,0 @@
+-gone
+";
+        let result = tool.run(&json!({"patch": patch})).unwrap();
+
+        let files = result["files"].as_array().unwrap();
+        assert_eq!(files[0]["status"], "modified");
+        assert_eq!(files[0]["additions"], 1);
+        assert_eq!(files[0]["deletions"], 1);
+        assert!(files[0]["diff"].as_str().unwrap().contains("+TWO"));
+        assert_eq!(files[1]["status"], "created");
+        assert_eq!(files[2]["status"], "deleted");
+
+        let root = temp_dir.path();
+        assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "one\nTWO\nthree\n");
+        assert_eq!(fs::read_to_string(root.join("new/b.txt")).unwrap(), "hello\n");
+        assert!(!root.join("old.txt").exists());
+    }
+
+    #[test]
+    fn test_apply_patch_headerless_and_dry_run() {
+        let (temp_dir, tool) = workspace();
+        let patch = "@@ -3,1 +3,2 @@\n three\n+four\n";
+
+        let result = tool
+            .run(&json!({"patch": patch, "path": "a.txt", "dry_run": true}))
+            .unwrap();
+        assert_eq!(result["dry_run"], true);
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("a.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+
+        tool.run(&json!({"patch": patch, "path": "a.txt"})).unwrap();
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("a.txt")).unwrap(),
+            "one\ntwo\nthree\nfour\n"
+        );
+
+        assert!(tool.run(&json!({"patch": patch})).is_err());
+    }
+
+    #[test]
+    fn test_apply_patch_is_atomic() {
+        let (temp_dir, tool) = workspace();
+        // The second file's hunk does not match, so the first is not written
+        let patch = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1,1 +1,1 @@
+-one
++ONE
+--- a/old.txt
++++ b/old.txt
+@@ -1,1 +1,1 @@
+-missing
++present
+";
+        let result = tool.run(&json!({"patch": patch}));
+        assert!(matches!(result, Err(ToolError::ExecutionFailed(msg)) if msg.starts_with("old.txt")));
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("a.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_patch_rename() {
+        let (temp_dir, tool) = workspace();
+        let patch = "\
+--- a/a.txt
++++ b/src/renamed.txt
+@@ -2,1 +2,1 @@
+-two
++TWO
+";
+        let result = tool.run(&json!({"patch": patch})).unwrap();
+        assert_eq!(result["files"][0]["status"], "renamed");
+        assert_eq!(result["files"][0]["path"], "src/renamed.txt");
+        assert_eq!(result["files"][0]["old_path"], "a.txt");
+
+        let root = temp_dir.path();
+        assert!(!root.join("a.txt").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("src/renamed.txt")).unwrap(),
+            "one\nTWO\nthree\n"
+        );
+
+        // A rename may not overwrite an existing file
+        let patch = "--- a/src/renamed.txt\n+++ b/old.txt\n@@ -1,1 +1,1 @@\n-one\n+1\n";
+        assert!(tool.run(&json!({"patch": patch})).is_err());
+    }
+
+    #[test]
+    fn test_apply_patch_rejects_duplicate_sections() {
+        let (temp_dir, tool) = workspace();
+        let patch = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1,1 +1,1 @@
+-one
++ONE
+--- a/a.txt
++++ b/a.txt
+@@ -3,1 +3,1 @@
+-three
++THREE
+";
+        let result = tool.run(&json!({"patch": patch}));
+        assert!(matches!(result, Err(ToolError::ExecutionFailed(msg)) if msg.contains("more than once")));
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("a.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_patch_sandboxed() {
+        let (_temp_dir, tool) = workspace();
+        let patch = "--- a/../escape.txt\n+++ b/../escape.txt\n@@ -0,0 +1,1 @@\n+x\n";
+        assert!(matches!(
+            tool.run(&json!({"patch": patch})),
+            Err(ToolError::PathViolation(_))
+        ));
+    }
+}
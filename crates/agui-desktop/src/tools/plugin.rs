@@ -0,0 +1,448 @@
+//! Plugin tools
+//!
+//! Loads tools from external executables in a plugin directory. Each
+//! executable is started once per request and speaks JSON-RPC 2.0 over
+//! stdin/stdout: the client writes one request line and closes stdin, and
+//! the plugin answers with one response line.
+//!
+//! - `describe` returns the tool's manifest: `name`, `description`,
+//!   `parameters` (a JSON Schema) and an optional `timeout_secs`
+//! - `execute` receives the validated parameters and returns the result
+//!
+//! Before responding, a plugin may send `output` notifications
+//! (`{"method": "output", "params": {"stream": "stdout", "chunk": "..."}}`);
+//! these and anything written to stderr are reported as tool output.
+
+use super::manifest::ManifestError;
+use super::schema::ParameterSchema;
+use super::shell::kill_process_group;
+use super::{Tool, ToolContext, ToolError, ToolMetadata, ToolRegistry, ToolResult};
+use async_trait::async_trait;
+use crate::protocol::OutputStream;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+
+/// Default timeout for a plugin call (30 seconds)
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Timeout for the `describe` call made while loading
+const DESCRIBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a plugin may keep running after its response
+const EXIT_GRACE: Duration = Duration::from_secs(1);
+
+/// Errors loading or calling a plugin
+#[derive(Debug, Error)]
+pub enum PluginError {
+    #[error("Failed to run plugin: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Plugin timed out")]
+    Timeout,
+
+    #[error("Plugin call cancelled")]
+    Cancelled,
+
+    #[error("Invalid plugin response: {0}")]
+    Protocol(String),
+
+    #[error("Plugin error {code}: {message}")]
+    Rpc { code: i64, message: String },
+
+    #[error("Invalid plugin manifest: {0}")]
+    Manifest(#[from] ManifestError),
+
+    #[error("Tool '{0}' is already registered")]
+    NameConflict(String),
+}
+
+impl From<PluginError> for ToolError {
+    fn from(error: PluginError) -> Self {
+        match error {
+            PluginError::Timeout => ToolError::Timeout,
+            PluginError::Cancelled => ToolError::Cancelled { partial: Value::Null },
+            PluginError::Rpc { message, .. } => ToolError::ExecutionFailed(message),
+            other => ToolError::ExecutionFailed(other.to_string()),
+        }
+    }
+}
+
+/// A tool backed by an external executable
+#[derive(Debug, Clone)]
+pub struct PluginTool {
+    path: PathBuf,
+    metadata: ToolMetadata,
+    /// None disables the timeout
+    timeout: Option<Duration>,
+}
+
+impl PluginTool {
+    /// Load a plugin by asking the executable to describe itself
+    pub async fn load(path: impl Into<PathBuf>) -> Result<Self, PluginError> {
+        let path = path.into();
+        let manifest = call(
+            &path,
+            "describe",
+            Value::Null,
+            Some(DESCRIBE_TIMEOUT),
+            ToolContext::new(),
+        )
+        .await?;
+
+        let metadata = ToolMetadata::import(&manifest)?;
+        let timeout_secs = manifest
+            .get("timeout_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+
+        Ok(Self {
+            path,
+            metadata,
+            timeout: (timeout_secs > 0).then(|| Duration::from_secs(timeout_secs)),
+        })
+    }
+
+    /// Path to the plugin executable
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl Tool for PluginTool {
+    fn name(&self) -> &str {
+        &self.metadata.name
+    }
+
+    fn description(&self) -> &str {
+        &self.metadata.description
+    }
+
+    fn parameters_schema(&self) -> &ParameterSchema {
+        &self.metadata.parameters_schema
+    }
+
+    async fn execute(&self, params: &Value, context: &ToolContext) -> ToolResult {
+        Ok(call(&self.path, "execute", params.clone(), self.timeout, context.clone()).await?)
+    }
+}
+
+/// Load every executable in a plugin directory
+///
+/// Files are loaded in name order; hidden files and non-executables are
+/// skipped. A missing directory has no plugins.
+pub async fn discover_plugins(dir: &Path) -> Vec<(PathBuf, Result<PluginTool, PluginError>)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let hidden = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with('.'));
+            !hidden && is_executable(path)
+        })
+        .collect();
+    paths.sort();
+
+    let mut plugins = Vec::with_capacity(paths.len());
+    for path in paths {
+        let plugin = PluginTool::load(&path).await;
+        plugins.push((path, plugin));
+    }
+    plugins
+}
+
+/// Register the plugins in a directory, returning the ones that failed
+///
+/// Plugins never replace an already registered tool.
+pub async fn register_plugins(
+    registry: &mut ToolRegistry,
+    dir: &Path,
+) -> Vec<(PathBuf, PluginError)> {
+    let mut failures = Vec::new();
+    for (path, plugin) in discover_plugins(dir).await {
+        match plugin {
+            Ok(plugin) if registry.get(plugin.name()).is_some() => {
+                failures.push((path, PluginError::NameConflict(plugin.name().to_string())));
+            }
+            Ok(plugin) => registry.register(Box::new(plugin)),
+            Err(e) => failures.push((path, e)),
+        }
+    }
+    failures
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Make one JSON-RPC call to a plugin process
+async fn call(
+    path: &Path,
+    method: &str,
+    params: Value,
+    timeout: Option<Duration>,
+    context: ToolContext,
+) -> Result<Value, PluginError> {
+    let mut cmd = Command::new(path);
+    cmd.stdin(std::process::Stdio::piped());
+    cmd.stdout(std::process::Stdio::piped());
+    cmd.stderr(std::process::Stdio::piped());
+    cmd.kill_on_drop(true);
+
+    // Run in a new process group so a timeout can kill its children too
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let mut child = cmd.spawn()?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| PluginError::Protocol("stdin not captured".to_string()))?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| PluginError::Protocol("stdout not captured".to_string()))?;
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| PluginError::Protocol("stderr not captured".to_string()))?;
+
+    // Stderr is diagnostic output, reported as it arrives
+    let stderr_context = context.clone();
+    let stderr_task = tokio::spawn(async move {
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            stderr_context.emit(OutputStream::Stderr, &format!("{}\n", line));
+        }
+    });
+
+    let mut request = json!({"jsonrpc": "2.0", "id": 1, "method": method});
+    if !params.is_null() {
+        request["params"] = params;
+    }
+    // A plugin that exits without reading is reported by the missing response
+    match stdin.write_all(format!("{}\n", request).as_bytes()).await {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e.into()),
+        _ => {}
+    }
+    // Closing stdin lets plugins read the request to end of input
+    drop(stdin);
+
+    let timeout = async {
+        match timeout {
+            Some(timeout) => tokio::time::sleep(timeout).await,
+            None => std::future::pending().await,
+        }
+    };
+    let response = tokio::select! {
+        response = read_response(BufReader::new(stdout), &context) => response,
+        _ = timeout => Err(PluginError::Timeout),
+        _ = context.cancelled() => Err(PluginError::Cancelled),
+    };
+
+    // Give the plugin a moment to exit after responding, then kill what is left
+    if response.is_err()
+        || tokio::time::timeout(EXIT_GRACE, child.wait()).await.is_err()
+    {
+        kill_process_group(&mut child);
+        let _ = child.wait().await;
+    }
+    let _ = tokio::time::timeout(EXIT_GRACE, stderr_task).await;
+    response
+}
+
+/// Read stdout until the response, forwarding output notifications
+async fn read_response<R: tokio::io::AsyncRead + Unpin>(
+    reader: BufReader<R>,
+    context: &ToolContext,
+) -> Result<Value, PluginError> {
+    let mut lines = reader.lines();
+    while let Some(line) = lines.next_line().await? {
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            // Stray prints are passed through as output
+            context.emit(OutputStream::Stdout, &format!("{}\n", line));
+            continue;
+        };
+
+        if message.get("id").is_none() {
+            if message["method"] == "output" {
+                let stream = match message["params"]["stream"].as_str() {
+                    Some("stderr") => OutputStream::Stderr,
+                    _ => OutputStream::Stdout,
+                };
+                let chunk = message["params"]["chunk"].as_str().unwrap_or_default();
+                context.emit(stream, chunk);
+            }
+            continue;
+        }
+
+        if let Some(error) = message.get("error") {
+            return Err(PluginError::Rpc {
+                code: error["code"].as_i64().unwrap_or(-32603),
+                message: error["message"].as_str().unwrap_or("unknown error").to_string(),
+            });
+        }
+        return message
+            .get("result")
+            .cloned()
+            .ok_or_else(|| PluginError::Protocol("response has no result".to_string()));
+    }
+
+    Err(PluginError::Protocol("plugin exited without a response".to_string()))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::tools::create_standard_registry;
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    /// Echo plugin: returns the request it received
+    const ECHO_PLUGIN: &str = r#"#!/bin/sh
+read -r line
+case "$line" in
+  *'"describe"'*)
+    echo '{"jsonrpc":"2.0","id":1,"result":{"name":"echo","description":"Echo parameters","parameters":{"type":"object","properties":{"text":{"type":"string"},"loud":{"type":"boolean","default":false}},"required":["text"]}}}'
+    ;;
+  *)
+    echo "working" >&2
+    echo '{"jsonrpc":"2.0","method":"output","params":{"stream":"stdout","chunk":"half way"}}'
+    printf '{"jsonrpc":"2.0","id":1,"result":{"request":%s}}\n' "$line"
+    ;;
+esac
+"#;
+
+    fn write_plugin(dir: &Path, name: &str, script: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    /// A plugin that describes itself and answers `execute` with `body`
+    fn scripted_plugin(name: &str, extra_manifest: &str, body: &str) -> String {
+        format!(
+            r#"#!/bin/sh
+read -r line
+case "$line" in
+  *'"describe"'*)
+    echo '{{"jsonrpc":"2.0","id":1,"result":{{"name":"{}"{}}}}}'
+    ;;
+  *)
+    {}
+    ;;
+esac
+"#,
+            name, extra_manifest, body
+        )
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_plugin_execute() {
+        let temp_dir = TempDir::new().unwrap();
+        write_plugin(temp_dir.path(), "echo", ECHO_PLUGIN);
+
+        let mut registry = ToolRegistry::new();
+        assert!(register_plugins(&mut registry, temp_dir.path()).await.is_empty());
+        assert_eq!(registry.list_tools(), vec!["echo"]);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let sink = output.clone();
+        let context = ToolContext::new()
+            .with_output(move |stream, chunk| sink.lock().unwrap().push((stream, chunk.to_string())));
+        let result = registry
+            .execute_with_context("echo", &json!({"text": "hi"}), &context)
+            .await
+            .unwrap();
+
+        // Defaults are applied before the plugin sees the parameters
+        assert_eq!(result["request"]["method"], "execute");
+        assert_eq!(result["request"]["params"], json!({"text": "hi", "loud": false}));
+
+        let output = output.lock().unwrap().clone();
+        assert!(output.contains(&(OutputStream::Stdout, "half way".to_string())));
+        assert!(output.contains(&(OutputStream::Stderr, "working\n".to_string())));
+
+        // The plugin's schema is enforced locally
+        assert!(matches!(
+            registry.execute("echo", &json!({"text": 1})).await,
+            Err(ToolError::ValidationError(_))
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_plugin_errors_and_timeout() {
+        let temp_dir = TempDir::new().unwrap();
+        let failing = write_plugin(
+            temp_dir.path(),
+            "failing",
+            &scripted_plugin(
+                "failing",
+                "",
+                r#"echo '{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"disk full"}}'"#,
+            ),
+        );
+        let slow = write_plugin(
+            temp_dir.path(),
+            "slow",
+            &scripted_plugin("slow", r#","timeout_secs":1"#, "sleep 10"),
+        );
+
+        let failing = PluginTool::load(failing).await.unwrap();
+        assert!(matches!(
+            failing.execute(&json!({}), &ToolContext::new()).await,
+            Err(ToolError::ExecutionFailed(msg)) if msg == "disk full"
+        ));
+
+        let slow = PluginTool::load(slow).await.unwrap();
+        assert!(matches!(
+            slow.execute(&json!({}), &ToolContext::new()).await,
+            Err(ToolError::Timeout)
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_discover_plugins() {
+        let temp_dir = TempDir::new().unwrap();
+        write_plugin(temp_dir.path(), "echo", ECHO_PLUGIN);
+        write_plugin(temp_dir.path(), "read_file", &scripted_plugin("read_file", "", "true"));
+        write_plugin(temp_dir.path(), "silent", "#!/bin/sh\nexit 0\n");
+        write_plugin(temp_dir.path(), ".hidden", ECHO_PLUGIN);
+        fs::write(temp_dir.path().join("README.md"), "not a plugin").unwrap();
+
+        let mut registry = create_standard_registry();
+        let failures = register_plugins(&mut registry, temp_dir.path()).await;
+        let failures: Vec<_> = failures
+            .iter()
+            .map(|(path, e)| (path.file_name().unwrap().to_str().unwrap(), e))
+            .collect();
+
+        assert_eq!(failures.len(), 2);
+        assert!(matches!(failures[0], ("read_file", PluginError::NameConflict(_))));
+        assert!(matches!(failures[1], ("silent", PluginError::Protocol(_))));
+        assert!(registry.get("echo").is_some());
+        // The built-in tool is kept
+        assert!(registry.get("read_file").unwrap().description().starts_with("Read the contents"));
+
+        assert!(discover_plugins(&temp_dir.path().join("missing")).await.is_empty());
+    }
+}
@@ -0,0 +1,355 @@
+//! Tool execution policy
+//!
+//! Decides whether a tool call may run unattended, needs human approval,
+//! or is refused. Rules are keyed by tool name and argument patterns and
+//! persisted as JSON so remembered decisions survive restarts.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Tools that only read from the filesystem
+const READ_ONLY_TOOLS: &[&str] = &["read_file", "list_directory", "file_exists"];
+
+/// What to do with a tool call
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyLevel {
+    /// Run without asking
+    Allow,
+    /// Ask the user before running
+    Ask,
+    /// Never run
+    Deny,
+}
+
+/// A policy rule matching tool calls by name and arguments
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PolicyRule {
+    /// Tool name pattern (`*` matches any sequence of characters)
+    pub tool: String,
+    /// Argument patterns; every listed argument must match
+    ///
+    /// Non-string arguments are matched against their JSON text.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub args: HashMap<String, String>,
+    /// Level applied when the rule matches
+    pub level: PolicyLevel,
+}
+
+impl PolicyRule {
+    /// Create a rule for a tool name pattern
+    pub fn new(tool: impl Into<String>, level: PolicyLevel) -> Self {
+        Self {
+            tool: tool.into(),
+            args: HashMap::new(),
+            level,
+        }
+    }
+
+    /// Require an argument to match a pattern
+    pub fn with_arg(mut self, name: impl Into<String>, pattern: impl Into<String>) -> Self {
+        self.args.insert(name.into(), pattern.into());
+        self
+    }
+
+    /// Create a rule matching one call's tool and exact top-level arguments
+    ///
+    /// Returns None if an argument contains `*`, which patterns cannot match
+    /// literally, so the rule would cover calls the user never saw.
+    pub fn for_call(tool_name: &str, params: &Value, level: PolicyLevel) -> Option<Self> {
+        let mut rule = Self::new(tool_name, level);
+        if let Some(params) = params.as_object() {
+            for (name, value) in params {
+                let text = match value {
+                    Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                rule.args.insert(name.clone(), text);
+            }
+        }
+        let literal = |pattern: &str| !pattern.contains('*');
+        (literal(&rule.tool) && rule.args.values().all(|pattern| literal(pattern))).then_some(rule)
+    }
+
+    /// Check whether this rule applies to every call `other` applies to
+    ///
+    /// Only decided for rules whose patterns are all literal, like those
+    /// from `for_call`; returns false otherwise.
+    fn covers(&self, other: &PolicyRule) -> bool {
+        !other.tool.contains('*')
+            && wildcard_match(&self.tool, &other.tool)
+            && self.args.iter().all(|(name, pattern)| {
+                other
+                    .args
+                    .get(name)
+                    .is_some_and(|value| !value.contains('*') && wildcard_match(pattern, value))
+            })
+    }
+
+    /// Check whether this rule applies to a tool call
+    pub fn matches(&self, tool_name: &str, params: &Value) -> bool {
+        if !wildcard_match(&self.tool, tool_name) {
+            return false;
+        }
+
+        self.args.iter().all(|(name, pattern)| match params.get(name) {
+            Some(Value::String(value)) => wildcard_match(pattern, value),
+            Some(value) => wildcard_match(pattern, &value.to_string()),
+            None => false,
+        })
+    }
+}
+
+/// Ordered set of policy rules
+///
+/// The first matching rule wins; calls matching no rule use `default`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolPolicy {
+    /// Level for calls that match no rule
+    pub default: PolicyLevel,
+    /// Rules in priority order
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+    /// File the policy is persisted to
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl ToolPolicy {
+    /// Create an empty policy with a default level
+    pub fn new(default: PolicyLevel) -> Self {
+        Self {
+            default,
+            rules: Vec::new(),
+            path: None,
+        }
+    }
+
+    /// Policy allowing read-only tools and asking for everything else
+    pub fn standard() -> Self {
+        READ_ONLY_TOOLS.iter().fold(Self::new(PolicyLevel::Ask), |policy, tool| {
+            policy.with_rule(PolicyRule::new(*tool, PolicyLevel::Allow))
+        })
+    }
+
+    /// Load a policy file, falling back to `standard()` if it does not exist
+    ///
+    /// Remembered decisions are saved back to the same path.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PolicyError> {
+        let path = path.as_ref();
+        let mut policy = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::standard(),
+            Err(e) => return Err(e.into()),
+        };
+        policy.path = Some(path.to_path_buf());
+        Ok(policy)
+    }
+
+    /// Add a rule after the existing ones
+    pub fn with_rule(mut self, rule: PolicyRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Get the file this policy is persisted to
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Decide what to do with a tool call
+    pub fn evaluate(&self, tool_name: &str, params: &Value) -> PolicyLevel {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(tool_name, params))
+            .map(|rule| rule.level)
+            .unwrap_or(self.default)
+    }
+
+    /// Record a user decision and persist it
+    ///
+    /// A Deny goes ahead of every rule. An Allow goes just ahead of the first
+    /// rule that already decides every call it matches, so it never overrides
+    /// a more specific rule, such as a Deny for particular arguments.
+    pub fn remember(&mut self, rule: PolicyRule) -> Result<(), PolicyError> {
+        self.rules.retain(|existing| existing.tool != rule.tool || existing.args != rule.args);
+        let index = match rule.level {
+            PolicyLevel::Deny => 0,
+            _ => self
+                .rules
+                .iter()
+                .position(|existing| existing.covers(&rule))
+                .unwrap_or(self.rules.len()),
+        };
+        self.rules.insert(index, rule);
+        self.save()
+    }
+
+    /// Write the policy to its file, if it has one
+    pub fn save(&self) -> Result<(), PolicyError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+impl Default for ToolPolicy {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Errors loading or saving a policy file
+#[derive(Debug, Error)]
+pub enum PolicyError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid policy file: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Match a value against a pattern where `*` matches any sequence
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard: exact match
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("git", "git"));
+        assert!(!wildcard_match("git", "gitk"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("/tmp/*", "/tmp/a/b.txt"));
+        assert!(!wildcard_match("/tmp/*", "/etc/passwd"));
+        assert!(wildcard_match("*.rs", "src/main.rs"));
+        assert!(wildcard_match("a*b*c", "axxbyyc"));
+        assert!(!wildcard_match("a*b*c", "axxcyyb"));
+        assert!(!wildcard_match("ab*ab", "ab"));
+    }
+
+    #[test]
+    fn test_standard_policy() {
+        let policy = ToolPolicy::standard();
+        assert_eq!(policy.evaluate("read_file", &json!({"path": "/x"})), PolicyLevel::Allow);
+        assert_eq!(policy.evaluate("write_file", &json!({})), PolicyLevel::Ask);
+        assert_eq!(policy.evaluate("run_command", &json!({})), PolicyLevel::Ask);
+    }
+
+    #[test]
+    fn test_argument_rules() {
+        let policy = ToolPolicy::new(PolicyLevel::Deny)
+            .with_rule(PolicyRule::new("run_command", PolicyLevel::Allow).with_arg("command", "git"))
+            .with_rule(PolicyRule::new("write_file", PolicyLevel::Ask).with_arg("path", "/work/*"))
+            .with_rule(PolicyRule::new("run_command", PolicyLevel::Allow).with_arg("args", "[]"));
+
+        assert_eq!(
+            policy.evaluate("run_command", &json!({"command": "git", "args": ["status"]})),
+            PolicyLevel::Allow
+        );
+        assert_eq!(
+            policy.evaluate("run_command", &json!({"command": "rm", "args": ["-rf"]})),
+            PolicyLevel::Deny
+        );
+        // Non-string arguments match against their JSON text
+        assert_eq!(
+            policy.evaluate("run_command", &json!({"command": "ls", "args": []})),
+            PolicyLevel::Allow
+        );
+        assert_eq!(
+            policy.evaluate("write_file", &json!({"path": "/work/a.txt"})),
+            PolicyLevel::Ask
+        );
+        assert_eq!(policy.evaluate("write_file", &json!({})), PolicyLevel::Deny);
+    }
+
+    #[test]
+    fn test_remember_persists() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("agui/tool-policy.json");
+
+        let mut policy = ToolPolicy::load(&path).unwrap();
+        assert_eq!(policy.rules, ToolPolicy::standard().rules);
+        assert_eq!(policy.path(), Some(path.as_path()));
+        policy
+            .remember(PolicyRule::new("run_command", PolicyLevel::Deny))
+            .unwrap();
+        policy
+            .remember(PolicyRule::new("run_command", PolicyLevel::Allow))
+            .unwrap();
+
+        let reloaded = ToolPolicy::load(&path).unwrap();
+        assert_eq!(reloaded.evaluate("run_command", &json!({})), PolicyLevel::Allow);
+        assert_eq!(
+            reloaded.rules.iter().filter(|r| r.tool == "run_command").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_remembered_allow_keeps_specific_deny() {
+        let mut policy = ToolPolicy::new(PolicyLevel::Ask)
+            .with_rule(PolicyRule::new("run_command", PolicyLevel::Deny).with_arg("command", "rm"))
+            .with_rule(PolicyRule::new("run_command", PolicyLevel::Ask));
+
+        let approved = json!({"command": "ls", "args": ["-la"]});
+        let rule = PolicyRule::for_call("run_command", &approved, PolicyLevel::Allow).unwrap();
+        assert_eq!(rule.args["command"], "ls");
+        assert_eq!(rule.args["args"], r#"["-la"]"#);
+        policy.remember(rule).unwrap();
+
+        assert_eq!(policy.evaluate("run_command", &approved), PolicyLevel::Allow);
+        assert_eq!(
+            policy.evaluate("run_command", &json!({"command": "rm", "args": ["-la"]})),
+            PolicyLevel::Deny
+        );
+        assert_eq!(
+            policy.evaluate("run_command", &json!({"command": "ls", "args": ["/"]})),
+            PolicyLevel::Ask
+        );
+        assert_eq!(policy.rules[1].level, PolicyLevel::Allow);
+
+        // Arguments with `*` cannot be remembered literally
+        let wildcard = json!({"command": "ls *"});
+        assert!(PolicyRule::for_call("run_command", &wildcard, PolicyLevel::Allow).is_none());
+    }
+
+    #[test]
+    fn test_load_invalid_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("tool-policy.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        assert!(matches!(ToolPolicy::load(&path), Err(PolicyError::Parse(_))));
+    }
+}
@@ -0,0 +1,269 @@
+//! Filesystem sandbox for file tools
+//!
+//! Confines file tool paths to a set of workspace roots. Paths are
+//! canonicalized before checking, so `..` traversal and symlinks pointing
+//! outside the roots are rejected. Protected paths, such as the tool policy
+//! file, are rejected even when they lie inside a root.
+
+use super::ToolError;
+use std::path::{Component, Path, PathBuf};
+
+/// Set of directories file tools may access
+///
+/// An unrestricted sandbox (no roots) allows any path outside the protected ones.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sandbox {
+    roots: Vec<PathBuf>,
+    protected: Vec<PathBuf>,
+}
+
+impl Sandbox {
+    /// Create a sandbox that allows any path
+    pub fn unrestricted() -> Self {
+        Self::default()
+    }
+
+    /// Create a sandbox confined to the given roots
+    ///
+    /// Roots must exist; they are canonicalized so later checks compare
+    /// real paths.
+    pub fn new<P: AsRef<Path>>(roots: impl IntoIterator<Item = P>) -> std::io::Result<Self> {
+        let roots = roots
+            .into_iter()
+            .map(|root| root.as_ref().canonicalize())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            roots,
+            protected: Vec::new(),
+        })
+    }
+
+    /// Deny access to the given files or directories, even inside a root
+    ///
+    /// Paths need not exist yet.
+    pub fn with_protected<P: AsRef<Path>>(mut self, paths: impl IntoIterator<Item = P>) -> Self {
+        self.protected.extend(paths.into_iter().map(|path| {
+            let path = path.as_ref();
+            canonicalize_partial(path).unwrap_or_else(|| path.to_path_buf())
+        }));
+        self
+    }
+
+    /// Get the canonical workspace roots
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Check whether any path is allowed
+    pub fn is_unrestricted(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Resolve a tool path argument to a real path inside the roots
+    ///
+    /// Relative paths are resolved against the first root, or the current
+    /// directory without roots. The path does not need to exist, but its
+    /// nearest existing ancestor must lie inside a root and the remainder
+    /// may not contain `..`. Without roots or protected paths the argument
+    /// is returned as is.
+    pub fn resolve(&self, path: &str) -> Result<PathBuf, ToolError> {
+        if self.allows_all() {
+            return Ok(PathBuf::from(path));
+        }
+
+        let requested = Path::new(path);
+        let absolute = if requested.is_absolute() {
+            requested.to_path_buf()
+        } else if let Some(root) = self.roots.first() {
+            root.join(requested)
+        } else {
+            std::env::current_dir()
+                .map_err(|_| ToolError::PathViolation(path.to_string()))?
+                .join(requested)
+        };
+
+        let resolved = canonicalize_partial(&absolute)
+            .ok_or_else(|| ToolError::PathViolation(path.to_string()))?;
+        if self.contains(&resolved) {
+            Ok(resolved)
+        } else {
+            Err(ToolError::PathViolation(path.to_string()))
+        }
+    }
+
+    /// Check whether a canonical path lies inside a root and outside protected paths
+    fn contains(&self, path: &Path) -> bool {
+        !self.protected.iter().any(|protected| path.starts_with(protected))
+            && (self.is_unrestricted() || self.roots.iter().any(|root| path.starts_with(root)))
+    }
+
+    /// Check whether nothing is confined, so paths need no checks
+    fn allows_all(&self) -> bool {
+        self.is_unrestricted() && self.protected.is_empty()
+    }
+
+    /// Check whether an existing path (following symlinks) lies inside a root
+    pub(crate) fn allows_existing(&self, path: &Path) -> bool {
+        self.allows_all()
+            || path
+                .canonicalize()
+                .map(|real| self.contains(&real))
+                .unwrap_or(false)
+    }
+}
+
+/// Canonicalize a path whose trailing components may not exist yet
+///
+/// Returns None if the non-existent remainder contains anything other than
+/// plain file names, or if a component exists but cannot be resolved, such
+/// as a dangling symlink that writes would follow out of the roots.
+fn canonicalize_partial(path: &Path) -> Option<PathBuf> {
+    let mut existing = path;
+    let mut missing = Vec::new();
+
+    loop {
+        match existing.canonicalize() {
+            Ok(real) => {
+                let mut resolved = real;
+                for component in missing.iter().rev() {
+                    resolved.push(component);
+                }
+                return Some(resolved);
+            }
+            Err(_) => {
+                if existing.symlink_metadata().is_ok() {
+                    return None;
+                }
+                match existing.components().next_back()? {
+                    Component::Normal(name) => missing.push(name.to_os_string()),
+                    _ => return None,
+                }
+                existing = existing.parent()?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace() -> (TempDir, Sandbox) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("project");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::fs::write(temp_dir.path().join("secret.txt"), "").unwrap();
+        let sandbox = Sandbox::new([&root]).unwrap();
+        (temp_dir, sandbox)
+    }
+
+    #[test]
+    fn test_resolve_inside_roots() {
+        let (_temp_dir, sandbox) = workspace();
+        let root = sandbox.roots()[0].clone();
+
+        assert_eq!(sandbox.resolve("src/lib.rs").unwrap(), root.join("src/lib.rs"));
+        assert_eq!(
+            sandbox.resolve(&root.join("src/new/mod.rs").to_string_lossy()).unwrap(),
+            root.join("src/new/mod.rs")
+        );
+        // Traversal that stays inside the root is fine
+        assert_eq!(sandbox.resolve("src/../src/lib.rs").unwrap(), root.join("src/lib.rs"));
+    }
+
+    #[test]
+    fn test_rejects_traversal() {
+        let (_temp_dir, sandbox) = workspace();
+
+        assert!(matches!(
+            sandbox.resolve("../secret.txt"),
+            Err(ToolError::PathViolation(_))
+        ));
+        assert!(matches!(
+            sandbox.resolve("missing/../../secret.txt"),
+            Err(ToolError::PathViolation(_))
+        ));
+        assert!(matches!(sandbox.resolve("/etc/passwd"), Err(ToolError::PathViolation(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_symlink_escape() {
+        let (temp_dir, sandbox) = workspace();
+        let root = sandbox.roots()[0].clone();
+        std::os::unix::fs::symlink(temp_dir.path(), root.join("escape")).unwrap();
+
+        assert!(matches!(
+            sandbox.resolve("escape/secret.txt"),
+            Err(ToolError::PathViolation(_))
+        ));
+        assert!(matches!(
+            sandbox.resolve("escape/new.txt"),
+            Err(ToolError::PathViolation(_))
+        ));
+        assert!(!sandbox.allows_existing(&root.join("escape")));
+        assert!(sandbox.allows_existing(&root.join("src")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_dangling_symlink_escape() {
+        let (temp_dir, sandbox) = workspace();
+        let root = sandbox.roots()[0].clone();
+        let outside = temp_dir.path().join("outside");
+        std::os::unix::fs::symlink(outside.join("new.txt"), root.join("link")).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("dir_link")).unwrap();
+
+        assert!(matches!(sandbox.resolve("link"), Err(ToolError::PathViolation(_))));
+        assert!(matches!(
+            sandbox.resolve("dir_link/new.txt"),
+            Err(ToolError::PathViolation(_))
+        ));
+        assert!(!outside.exists());
+    }
+
+    #[test]
+    fn test_rejects_protected_paths() {
+        let (_temp_dir, sandbox) = workspace();
+        let root = sandbox.roots()[0].clone();
+        let sandbox = sandbox.with_protected([root.join(".agui")]);
+
+        assert!(matches!(
+            sandbox.resolve(".agui/tool-policy.json"),
+            Err(ToolError::PathViolation(_))
+        ));
+        assert!(matches!(
+            sandbox.resolve("src/../.agui/plugins/evil"),
+            Err(ToolError::PathViolation(_))
+        ));
+        assert!(sandbox.resolve(".aguirc").is_ok());
+        assert!(sandbox.resolve("src/lib.rs").is_ok());
+    }
+
+    #[test]
+    fn test_unrestricted_with_protected_paths() {
+        let (temp_dir, _sandbox) = workspace();
+        let protected = temp_dir.path().join("agui");
+        let sandbox = Sandbox::unrestricted().with_protected([&protected]);
+
+        assert!(matches!(
+            sandbox.resolve(&protected.join("tool-policy.json").to_string_lossy()),
+            Err(ToolError::PathViolation(_))
+        ));
+        assert!(matches!(
+            sandbox.resolve(&temp_dir.path().join("project/../agui/x").to_string_lossy()),
+            Err(ToolError::PathViolation(_))
+        ));
+        assert!(sandbox.resolve(&temp_dir.path().join("secret.txt").to_string_lossy()).is_ok());
+        assert!(sandbox.allows_existing(&temp_dir.path().join("secret.txt")));
+    }
+
+    #[test]
+    fn test_unrestricted() {
+        let sandbox = Sandbox::unrestricted();
+        assert_eq!(sandbox.resolve("../x").unwrap(), PathBuf::from("../x"));
+        assert!(Sandbox::new(["/definitely/not/here"]).is_err());
+    }
+}
@@ -1,62 +1,102 @@
 //! Tool parameter schema definition and validation
 //!
-//! Provides JSON Schema support for tool parameter validation.
+//! Provides a JSON Schema subset for tool parameter validation. Schemas
+//! serialize as standard JSON Schema (`items`, `enum`, `minLength`,
+//! `$ref`, `oneOf`, ...) so tool definitions can be sent to model APIs
+//! as-is, and unrecognized keywords are kept so imported schemas
+//! round-trip.
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use std::collections::HashMap;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
 use thiserror::Error;
 
+/// Maximum `$ref` indirections followed while validating one value
+const MAX_REF_DEPTH: usize = 32;
+
 /// JSON Schema for tool parameters
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "Value", into = "Value")]
 pub struct ParameterSchema {
-    /// Schema type (object, string, number, etc.)
-    #[serde(rename = "type")]
-    pub schema_type: SchemaType,
+    /// Schema type; None for schemas made only of combinators or a `$ref`
+    pub schema_type: Option<SchemaType>,
+
+    /// Whether null is accepted as well (`"type": [T, "null"]`)
+    pub nullable: bool,
 
     /// Human-readable description
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 
     /// Required properties (for object type)
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<Vec<String>>,
 
     /// Property definitions (for object type)
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub properties: Option<HashMap<String, ParameterSchema>>,
 
+    /// Rule for properties not listed in `properties` (for object type)
+    pub additional_properties: Option<AdditionalProperties>,
+
     /// Array item schema
-    #[serde(rename = "item", skip_serializing_if = "Option::is_none")]
-    pub item: Option<Box<ParameterSchema>>,
+    pub items: Option<Box<ParameterSchema>>,
 
     /// Enum values
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub enum_values: Option<Vec<Value>>,
 
+    /// Single allowed value (`const`)
+    pub const_value: Option<Value>,
+
     /// Minimum value (for numbers)
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub minimum: Option<f64>,
 
     /// Maximum value (for numbers)
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub maximum: Option<f64>,
 
-    /// Minimum length (for strings/arrays)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Minimum length (for strings)
     pub min_length: Option<usize>,
 
-    /// Maximum length (for strings/arrays)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Maximum length (for strings)
     pub max_length: Option<usize>,
 
+    /// Minimum number of items (for arrays)
+    pub min_items: Option<usize>,
+
+    /// Maximum number of items (for arrays)
+    pub max_items: Option<usize>,
+
+    /// Regular expression strings must match (unanchored)
+    pub pattern: Option<Pattern>,
+
+    /// String format such as `date-time`, `email`, `uri` or `uuid`
+    pub format: Option<String>,
+
     /// Default value
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<Value>,
+
+    /// Value must match exactly one of these schemas
+    pub one_of: Option<Vec<ParameterSchema>>,
+
+    /// Value must match at least one of these schemas
+    pub any_of: Option<Vec<ParameterSchema>>,
+
+    /// Value must match all of these schemas
+    pub all_of: Option<Vec<ParameterSchema>>,
+
+    /// Reference to another schema (`$ref`), e.g. `#/$defs/Point`
+    pub reference: Option<String>,
+
+    /// Named schemas for `$ref` (`$defs`)
+    pub defs: Option<HashMap<String, ParameterSchema>>,
+
+    /// Named schemas for `$ref` using the older `definitions` keyword
+    pub definitions: Option<HashMap<String, ParameterSchema>>,
+
+    /// Keywords outside the supported subset, kept for export but not validated
+    pub extra: BTreeMap<String, Value>,
 }
 
 /// Schema types
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum SchemaType {
     String,
@@ -65,108 +105,187 @@ pub enum SchemaType {
     Boolean,
     Array,
     Object,
+    Null,
+}
+
+impl SchemaType {
+    /// JSON Schema name of the type
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Integer => "integer",
+            Self::Boolean => "boolean",
+            Self::Array => "array",
+            Self::Object => "object",
+            Self::Null => "null",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "string" => Self::String,
+            "number" => Self::Number,
+            "integer" => Self::Integer,
+            "boolean" => Self::Boolean,
+            "array" => Self::Array,
+            "object" => Self::Object,
+            "null" => Self::Null,
+            _ => return None,
+        })
+    }
+
+    /// Check whether a value has this type
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Number => value.is_number(),
+            Self::Integer => value.is_i64() || value.is_u64(),
+            Self::Boolean => value.is_boolean(),
+            Self::Array => value.is_array(),
+            Self::Object => value.is_object(),
+            Self::Null => value.is_null(),
+        }
+    }
+}
+
+/// Compiled `pattern` regular expression
+///
+/// Compiled once when the schema is built; compares by its source text.
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    /// Compile a pattern, rejecting syntax the regex engine does not support
+    pub fn new(source: &str) -> Result<Self, SchemaError> {
+        Regex::new(source)
+            .map(Self)
+            .map_err(|e| SchemaError::invalid("pattern", &e.to_string()))
+    }
+
+    /// Get the pattern source
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Check whether a string matches anywhere
+    pub fn is_match(&self, s: &str) -> bool {
+        self.0.is_match(s)
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+/// Rule for object properties not listed in `properties`
+#[derive(Debug, Clone, PartialEq)]
+pub enum AdditionalProperties {
+    /// `true` allows any value, `false` rejects unlisted properties
+    Allowed(bool),
+    /// Unlisted properties must match this schema
+    Schema(Box<ParameterSchema>),
+}
+
+/// Error converting JSON into a `ParameterSchema`
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    #[error("Invalid schema at '{keyword}': {reason}")]
+    Invalid { keyword: String, reason: String },
+
+    #[error("Unsupported schema: {0}")]
+    Unsupported(String),
+}
+
+impl SchemaError {
+    fn invalid(keyword: &str, reason: &str) -> Self {
+        Self::Invalid {
+            keyword: keyword.to_string(),
+            reason: reason.to_string(),
+        }
+    }
 }
 
 impl ParameterSchema {
-    /// Create a new string schema
-    pub fn string() -> Self {
+    fn typed(schema_type: SchemaType) -> Self {
         Self {
-            schema_type: SchemaType::String,
-            description: None,
-            required: None,
-            properties: None,
-            item: None,
-            enum_values: None,
-            minimum: None,
-            maximum: None,
-            min_length: None,
-            max_length: None,
-            default: None,
+            schema_type: Some(schema_type),
+            ..Default::default()
         }
     }
 
+    /// Create a new string schema
+    pub fn string() -> Self {
+        Self::typed(SchemaType::String)
+    }
+
     /// Create a new number schema
     pub fn number() -> Self {
-        Self {
-            schema_type: SchemaType::Number,
-            description: None,
-            required: None,
-            properties: None,
-            item: None,
-            enum_values: None,
-            minimum: None,
-            maximum: None,
-            min_length: None,
-            max_length: None,
-            default: None,
-        }
+        Self::typed(SchemaType::Number)
     }
 
     /// Create a new integer schema
     pub fn integer() -> Self {
-        Self {
-            schema_type: SchemaType::Integer,
-            description: None,
-            required: None,
-            properties: None,
-            item: None,
-            enum_values: None,
-            minimum: None,
-            maximum: None,
-            min_length: None,
-            max_length: None,
-            default: None,
-        }
+        Self::typed(SchemaType::Integer)
     }
 
     /// Create a new boolean schema
     pub fn boolean() -> Self {
-        Self {
-            schema_type: SchemaType::Boolean,
-            description: None,
-            required: None,
-            properties: None,
-            item: None,
-            enum_values: None,
-            minimum: None,
-            maximum: None,
-            min_length: None,
-            max_length: None,
-            default: None,
-        }
+        Self::typed(SchemaType::Boolean)
     }
 
     /// Create a new array schema
-    pub fn array(item: ParameterSchema) -> Self {
+    pub fn array(items: ParameterSchema) -> Self {
         Self {
-            schema_type: SchemaType::Array,
-            description: None,
-            required: None,
-            properties: None,
-            item: Some(Box::new(item)),
-            enum_values: None,
-            minimum: None,
-            maximum: None,
-            min_length: None,
-            max_length: None,
-            default: None,
+            items: Some(Box::new(items)),
+            ..Self::typed(SchemaType::Array)
         }
     }
 
     /// Create a new object schema
     pub fn object(properties: HashMap<String, ParameterSchema>) -> Self {
         Self {
-            schema_type: SchemaType::Object,
-            description: None,
-            required: None,
             properties: Some(properties),
-            item: None,
-            enum_values: None,
-            minimum: None,
-            maximum: None,
-            min_length: None,
-            max_length: None,
-            default: None,
+            ..Self::typed(SchemaType::Object)
+        }
+    }
+
+    /// Create an object schema whose values all match one schema, e.g. a string map
+    pub fn map(values: ParameterSchema) -> Self {
+        Self::typed(SchemaType::Object).with_additional_properties(values)
+    }
+
+    /// Create a schema matching exactly one of the variants
+    pub fn one_of(variants: Vec<ParameterSchema>) -> Self {
+        Self {
+            one_of: Some(variants),
+            ..Default::default()
+        }
+    }
+
+    /// Create a schema matching at least one of the variants
+    pub fn any_of(variants: Vec<ParameterSchema>) -> Self {
+        Self {
+            any_of: Some(variants),
+            ..Default::default()
+        }
+    }
+
+    /// Create a schema matching all of the parts
+    pub fn all_of(parts: Vec<ParameterSchema>) -> Self {
+        Self {
+            all_of: Some(parts),
+            ..Default::default()
+        }
+    }
+
+    /// Create a reference to a named definition, e.g. `#/$defs/Point`
+    pub fn reference(reference: &str) -> Self {
+        Self {
+            reference: Some(reference.to_string()),
+            ..Default::default()
         }
     }
 
@@ -206,213 +325,727 @@ impl ParameterSchema {
         self
     }
 
+    /// Set min items
+    pub fn with_min_items(mut self, min_items: usize) -> Self {
+        self.min_items = Some(min_items);
+        self
+    }
+
+    /// Set max items
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
     /// Set enum values
     pub fn with_enum(mut self, values: Vec<Value>) -> Self {
         self.enum_values = Some(values);
         self
     }
 
+    /// Set the single allowed value
+    pub fn with_const(mut self, value: Value) -> Self {
+        self.const_value = Some(value);
+        self
+    }
+
     /// Set default value
     pub fn with_default(mut self, default: Value) -> Self {
         self.default = Some(default);
         self
     }
 
-    /// Validate a value against this schema
-    pub fn validate(&self, value: &Value) -> Result<(), ValidationError> {
-        match self.schema_type {
-            SchemaType::String => self.validate_string(value),
-            SchemaType::Number => self.validate_number(value),
-            SchemaType::Integer => self.validate_integer(value),
-            SchemaType::Boolean => self.validate_boolean(value),
-            SchemaType::Array => self.validate_array(value),
-            SchemaType::Object => self.validate_object(value),
-        }
+    /// Set a regular expression strings must match
+    ///
+    /// Fails if the pattern does not compile.
+    pub fn with_pattern(mut self, pattern: &str) -> Result<Self, SchemaError> {
+        self.pattern = Some(Pattern::new(pattern)?);
+        Ok(self)
     }
 
-    fn validate_string(&self, value: &Value) -> Result<(), ValidationError> {
-        if !value.is_string() {
-            return Err(ValidationError::TypeMismatch {
-                expected: "string".to_string(),
-                found: value_type_name(value),
-            });
-        }
+    /// Set the string format
+    pub fn with_format(mut self, format: &str) -> Self {
+        self.format = Some(format.to_string());
+        self
+    }
 
-        let s = value.as_str().unwrap();
+    /// Also accept null
+    pub fn with_nullable(mut self, nullable: bool) -> Self {
+        self.nullable = nullable;
+        self
+    }
 
-        if let Some(min) = self.min_length {
-            if s.len() < min {
-                return Err(ValidationError::MinLength {
-                    min,
-                    found: s.len(),
-                });
-            }
+    /// Require properties not listed in `properties` to match a schema
+    pub fn with_additional_properties(mut self, schema: ParameterSchema) -> Self {
+        self.additional_properties = Some(AdditionalProperties::Schema(Box::new(schema)));
+        self
+    }
+
+    /// Reject properties not listed in `properties`
+    pub fn deny_additional_properties(mut self) -> Self {
+        self.additional_properties = Some(AdditionalProperties::Allowed(false));
+        self
+    }
+
+    /// Add a named definition under `$defs`
+    pub fn with_definition(mut self, name: &str, schema: ParameterSchema) -> Self {
+        self.defs
+            .get_or_insert_with(HashMap::new)
+            .insert(name.to_string(), schema);
+        self
+    }
+
+    /// Convert to a JSON Schema document
+    pub fn to_json(&self) -> Value {
+        let mut map = Map::new();
+        for (key, value) in &self.extra {
+            map.insert(key.clone(), value.clone());
         }
 
+        if let Some(type_value) = self.type_json() {
+            map.insert("type".to_string(), type_value);
+        }
+        if let Some(ref reference) = self.reference {
+            map.insert("$ref".to_string(), Value::from(reference.as_str()));
+        }
+        if let Some(ref description) = self.description {
+            map.insert("description".to_string(), Value::from(description.as_str()));
+        }
+        if let Some(ref properties) = self.properties {
+            map.insert("properties".to_string(), schema_map_to_json(properties));
+        }
+        if let Some(ref required) = self.required {
+            map.insert("required".to_string(), Value::from(required.clone()));
+        }
+        match self.additional_properties {
+            Some(AdditionalProperties::Allowed(allowed)) => {
+                map.insert("additionalProperties".to_string(), Value::Bool(allowed));
+            }
+            Some(AdditionalProperties::Schema(ref schema)) => {
+                map.insert("additionalProperties".to_string(), schema.to_json());
+            }
+            None => {}
+        }
+        if let Some(ref items) = self.items {
+            map.insert("items".to_string(), items.to_json());
+        }
+        if let Some(ref values) = self.enum_values {
+            map.insert("enum".to_string(), Value::Array(values.clone()));
+        }
+        if let Some(ref value) = self.const_value {
+            map.insert("const".to_string(), value.clone());
+        }
+        if let Some(minimum) = self.minimum {
+            map.insert("minimum".to_string(), number_to_json(minimum));
+        }
+        if let Some(maximum) = self.maximum {
+            map.insert("maximum".to_string(), number_to_json(maximum));
+        }
+        if let Some(min) = self.min_length {
+            map.insert("minLength".to_string(), Value::from(min));
+        }
         if let Some(max) = self.max_length {
-            if s.len() > max {
-                return Err(ValidationError::MaxLength {
-                    max,
-                    found: s.len(),
-                });
+            map.insert("maxLength".to_string(), Value::from(max));
+        }
+        if let Some(min) = self.min_items {
+            map.insert("minItems".to_string(), Value::from(min));
+        }
+        if let Some(max) = self.max_items {
+            map.insert("maxItems".to_string(), Value::from(max));
+        }
+        if let Some(ref pattern) = self.pattern {
+            map.insert("pattern".to_string(), Value::from(pattern.as_str()));
+        }
+        if let Some(ref format) = self.format {
+            map.insert("format".to_string(), Value::from(format.as_str()));
+        }
+        if let Some(ref default) = self.default {
+            map.insert("default".to_string(), default.clone());
+        }
+        for (key, variants) in [
+            ("oneOf", &self.one_of),
+            ("anyOf", &self.any_of),
+            ("allOf", &self.all_of),
+        ] {
+            if let Some(variants) = variants {
+                map.insert(key.to_string(), variants.iter().map(Self::to_json).collect());
             }
         }
+        if let Some(ref defs) = self.defs {
+            map.insert("$defs".to_string(), schema_map_to_json(defs));
+        }
+        if let Some(ref definitions) = self.definitions {
+            map.insert("definitions".to_string(), schema_map_to_json(definitions));
+        }
 
-        if let Some(ref enum_values) = self.enum_values {
-            if !enum_values.contains(value) {
-                return Err(ValidationError::InvalidEnumValue {
-                    allowed: enum_values.clone(),
-                    found: value.clone(),
-                });
+        Value::Object(map)
+    }
+
+    /// The `type` keyword's value, with `"null"` added when nullable
+    fn type_json(&self) -> Option<Value> {
+        let schema_type = self.schema_type?;
+        let name = Value::from(schema_type.as_str());
+        Some(if self.nullable && schema_type != SchemaType::Null {
+            Value::Array(vec![name, Value::from("null")])
+        } else {
+            name
+        })
+    }
+
+    /// Parse a JSON Schema document
+    ///
+    /// `true` parses as an empty schema. Union types other than
+    /// `[T, "null"]` and `false` schemas are not supported.
+    pub fn from_json(value: &Value) -> Result<Self, SchemaError> {
+        let map = match value {
+            Value::Object(map) => map,
+            Value::Bool(true) => return Ok(Self::default()),
+            Value::Bool(false) => {
+                return Err(SchemaError::Unsupported("`false` schema".to_string()))
             }
-        }
+            _ => return Err(SchemaError::invalid("", "schema must be an object")),
+        };
 
-        Ok(())
+        let mut schema = Self::default();
+        for (key, value) in map {
+            match key.as_str() {
+                "type" => {
+                    let (schema_type, nullable) = parse_type(value)?;
+                    schema.schema_type = Some(schema_type);
+                    schema.nullable = nullable;
+                }
+                "$ref" => schema.reference = Some(expect_str(key, value)?.to_string()),
+                "description" => schema.description = Some(expect_str(key, value)?.to_string()),
+                "properties" => schema.properties = Some(parse_schema_map(key, value)?),
+                "required" => {
+                    let required = value
+                        .as_array()
+                        .and_then(|items| {
+                            items
+                                .iter()
+                                .map(|item| item.as_str().map(str::to_string))
+                                .collect::<Option<Vec<_>>>()
+                        })
+                        .ok_or_else(|| SchemaError::invalid(key, "expected an array of strings"))?;
+                    schema.required = Some(required);
+                }
+                "additionalProperties" => {
+                    schema.additional_properties = Some(match value {
+                        Value::Bool(allowed) => AdditionalProperties::Allowed(*allowed),
+                        _ => AdditionalProperties::Schema(Box::new(Self::from_json(value)?)),
+                    });
+                }
+                "items" => schema.items = Some(Box::new(Self::from_json(value)?)),
+                "enum" => {
+                    let values = value
+                        .as_array()
+                        .ok_or_else(|| SchemaError::invalid(key, "expected an array"))?;
+                    schema.enum_values = Some(values.clone());
+                }
+                "const" => schema.const_value = Some(value.clone()),
+                "minimum" => schema.minimum = Some(expect_f64(key, value)?),
+                "maximum" => schema.maximum = Some(expect_f64(key, value)?),
+                "minLength" => schema.min_length = Some(expect_usize(key, value)?),
+                "maxLength" => schema.max_length = Some(expect_usize(key, value)?),
+                "minItems" => schema.min_items = Some(expect_usize(key, value)?),
+                "maxItems" => schema.max_items = Some(expect_usize(key, value)?),
+                "pattern" => {
+                    schema.pattern = Some(Pattern::new(expect_str(key, value)?)?);
+                }
+                "format" => schema.format = Some(expect_str(key, value)?.to_string()),
+                "default" => schema.default = Some(value.clone()),
+                "oneOf" => schema.one_of = Some(parse_schema_list(key, value)?),
+                "anyOf" => schema.any_of = Some(parse_schema_list(key, value)?),
+                "allOf" => schema.all_of = Some(parse_schema_list(key, value)?),
+                "$defs" => schema.defs = Some(parse_schema_map(key, value)?),
+                "definitions" => schema.definitions = Some(parse_schema_map(key, value)?),
+                _ => {
+                    schema.extra.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        Ok(schema)
     }
 
-    fn validate_number(&self, value: &Value) -> Result<(), ValidationError> {
-        if !value.is_number() {
-            return Err(ValidationError::TypeMismatch {
-                expected: "number".to_string(),
-                found: value_type_name(value),
-            });
+    /// Validate a value against this schema
+    ///
+    /// Every violation is collected, each with a JSON Pointer to the
+    /// offending value. `$ref`s are resolved against this schema's
+    /// `$defs`/`definitions`.
+    pub fn validate(&self, value: &Value) -> Result<(), ValidationError> {
+        let mut violations = Vec::new();
+        self.collect_violations(self, value, "", 0, &mut violations);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { violations })
         }
+    }
 
-        let n = value.as_f64().unwrap();
+    /// Fill in `default`s for missing object properties
+    ///
+    /// Recurses into nested objects and array items that are present, and
+    /// into defaults it inserts. The result is the normalized parameter
+    /// object a tool receives.
+    pub fn apply_defaults(&self, value: &Value) -> Value {
+        let mut value = value.clone();
+        self.fill_defaults(self, &mut value, 0);
+        value
+    }
 
-        if let Some(min) = self.minimum {
-            if n < min {
-                return Err(ValidationError::Minimum { min, found: n });
-            }
+    fn fill_defaults(&self, root: &ParameterSchema, value: &mut Value, depth: usize) {
+        if depth > MAX_REF_DEPTH {
+            return;
+        }
+        if let Some(target) = self.reference.as_deref().and_then(|r| root.resolve_ref(r)) {
+            target.fill_defaults(root, value, depth + 1);
+        }
+        for part in self.all_of.iter().flatten() {
+            part.fill_defaults(root, value, depth + 1);
         }
 
-        if let Some(max) = self.maximum {
-            if n > max {
-                return Err(ValidationError::Maximum { max, found: n });
+        match value {
+            Value::Object(obj) => {
+                for (name, schema) in self.properties.iter().flatten() {
+                    if !obj.contains_key(name) {
+                        match schema.default_in(root) {
+                            Some(default) => obj.insert(name.clone(), default.clone()),
+                            None => continue,
+                        };
+                    }
+                    if let Some(property) = obj.get_mut(name) {
+                        schema.fill_defaults(root, property, depth + 1);
+                    }
+                }
             }
+            Value::Array(items) => {
+                if let Some(ref item_schema) = self.items {
+                    for item in items {
+                        item_schema.fill_defaults(root, item, depth + 1);
+                    }
+                }
+            }
+            _ => {}
         }
+    }
 
-        Ok(())
+    /// The schema's default, or the default of the schema it references
+    fn default_in<'a>(&'a self, root: &'a ParameterSchema) -> Option<&'a Value> {
+        self.default.as_ref().or_else(|| {
+            self.reference
+                .as_deref()
+                .and_then(|r| root.resolve_ref(r))
+                .and_then(|target| target.default.as_ref())
+        })
     }
 
-    fn validate_integer(&self, value: &Value) -> Result<(), ValidationError> {
-        if !value.is_i64() && !value.is_u64() {
-            return Err(ValidationError::TypeMismatch {
-                expected: "integer".to_string(),
-                found: value_type_name(value),
-            });
+    /// Check this (sub)schema, resolving references in `root`
+    fn collect_violations(
+        &self,
+        root: &ParameterSchema,
+        value: &Value,
+        path: &str,
+        depth: usize,
+        out: &mut Vec<Violation>,
+    ) {
+        if let Some(ref reference) = self.reference {
+            match root.resolve_ref(reference).filter(|_| depth < MAX_REF_DEPTH) {
+                Some(target) => target.collect_violations(root, value, path, depth + 1, out),
+                None => out.push(Violation::new(
+                    path,
+                    "$ref",
+                    Value::from(reference.as_str()),
+                    value,
+                    format!("cannot resolve schema reference '{}'", reference),
+                )),
+            }
         }
 
-        let n = if let Some(i) = value.as_i64() {
-            i as f64
-        } else if let Some(u) = value.as_u64() {
-            u as f64
-        } else {
-            return Err(ValidationError::TypeMismatch {
-                expected: "integer".to_string(),
-                found: value_type_name(value),
-            });
-        };
+        if value.is_null() && self.nullable {
+            return;
+        }
 
-        if let Some(min) = self.minimum {
-            if n < min {
-                return Err(ValidationError::Minimum { min, found: n });
+        if let Some(schema_type) = self.schema_type {
+            if !schema_type.matches(value) {
+                out.push(Violation::new(
+                    path,
+                    "type",
+                    self.type_json().unwrap_or_default(),
+                    value,
+                    format!("expected {}, found {}", schema_type.as_str(), value_type_name(value)),
+                ));
+                return;
             }
         }
 
-        if let Some(max) = self.maximum {
-            if n > max {
-                return Err(ValidationError::Maximum { max, found: n });
+        match value {
+            Value::String(s) => self.check_string(s, value, path, out),
+            Value::Number(_) => self.check_number(value, path, out),
+            Value::Array(items) => self.check_array(root, items, value, path, depth, out),
+            Value::Object(obj) => self.check_object(root, obj, path, depth, out),
+            _ => {}
+        }
+
+        if let Some(ref enum_values) = self.enum_values {
+            if !enum_values.contains(value) {
+                let allowed: Vec<String> = enum_values.iter().map(Value::to_string).collect();
+                out.push(Violation::new(
+                    path,
+                    "enum",
+                    Value::Array(enum_values.clone()),
+                    value,
+                    format!("must be one of {}", allowed.join(", ")),
+                ));
+            }
+        }
+
+        if let Some(ref expected) = self.const_value {
+            if expected != value {
+                out.push(Violation::new(
+                    path,
+                    "const",
+                    expected.clone(),
+                    value,
+                    format!("must be {}", expected),
+                ));
             }
         }
 
-        Ok(())
+        self.check_combinators(root, value, path, depth, out);
     }
 
-    fn validate_boolean(&self, value: &Value) -> Result<(), ValidationError> {
-        if !value.is_boolean() {
-            return Err(ValidationError::TypeMismatch {
-                expected: "boolean".to_string(),
-                found: value_type_name(value),
-            });
+    /// Look up a `$ref` of the form `#`, `#/$defs/<name>` or `#/definitions/<name>`
+    fn resolve_ref(&self, reference: &str) -> Option<&ParameterSchema> {
+        if reference == "#" {
+            return Some(self);
+        }
+        let unescape = |name: &str| name.replace("~1", "/").replace("~0", "~");
+        if let Some(name) = reference.strip_prefix("#/$defs/") {
+            return self.defs.as_ref()?.get(&unescape(name));
         }
-        Ok(())
+        if let Some(name) = reference.strip_prefix("#/definitions/") {
+            return self.definitions.as_ref()?.get(&unescape(name));
+        }
+        None
     }
 
-    fn validate_array(&self, value: &Value) -> Result<(), ValidationError> {
-        if !value.is_array() {
-            return Err(ValidationError::TypeMismatch {
-                expected: "array".to_string(),
-                found: value_type_name(value),
-            });
+    fn check_string(&self, s: &str, value: &Value, path: &str, out: &mut Vec<Violation>) {
+        let length = s.chars().count();
+
+        if let Some(min) = self.min_length.filter(|&min| length < min) {
+            out.push(Violation::new(
+                path,
+                "minLength",
+                Value::from(min),
+                value,
+                format!("must be at least {} characters, found {}", min, length),
+            ));
         }
 
-        let arr = value.as_array().unwrap();
+        if let Some(max) = self.max_length.filter(|&max| length > max) {
+            out.push(Violation::new(
+                path,
+                "maxLength",
+                Value::from(max),
+                value,
+                format!("must be at most {} characters, found {}", max, length),
+            ));
+        }
 
-        if let Some(min) = self.min_length {
-            if arr.len() < min {
-                return Err(ValidationError::MinLength {
-                    min,
-                    found: arr.len(),
-                });
+        if let Some(ref pattern) = self.pattern {
+            if !pattern.is_match(s) {
+                out.push(Violation::new(
+                    path,
+                    "pattern",
+                    Value::from(pattern.as_str()),
+                    value,
+                    format!("must match pattern '{}'", pattern.as_str()),
+                ));
             }
         }
 
-        if let Some(max) = self.max_length {
-            if arr.len() > max {
-                return Err(ValidationError::MaxLength {
-                    max,
-                    found: arr.len(),
-                });
+        if let Some(ref format) = self.format {
+            if !format_matches(format, s) {
+                out.push(Violation::new(
+                    path,
+                    "format",
+                    Value::from(format.as_str()),
+                    value,
+                    format!("must be a valid {}", format),
+                ));
             }
         }
+    }
 
-        if let Some(ref item_schema) = self.item {
-            for (i, item) in arr.iter().enumerate() {
-                item_schema
-                    .validate(item)
-                    .map_err(|e| ValidationError::ItemInvalid {
-                        index: i,
-                        cause: Box::new(e),
-                    })?;
-            }
+    fn check_number(&self, value: &Value, path: &str, out: &mut Vec<Violation>) {
+        let Some(n) = value.as_f64() else {
+            return;
+        };
+
+        if let Some(min) = self.minimum.filter(|&min| n < min) {
+            out.push(Violation::new(
+                path,
+                "minimum",
+                number_to_json(min),
+                value,
+                format!("must be at least {}", min),
+            ));
         }
 
-        Ok(())
+        if let Some(max) = self.maximum.filter(|&max| n > max) {
+            out.push(Violation::new(
+                path,
+                "maximum",
+                number_to_json(max),
+                value,
+                format!("must be at most {}", max),
+            ));
+        }
     }
 
-    fn validate_object(&self, value: &Value) -> Result<(), ValidationError> {
-        if !value.is_object() {
-            return Err(ValidationError::TypeMismatch {
-                expected: "object".to_string(),
-                found: value_type_name(value),
-            });
+    fn check_array(
+        &self,
+        root: &ParameterSchema,
+        items: &[Value],
+        value: &Value,
+        path: &str,
+        depth: usize,
+        out: &mut Vec<Violation>,
+    ) {
+        if let Some(min) = self.min_items.filter(|&min| items.len() < min) {
+            out.push(Violation::new(
+                path,
+                "minItems",
+                Value::from(min),
+                value,
+                format!("must have at least {} items, found {}", min, items.len()),
+            ));
         }
 
-        let obj = value.as_object().unwrap();
+        if let Some(max) = self.max_items.filter(|&max| items.len() > max) {
+            out.push(Violation::new(
+                path,
+                "maxItems",
+                Value::from(max),
+                value,
+                format!("must have at most {} items, found {}", max, items.len()),
+            ));
+        }
 
-        if let Some(ref required) = self.required {
-            for prop in required {
-                if !obj.contains_key(prop) {
-                    return Err(ValidationError::MissingProperty {
-                        property: prop.clone(),
-                    });
-                }
+        if let Some(ref item_schema) = self.items {
+            for (index, item) in items.iter().enumerate() {
+                let item_path = pointer(path, &index.to_string());
+                item_schema.collect_violations(root, item, &item_path, depth, out);
             }
         }
+    }
 
-        if let Some(ref properties) = self.properties {
-            for (key, val) in obj.iter() {
-                if let Some(schema) = properties.get(key) {
-                    schema.validate(val).map_err(|e| ValidationError::PropertyInvalid {
-                        property: key.clone(),
-                        cause: Box::new(e),
-                    })?;
+    fn check_object(
+        &self,
+        root: &ParameterSchema,
+        obj: &Map<String, Value>,
+        path: &str,
+        depth: usize,
+        out: &mut Vec<Violation>,
+    ) {
+        for property in self.required.iter().flatten() {
+            if !obj.contains_key(property) {
+                out.push(Violation::new(
+                    &pointer(path, property),
+                    "required",
+                    Value::from(property.as_str()),
+                    &Value::Null,
+                    format!("missing required property '{}'", property),
+                ));
+            }
+        }
+
+        for (key, val) in obj {
+            let property_path = pointer(path, key);
+            let schema = match self.properties.as_ref().and_then(|props| props.get(key)) {
+                Some(schema) => schema,
+                None => match self.additional_properties {
+                    Some(AdditionalProperties::Allowed(false)) => {
+                        out.push(Violation::new(
+                            &property_path,
+                            "additionalProperties",
+                            Value::Bool(false),
+                            val,
+                            format!("unexpected property '{}'", key),
+                        ));
+                        continue;
+                    }
+                    Some(AdditionalProperties::Schema(ref schema)) => schema,
+                    _ => continue,
+                },
+            };
+            schema.collect_violations(root, val, &property_path, depth, out);
+        }
+    }
+
+    fn check_combinators(
+        &self,
+        root: &ParameterSchema,
+        value: &Value,
+        path: &str,
+        depth: usize,
+        out: &mut Vec<Violation>,
+    ) {
+        for part in self.all_of.iter().flatten() {
+            part.collect_violations(root, value, path, depth, out);
+        }
+
+        for (keyword, variants) in [("anyOf", &self.any_of), ("oneOf", &self.one_of)] {
+            let Some(variants) = variants else {
+                continue;
+            };
+            let results: Vec<Vec<Violation>> = variants
+                .iter()
+                .map(|variant| {
+                    let mut violations = Vec::new();
+                    variant.collect_violations(root, value, path, depth, &mut violations);
+                    violations
+                })
+                .collect();
+            let matched = results.iter().filter(|violations| violations.is_empty()).count();
+
+            if matched == 0 {
+                // When only one variant has the right type, its violations are the useful ones
+                let mut candidates = results.into_iter().filter(|violations| {
+                    !violations.iter().any(|v| v.keyword == "type" && v.path == path)
+                });
+                match (candidates.next(), candidates.next()) {
+                    (Some(only), None) => out.extend(only),
+                    _ => out.push(Violation::new(
+                        path,
+                        keyword,
+                        variants.iter().map(Self::to_json).collect(),
+                        value,
+                        format!("does not match any of the {} allowed schemas", variants.len()),
+                    )),
                 }
+            } else if keyword == "oneOf" && matched > 1 {
+                out.push(Violation::new(
+                    path,
+                    keyword,
+                    variants.iter().map(Self::to_json).collect(),
+                    value,
+                    format!("matches {} schemas, expected exactly one", matched),
+                ));
             }
         }
+    }
+}
 
-        Ok(())
+impl From<ParameterSchema> for Value {
+    fn from(schema: ParameterSchema) -> Self {
+        schema.to_json()
+    }
+}
+
+impl TryFrom<Value> for ParameterSchema {
+    type Error = SchemaError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        Self::from_json(&value)
+    }
+}
+
+/// Parse `type`, which may be a name or a `[name, "null"]` pair
+fn parse_type(value: &Value) -> Result<(SchemaType, bool), SchemaError> {
+    let parse_name = |name: &Value| {
+        name.as_str()
+            .and_then(SchemaType::parse)
+            .ok_or_else(|| SchemaError::invalid("type", &format!("unknown type {}", name)))
+    };
+
+    match value {
+        Value::Array(names) => {
+            let types = names.iter().map(parse_name).collect::<Result<Vec<_>, _>>()?;
+            match types.as_slice() {
+                [only] => Ok((*only, false)),
+                [SchemaType::Null, other] | [other, SchemaType::Null] => Ok((*other, true)),
+                _ => Err(SchemaError::Unsupported(format!("union type {}", value))),
+            }
+        }
+        _ => Ok((parse_name(value)?, false)),
+    }
+}
+
+fn parse_schema_map(keyword: &str, value: &Value) -> Result<HashMap<String, ParameterSchema>, SchemaError> {
+    value
+        .as_object()
+        .ok_or_else(|| SchemaError::invalid(keyword, "expected an object"))?
+        .iter()
+        .map(|(name, schema)| Ok((name.clone(), ParameterSchema::from_json(schema)?)))
+        .collect()
+}
+
+fn parse_schema_list(keyword: &str, value: &Value) -> Result<Vec<ParameterSchema>, SchemaError> {
+    value
+        .as_array()
+        .ok_or_else(|| SchemaError::invalid(keyword, "expected an array"))?
+        .iter()
+        .map(ParameterSchema::from_json)
+        .collect()
+}
+
+fn schema_map_to_json(schemas: &HashMap<String, ParameterSchema>) -> Value {
+    Value::Object(
+        schemas
+            .iter()
+            .map(|(name, schema)| (name.clone(), schema.to_json()))
+            .collect(),
+    )
+}
+
+fn expect_str<'a>(keyword: &str, value: &'a Value) -> Result<&'a str, SchemaError> {
+    value
+        .as_str()
+        .ok_or_else(|| SchemaError::invalid(keyword, "expected a string"))
+}
+
+fn expect_f64(keyword: &str, value: &Value) -> Result<f64, SchemaError> {
+    value
+        .as_f64()
+        .ok_or_else(|| SchemaError::invalid(keyword, "expected a number"))
+}
+
+fn expect_usize(keyword: &str, value: &Value) -> Result<usize, SchemaError> {
+    value
+        .as_u64()
+        .map(|n| n as usize)
+        .ok_or_else(|| SchemaError::invalid(keyword, "expected a non-negative integer"))
+}
+
+/// Write whole numbers as integers so `"minimum": 1` round-trips unchanged
+fn number_to_json(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Value::from(n as i64)
+    } else {
+        Value::from(n)
+    }
+}
+
+/// Check a string against a known `format`; unknown formats always pass
+fn format_matches(format: &str, s: &str) -> bool {
+    match format {
+        "date-time" => chrono::DateTime::parse_from_rfc3339(s).is_ok(),
+        "date" => chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok(),
+        "time" => chrono::NaiveTime::parse_from_str(s.trim_end_matches('Z'), "%H:%M:%S%.f").is_ok(),
+        "email" => s
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.') && !domain.contains('@')),
+        "uri" => s.split_once(':').is_some_and(|(scheme, _)| {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }),
+        "uuid" => uuid::Uuid::parse_str(s).is_ok(),
+        "ipv4" => s.parse::<std::net::Ipv4Addr>().is_ok(),
+        "ipv6" => s.parse::<std::net::Ipv6Addr>().is_ok(),
+        _ => true,
     }
 }
 
@@ -434,46 +1067,76 @@ fn value_type_name(value: &Value) -> String {
     }
 }
 
-/// Parameter validation errors
-#[derive(Debug, Error)]
-pub enum ValidationError {
-    #[error("Type mismatch: expected {expected}, found {found}")]
-    TypeMismatch { expected: String, found: String },
-
-    #[error("Missing required property: {property}")]
-    MissingProperty { property: String },
-
-    #[error("Property '{property}' is invalid: {cause}")]
-    PropertyInvalid {
-        property: String,
-        cause: Box<ValidationError>,
-    },
-
-    #[error("Array item at index {index} is invalid: {cause}")]
-    ItemInvalid {
-        index: usize,
-        cause: Box<ValidationError>,
-    },
+/// A single way a value breaks its schema
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    /// JSON Pointer to the offending value; empty for the root
+    pub path: String,
+    /// Schema keyword that failed, e.g. `type`, `required` or `minLength`
+    pub keyword: String,
+    /// The keyword's constraint, e.g. the expected type or the minimum
+    pub expected: Value,
+    /// The value found at `path` (null for a missing property)
+    pub found: Value,
+    /// Human-readable explanation
+    pub message: String,
+}
 
-    #[error("Minimum length constraint violated: min {min}, found {found}")]
-    MinLength { min: usize, found: usize },
+impl Violation {
+    fn new(path: &str, keyword: &str, expected: Value, found: &Value, message: String) -> Self {
+        Self {
+            path: path.to_string(),
+            keyword: keyword.to_string(),
+            expected,
+            found: found.clone(),
+            message,
+        }
+    }
+}
 
-    #[error("Maximum length constraint violated: max {max}, found {found}")]
-    MaxLength { max: usize, found: usize },
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
 
-    #[error("Minimum value constraint violated: min {min}, found {found}")]
-    Minimum { min: f64, found: f64 },
+/// Parameter validation failure, listing every violation found
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{}", join_violations(.violations))]
+pub struct ValidationError {
+    pub violations: Vec<Violation>,
+}
 
-    #[error("Maximum value constraint violated: max {max}, found {found}")]
-    Maximum { max: f64, found: f64 },
+fn join_violations(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .map(Violation::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
 
-    #[error("Invalid enum value: must be one of {allowed:?}, found {found}")]
-    InvalidEnumValue { allowed: Vec<Value>, found: Value },
+/// Append a segment to a JSON Pointer, escaping `~` and `/`
+fn pointer(path: &str, segment: &str) -> String {
+    format!("{}/{}", path, segment.replace('~', "~0").replace('/', "~1"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
+
+    /// "<path> <keyword>" for each violation
+    fn violations(schema: &ParameterSchema, value: &Value) -> Vec<String> {
+        schema
+            .validate(value)
+            .err()
+            .map(|e| e.violations.iter().map(|v| format!("{} {}", v.path, v.keyword)).collect())
+            .unwrap_or_default()
+    }
 
     #[test]
     fn test_string_schema() {
@@ -485,6 +1148,10 @@ mod tests {
         assert!(schema.validate(&serde_json::json!("")).is_err());
         assert!(schema.validate(&serde_json::json!("this is too long")).is_err());
         assert!(schema.validate(&serde_json::json!(123)).is_err());
+
+        let schema = ParameterSchema::string().with_pattern("^v[0-9]+$").unwrap();
+        assert!(schema.validate(&serde_json::json!("v12")).is_ok());
+        assert!(schema.validate(&serde_json::json!("12")).is_err());
     }
 
     #[test]
@@ -515,12 +1182,225 @@ mod tests {
     #[test]
     fn test_array_schema() {
         let schema = ParameterSchema::array(ParameterSchema::string())
-            .with_min_length(1)
-            .with_max_length(5);
+            .with_min_items(1)
+            .with_max_items(5);
 
         assert!(schema.validate(&serde_json::json!(["a", "b"])).is_ok());
         assert!(schema.validate(&serde_json::json!([])).is_err());
         assert!(schema.validate(&serde_json::json!(["a", "b", "c", "d", "e", "f"])).is_err());
         assert!(schema.validate(&serde_json::json!([1, 2, 3])).is_err());
     }
+
+    #[test]
+    fn test_serializes_standard_keywords() {
+        let schema = ParameterSchema::array(ParameterSchema::string().with_enum(vec![json!("a")]))
+            .with_min_items(1);
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            json!({"type": "array", "items": {"type": "string", "enum": ["a"]}, "minItems": 1})
+        );
+
+        let schema = ParameterSchema::string().with_min_length(2).with_nullable(true);
+        assert_eq!(
+            schema.to_json(),
+            json!({"type": ["string", "null"], "minLength": 2})
+        );
+        assert!(schema.validate(&json!(null)).is_ok());
+        assert!(schema.validate(&json!("x")).is_err());
+    }
+
+    #[test]
+    fn test_untyped_length_keywords_stay_separate() {
+        let document = json!({"minItems": 1, "maxLength": 2});
+        let schema: ParameterSchema = serde_json::from_value(document.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), document);
+
+        // Each limit applies only to its own kind of value
+        assert!(schema.validate(&json!(["abc"])).is_ok());
+        assert!(schema.validate(&json!("")).is_ok());
+        assert!(schema.validate(&json!([])).is_err());
+        assert!(schema.validate(&json!("abc")).is_err());
+    }
+
+    #[test]
+    fn test_round_trips_provider_schema() {
+        let document = json!({
+            "type": "object",
+            "title": "Shape",
+            "properties": {
+                "kind": {"type": "string", "const": "circle"},
+                "center": {"$ref": "#/$defs/Point"},
+                "radius": {"type": "number", "minimum": 0, "exclusiveMaximum": 100},
+                "tags": {"type": "array", "items": {"type": "string", "pattern": "^[a-z]+$"}, "maxItems": 3},
+                "created": {"type": ["string", "null"], "format": "date-time"}
+            },
+            "required": ["kind", "center"],
+            "additionalProperties": false,
+            "$defs": {
+                "Point": {
+                    "type": "object",
+                    "properties": {"x": {"type": "number"}, "y": {"type": "number"}},
+                    "required": ["x", "y"]
+                }
+            }
+        });
+
+        let schema: ParameterSchema = serde_json::from_value(document.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), document);
+        assert_eq!(schema.extra.get("title"), Some(&json!("Shape")));
+
+        let valid = json!({"kind": "circle", "center": {"x": 1, "y": 2}, "tags": ["a"], "created": null});
+        assert!(schema.validate(&valid).is_ok());
+
+        let invalid = [
+            json!({"kind": "square", "center": {"x": 1, "y": 2}}),
+            json!({"kind": "circle", "center": {"x": 1}}),
+            json!({"kind": "circle", "center": {"x": 1, "y": 2}, "extra": true}),
+            json!({"kind": "circle", "center": {"x": 1, "y": 2}, "tags": ["A"]}),
+            json!({"kind": "circle", "center": {"x": 1, "y": 2}, "created": "yesterday"}),
+        ];
+        for value in invalid {
+            assert!(schema.validate(&value).is_err(), "{} should be invalid", value);
+        }
+    }
+
+    #[test]
+    fn test_combinators() {
+        let schema = ParameterSchema::one_of(vec![
+            ParameterSchema::string(),
+            ParameterSchema::integer().with_minimum(0.0),
+        ]);
+        assert!(schema.validate(&json!("x")).is_ok());
+        assert!(schema.validate(&json!(3)).is_ok());
+        // Only the integer variant has the right type, so its violation is reported
+        assert_eq!(violations(&schema, &json!(-1)), vec![" minimum"]);
+        assert_eq!(violations(&schema, &json!(true)), vec![" oneOf"]);
+
+        // 4 is both an integer and a number
+        let schema = ParameterSchema::one_of(vec![ParameterSchema::integer(), ParameterSchema::number()]);
+        assert_eq!(violations(&schema, &json!(4)), vec![" oneOf"]);
+        assert!(schema.validate(&json!(4.5)).is_ok());
+
+        let schema = ParameterSchema::all_of(vec![
+            ParameterSchema::string().with_min_length(2),
+            ParameterSchema::string().with_max_length(3),
+        ]);
+        assert!(schema.validate(&json!("abc")).is_ok());
+        assert!(schema.validate(&json!("abcd")).is_err());
+
+        let schema = ParameterSchema::any_of(vec![ParameterSchema::boolean(), ParameterSchema::reference("#/$defs/missing")]);
+        assert!(schema.validate(&json!(true)).is_ok());
+        assert!(schema.validate(&json!("x")).is_err());
+    }
+
+    #[test]
+    fn test_map_and_formats() {
+        let schema = ParameterSchema::map(ParameterSchema::string());
+        assert_eq!(
+            schema.to_json(),
+            json!({"type": "object", "additionalProperties": {"type": "string"}})
+        );
+        assert!(schema.validate(&json!({"PATH": "/bin"})).is_ok());
+        assert!(schema.validate(&json!({"DEBUG": 1})).is_err());
+
+        let cases = [
+            ("date-time", "2026-01-09T10:00:00Z", "2026-01-09"),
+            ("date", "2026-01-09", "09/01/2026"),
+            ("email", "a@example.com", "example.com"),
+            ("uri", "https://example.com", "example.com"),
+            ("uuid", "67e55044-10b1-426f-9247-bb680e5fe0c8", "not-a-uuid"),
+            ("ipv4", "127.0.0.1", "::1"),
+        ];
+        for (format, good, bad) in cases {
+            let schema = ParameterSchema::string().with_format(format);
+            assert!(schema.validate(&json!(good)).is_ok(), "{} should accept {}", format, good);
+            assert!(schema.validate(&json!(bad)).is_err(), "{} should reject {}", format, bad);
+        }
+        // Unknown formats are annotations only
+        assert!(ParameterSchema::string().with_format("color").validate(&json!("red")).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_unsupported_schemas() {
+        assert!(matches!(
+            ParameterSchema::from_json(&json!({"type": ["string", "integer"]})),
+            Err(SchemaError::Unsupported(_))
+        ));
+        assert!(ParameterSchema::from_json(&json!({"type": "strng"})).is_err());
+        assert!(ParameterSchema::from_json(&json!({"pattern": "("})).is_err());
+        // Lookaround is not supported by the regex engine
+        assert!(matches!(
+            ParameterSchema::from_json(&json!({"pattern": "^(?!x)"})),
+            Err(SchemaError::Invalid { keyword, .. }) if keyword == "pattern"
+        ));
+        assert!(ParameterSchema::string().with_pattern("[").is_err());
+        assert_eq!(violations(&ParameterSchema::reference("#"), &json!(1)), vec![" $ref"]);
+    }
+
+    #[test]
+    fn test_collects_all_violations_with_paths() {
+        let mut point = HashMap::new();
+        point.insert("x".to_string(), ParameterSchema::number());
+        point.insert("y".to_string(), ParameterSchema::number());
+        let mut props = HashMap::new();
+        props.insert("name".to_string(), ParameterSchema::string().with_min_length(3));
+        props.insert(
+            "points".to_string(),
+            ParameterSchema::array(ParameterSchema::reference("#/$defs/Point")),
+        );
+        let schema = ParameterSchema::object(props)
+            .with_required(vec!["name".to_string(), "points".to_string()])
+            .deny_additional_properties()
+            .with_definition(
+                "Point",
+                ParameterSchema::object(point).with_required(vec!["x".to_string(), "y".to_string()]),
+            );
+
+        let value = json!({
+            "name": "ab",
+            "points": [{"x": 1, "y": 2}, {"x": "1"}],
+            "a/b": true,
+        });
+        let mut found = violations(&schema, &value);
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                "/a~1b additionalProperties",
+                "/name minLength",
+                "/points/1/x type",
+                "/points/1/y required",
+            ]
+        );
+
+        let error = schema.validate(&json!({"name": 7})).unwrap_err();
+        let violation = error.violations.iter().find(|v| v.path == "/name").unwrap();
+        assert_eq!(violation.expected, json!("string"));
+        assert_eq!(violation.found, json!(7));
+        assert!(error.to_string().contains("/name: expected string, found integer"));
+        assert!(error.to_string().contains("/points: missing required property 'points'"));
+    }
+
+    #[test]
+    fn test_apply_defaults() {
+        let mut options = HashMap::new();
+        options.insert("depth".to_string(), ParameterSchema::integer().with_default(json!(1)));
+        let mut props = HashMap::new();
+        props.insert("path".to_string(), ParameterSchema::string());
+        props.insert("recursive".to_string(), ParameterSchema::boolean().with_default(json!(false)));
+        props.insert(
+            "options".to_string(),
+            ParameterSchema::object(options).with_default(json!({})),
+        );
+        let schema = ParameterSchema::object(props);
+
+        assert_eq!(
+            schema.apply_defaults(&json!({"path": "."})),
+            json!({"path": ".", "recursive": false, "options": {"depth": 1}})
+        );
+        assert_eq!(
+            schema.apply_defaults(&json!({"path": ".", "recursive": true, "options": {"depth": 3}})),
+            json!({"path": ".", "recursive": true, "options": {"depth": 3}})
+        );
+    }
 }
@@ -0,0 +1,403 @@
+//! File search tools
+//!
+//! Provides `search_files` (regex over file contents) and `glob_files`
+//! (path patterns). Both walk a directory tree through the `Sandbox`,
+//! honouring `.gitignore` and `.ignore` files, and return structured
+//! matches rather than raw text.
+
+use super::sandbox::Sandbox;
+use super::{run_blocking, Tool, ToolContext, ToolError, ToolResult};
+use async_trait::async_trait;
+use globset::{Glob, GlobBuilder, GlobMatcher};
+use ignore::WalkBuilder;
+use regex::RegexBuilder;
+use serde_json::json;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Default cap on returned matches for `search_files`
+const DEFAULT_MAX_MATCHES: u64 = 200;
+/// Default cap on returned paths for `glob_files`
+const DEFAULT_MAX_FILES: u64 = 500;
+/// Files larger than this are not searched (10MB)
+const MAX_SEARCH_FILE_BYTES: u64 = 10 * 1024 * 1024;
+/// Matched lines are cut to this many characters
+const MAX_MATCH_LINE_CHARS: usize = 500;
+
+/// Search file contents with a regular expression
+#[derive(Debug, Clone, Default)]
+pub struct SearchFiles {
+    sandbox: Sandbox,
+}
+
+impl SearchFiles {
+    /// Create the tool confined to a sandbox
+    pub fn new(sandbox: Sandbox) -> Self {
+        Self { sandbox }
+    }
+}
+
+#[async_trait]
+impl Tool for SearchFiles {
+    fn name(&self) -> &str {
+        "search_files"
+    }
+
+    fn description(&self) -> &str {
+        "Search file contents under a directory with a regular expression, skipping ignored and binary files"
+    }
+
+    fn parameters_schema(&self) -> &super::schema::ParameterSchema {
+        static SCHEMA: std::sync::OnceLock<super::schema::ParameterSchema> = std::sync::OnceLock::new();
+        SCHEMA.get_or_init(|| {
+            let mut props = HashMap::new();
+            props.insert(
+                "pattern".to_string(),
+                super::schema::ParameterSchema::string()
+                    .with_description("Regular expression to search for")
+                    .with_min_length(1),
+            );
+            props.insert(
+                "path".to_string(),
+                super::schema::ParameterSchema::string()
+                    .with_description("Directory or file to search")
+                    .with_default(json!(".")),
+            );
+            props.insert(
+                "glob".to_string(),
+                super::schema::ParameterSchema::string()
+                    .with_description("Only search files whose relative path matches this glob, e.g. \"**/*.rs\""),
+            );
+            props.insert(
+                "case_insensitive".to_string(),
+                super::schema::ParameterSchema::boolean()
+                    .with_description("Ignore case when matching")
+                    .with_default(json!(false)),
+            );
+            props.insert(
+                "include_hidden".to_string(),
+                super::schema::ParameterSchema::boolean()
+                    .with_description("Also search hidden files and directories")
+                    .with_default(json!(false)),
+            );
+            props.insert(
+                "max_results".to_string(),
+                super::schema::ParameterSchema::integer()
+                    .with_description("Maximum number of matches to return")
+                    .with_minimum(1.0)
+                    .with_default(json!(DEFAULT_MAX_MATCHES)),
+            );
+            super::schema::ParameterSchema::object(props)
+                .with_required(vec!["pattern".to_string()])
+        })
+    }
+
+    async fn execute(&self, params: &Value, _context: &ToolContext) -> ToolResult {
+        run_blocking(self, params, Self::run).await
+    }
+}
+
+impl SearchFiles {
+    fn run(&self, params: &Value) -> ToolResult {
+        let pattern = params
+            .get("pattern")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::ExecutionFailed("Missing 'pattern' parameter".to_string()))?;
+        let path = params.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        let case_insensitive = params
+            .get("case_insensitive")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let include_hidden = params
+            .get("include_hidden")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let max_results = params
+            .get("max_results")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_MAX_MATCHES) as usize;
+        let filter = params
+            .get("glob")
+            .and_then(|v| v.as_str())
+            .map(glob_matcher)
+            .transpose()?;
+
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|e| ToolError::ExecutionFailed(format!("Invalid pattern: {}", e)))?;
+
+        let root = self.sandbox.resolve(path)?;
+        let mut matches = Vec::new();
+        let mut files = Vec::new();
+        let mut truncated = false;
+
+        'files: for (file, relative) in walk_files(&root, include_hidden) {
+            if filter.as_ref().is_some_and(|glob| !glob.is_match(&relative)) {
+                continue;
+            }
+            let Some(content) = read_text(&file) else {
+                continue;
+            };
+
+            let mut matched = false;
+            for (index, line) in content.lines().enumerate() {
+                let Some(found) = regex.find(line) else {
+                    continue;
+                };
+                if matches.len() == max_results {
+                    truncated = true;
+                    break 'files;
+                }
+                matched = true;
+                matches.push(json!({
+                    "path": relative,
+                    "line": index + 1,
+                    "column": line[..found.start()].chars().count() + 1,
+                    "text": line.chars().take(MAX_MATCH_LINE_CHARS).collect::<String>(),
+                }));
+            }
+            if matched {
+                files.push(relative);
+            }
+        }
+        // A file cut short by the limit still had matches
+        if truncated {
+            if let Some(last) = matches.last().and_then(|m| m["path"].as_str()) {
+                if files.last().map(String::as_str) != Some(last) {
+                    files.push(last.to_string());
+                }
+            }
+        }
+
+        Ok(json!({
+            "pattern": pattern,
+            "path": path,
+            "matches": matches,
+            "files": files,
+            "truncated": truncated,
+        }))
+    }
+}
+
+/// Find files by glob pattern
+#[derive(Debug, Clone, Default)]
+pub struct GlobFiles {
+    sandbox: Sandbox,
+}
+
+impl GlobFiles {
+    /// Create the tool confined to a sandbox
+    pub fn new(sandbox: Sandbox) -> Self {
+        Self { sandbox }
+    }
+}
+
+#[async_trait]
+impl Tool for GlobFiles {
+    fn name(&self) -> &str {
+        "glob_files"
+    }
+
+    fn description(&self) -> &str {
+        "Find files whose path (relative to a directory) matches a glob such as \"src/**/*.rs\", skipping ignored files"
+    }
+
+    fn parameters_schema(&self) -> &super::schema::ParameterSchema {
+        static SCHEMA: std::sync::OnceLock<super::schema::ParameterSchema> = std::sync::OnceLock::new();
+        SCHEMA.get_or_init(|| {
+            let mut props = HashMap::new();
+            props.insert(
+                "pattern".to_string(),
+                super::schema::ParameterSchema::string()
+                    .with_description("Glob pattern; `*` stays within a directory, `**` spans directories")
+                    .with_min_length(1),
+            );
+            props.insert(
+                "path".to_string(),
+                super::schema::ParameterSchema::string()
+                    .with_description("Directory to search from")
+                    .with_default(json!(".")),
+            );
+            props.insert(
+                "include_hidden".to_string(),
+                super::schema::ParameterSchema::boolean()
+                    .with_description("Also match hidden files and directories")
+                    .with_default(json!(false)),
+            );
+            props.insert(
+                "max_results".to_string(),
+                super::schema::ParameterSchema::integer()
+                    .with_description("Maximum number of paths to return")
+                    .with_minimum(1.0)
+                    .with_default(json!(DEFAULT_MAX_FILES)),
+            );
+            super::schema::ParameterSchema::object(props)
+                .with_required(vec!["pattern".to_string()])
+        })
+    }
+
+    async fn execute(&self, params: &Value, _context: &ToolContext) -> ToolResult {
+        run_blocking(self, params, Self::run).await
+    }
+}
+
+impl GlobFiles {
+    fn run(&self, params: &Value) -> ToolResult {
+        let pattern = params
+            .get("pattern")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::ExecutionFailed("Missing 'pattern' parameter".to_string()))?;
+        let path = params.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        let include_hidden = params
+            .get("include_hidden")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let max_results = params
+            .get("max_results")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_MAX_FILES) as usize;
+
+        let matcher = glob_matcher(pattern)?;
+        let root = self.sandbox.resolve(path)?;
+
+        let mut files: Vec<String> = walk_files(&root, include_hidden)
+            .into_iter()
+            .map(|(_, relative)| relative)
+            .filter(|relative| matcher.is_match(relative))
+            .collect();
+        let truncated = files.len() > max_results;
+        files.truncate(max_results);
+
+        Ok(json!({
+            "pattern": pattern,
+            "path": path,
+            "files": files,
+            "truncated": truncated,
+        }))
+    }
+}
+
+/// Compile a glob where `*` does not cross directory separators
+fn glob_matcher(pattern: &str) -> Result<GlobMatcher, ToolError> {
+    let glob: Glob = GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|e| ToolError::ExecutionFailed(format!("Invalid glob: {}", e)))?;
+    Ok(glob.compile_matcher())
+}
+
+/// List files under a root, honouring ignore files, sorted by path
+///
+/// Returns each file's path with its `/`-separated path relative to the
+/// root (or its file name when the root is a file). Symlinks are not
+/// followed, so the walk stays inside the sandbox.
+fn walk_files(root: &Path, include_hidden: bool) -> Vec<(PathBuf, String)> {
+    let mut files: Vec<(PathBuf, String)> = WalkBuilder::new(root)
+        .hidden(!include_hidden)
+        .require_git(false)
+        .follow_links(false)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(|entry| {
+            let path = entry.into_path();
+            let relative = match path.strip_prefix(root) {
+                Ok(rel) if !rel.as_os_str().is_empty() => rel,
+                _ => Path::new(path.file_name().unwrap_or_default()),
+            };
+            let relative = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            (path, relative)
+        })
+        .collect();
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    files
+}
+
+/// Read a file as text, skipping large and binary files
+fn read_text(path: &Path) -> Option<String> {
+    if std::fs::metadata(path).ok()?.len() > MAX_SEARCH_FILE_BYTES {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    if bytes.iter().take(8192).any(|&b| b == 0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace() -> (TempDir, Sandbox) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/nested")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "fn alpha() {}\n// TODO: beta\n").unwrap();
+        std::fs::write(root.join("src/nested/mod.rs"), "fn gamma() { todo!() }\n").unwrap();
+        std::fs::write(root.join("README.md"), "TODO docs\n").unwrap();
+        std::fs::write(root.join("target/out.rs"), "// TODO ignored\n").unwrap();
+        std::fs::write(root.join("data.bin"), b"TODO\0binary").unwrap();
+        let sandbox = Sandbox::new([root]).unwrap();
+        (temp_dir, sandbox)
+    }
+
+    #[test]
+    fn test_search_files() {
+        let (_temp_dir, sandbox) = workspace();
+        let tool = SearchFiles::new(sandbox);
+
+        let result = tool.run(&json!({"pattern": "TODO"})).unwrap();
+        assert_eq!(result["files"], json!(["README.md", "src/lib.rs"]));
+        assert_eq!(result["matches"][1]["line"], 2);
+        assert_eq!(result["matches"][1]["column"], 4);
+        assert_eq!(result["matches"][1]["text"], "// TODO: beta");
+        assert_eq!(result["truncated"], false);
+
+        let result = tool
+            .run(&json!({"pattern": "todo", "case_insensitive": true, "glob": "**/*.rs"}))
+            .unwrap();
+        assert_eq!(result["files"], json!(["src/lib.rs", "src/nested/mod.rs"]));
+
+        let result = tool.run(&json!({"pattern": "TODO", "max_results": 1})).unwrap();
+        assert_eq!(result["matches"].as_array().unwrap().len(), 1);
+        assert_eq!(result["truncated"], true);
+    }
+
+    #[test]
+    fn test_search_invalid_pattern() {
+        let (_temp_dir, sandbox) = workspace();
+        let result = SearchFiles::new(sandbox).run(&json!({"pattern": "("}));
+        assert!(matches!(result, Err(ToolError::ExecutionFailed(msg)) if msg.starts_with("Invalid pattern")));
+    }
+
+    #[test]
+    fn test_glob_files() {
+        let (_temp_dir, sandbox) = workspace();
+        let tool = GlobFiles::new(sandbox.clone());
+
+        let result = tool.run(&json!({"pattern": "**/*.rs"})).unwrap();
+        assert_eq!(result["files"], json!(["src/lib.rs", "src/nested/mod.rs"]));
+
+        // `*` does not cross directories
+        let result = tool.run(&json!({"pattern": "src/*.rs"})).unwrap();
+        assert_eq!(result["files"], json!(["src/lib.rs"]));
+
+        let result = tool.run(&json!({"pattern": "*", "include_hidden": true})).unwrap();
+        assert_eq!(result["files"], json!([".gitignore", "README.md", "data.bin"]));
+
+        assert!(matches!(
+            tool.run(&json!({"pattern": "*", "path": "/"})),
+            Err(ToolError::PathViolation(_))
+        ));
+    }
+}
@@ -2,21 +2,28 @@
 //!
 //! Provides tool for running shell commands with timeout support.
 
-use super::{Tool, ToolError, ToolResult};
+use super::{Tool, ToolContext, ToolError, ToolResult};
+use async_trait::async_trait;
+use crate::protocol::OutputStream;
 use serde_json::json;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
 
 /// Default timeout for shell commands (30 seconds)
 const DEFAULT_TIMEOUT_SECS: u64 = 30;
 
+/// How long to wait for buffered output after killing a process
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Run a shell command
 #[derive(Debug, Clone, Copy)]
 pub struct RunCommand;
 
+#[async_trait]
 impl Tool for RunCommand {
     fn name(&self) -> &str {
         "run_command"
@@ -55,7 +62,7 @@ impl Tool for RunCommand {
             );
             props.insert(
                 "environment".to_string(),
-                super::schema::ParameterSchema::object(HashMap::new())
+                super::schema::ParameterSchema::map(super::schema::ParameterSchema::string())
                     .with_description("Environment variables for the command"),
             );
             super::schema::ParameterSchema::object(props)
@@ -63,7 +70,8 @@ impl Tool for RunCommand {
         })
     }
 
-    fn execute(&self, params: &Value) -> ToolResult {
+    /// Runs the command, forwarding each stdout/stderr line as it is read
+    async fn execute(&self, params: &Value, context: &ToolContext) -> ToolResult {
         let command = params
             .get("command")
             .and_then(|v| v.as_str())
@@ -96,34 +104,15 @@ impl Tool for RunCommand {
             })
             .unwrap_or_default();
 
-        // Execute async command
-        // Use block_in_place to avoid nested runtime issues
-        match tokio::runtime::Handle::try_current() {
-            Ok(handle) => {
-                // We're in an async context - spawn a blocking task
-                tokio::task::block_in_place(move || {
-                    handle.block_on(execute_command_async(
-                        command,
-                        args,
-                        working_dir,
-                        timeout_secs,
-                        environment,
-                    ))
-                })
-            }
-            Err(_) => {
-                // Not in an async context - create a new runtime
-                let rt = tokio::runtime::Runtime::new()
-                    .map_err(|e| ToolError::ExecutionFailed(format!("Failed to create runtime: {}", e)))?;
-                rt.block_on(execute_command_async(
-                    command,
-                    args,
-                    working_dir,
-                    timeout_secs,
-                    environment,
-                ))
-            }
-        }
+        execute_command_async(
+            command,
+            args,
+            working_dir,
+            timeout_secs,
+            environment,
+            context.clone(),
+        )
+        .await
     }
 }
 
@@ -131,13 +120,15 @@ impl Tool for RunCommand {
 ///
 /// Uses tokio::process for async I/O with proper timeout handling.
 /// The timeout applies to the entire command execution, and the process
-/// is properly killed if the timeout is exceeded.
+/// is properly killed if the timeout is exceeded. Output lines are
+/// reported through `context` as they arrive.
 async fn execute_command_async(
     command: &str,
     args: Vec<String>,
     working_dir: Option<String>,
     timeout_secs: u64,
     environment: HashMap<String, String>,
+    context: ToolContext,
 ) -> ToolResult {
     let mut cmd = Command::new(command);
 
@@ -161,6 +152,10 @@ async fn execute_command_async(
     // Kill process on drop
     cmd.kill_on_drop(true);
 
+    // Run in a new process group so cancellation can kill its children too
+    #[cfg(unix)]
+    cmd.process_group(0);
+
     // Spawn the process
     let mut child = cmd
         .spawn()
@@ -174,65 +169,78 @@ async fn execute_command_async(
         ToolError::ExecutionFailed("Failed to capture stderr".to_string())
     })?;
 
-    // Setup timeout if specified
-    let timeout_duration = if timeout_secs > 0 {
-        Some(Duration::from_secs(timeout_secs))
-    } else {
-        None
+    // Output is collected into shared buffers so it survives a kill
+    let stdout_lines = Arc::new(Mutex::new(Vec::new()));
+    let stderr_lines = Arc::new(Mutex::new(Vec::new()));
+    let mut stdout_task = tokio::spawn(read_lines(
+        BufReader::new(stdout),
+        OutputStream::Stdout,
+        stdout_lines.clone(),
+        context.clone(),
+    ));
+    let mut stderr_task = tokio::spawn(read_lines(
+        BufReader::new(stderr),
+        OutputStream::Stderr,
+        stderr_lines.clone(),
+        context.clone(),
+    ));
+
+    // Wait for the process, the timeout, or cancellation, whichever comes first
+    let timeout = async {
+        if timeout_secs > 0 {
+            tokio::time::sleep(Duration::from_secs(timeout_secs)).await
+        } else {
+            std::future::pending().await
+        }
     };
-
-    // Read output and wait for process completion with timeout
-    let result_future = async {
-        // Read stdout and stderr concurrently
-        let stdout_reader = BufReader::new(stdout);
-        let stderr_reader = BufReader::new(stderr);
-
-        let stdout_task = tokio::spawn(async move {
-            let mut lines = Vec::new();
-            let mut line_stream = stdout_reader.lines();
-            while let Ok(Some(line)) = line_stream.next_line().await {
-                lines.push(line);
-            }
-            lines
-        });
-
-        let stderr_task = tokio::spawn(async move {
-            let mut lines = Vec::new();
-            let mut line_stream = stderr_reader.lines();
-            while let Ok(Some(line)) = line_stream.next_line().await {
-                lines.push(line);
-            }
-            lines
-        });
-
-        // Wait for process to complete
+    // Background processes can keep the pipes open after the child exits,
+    // so draining the output stays under the timeout and cancellation too
+    let process_group = child.id();
+    let finished = async {
         let status = child.wait().await
             .map_err(|e| ToolError::ExecutionFailed(format!("Failed to wait for command: {}", e)))?;
-
-        // Wait for output tasks to complete
-        let stdout_lines = stdout_task.await
+        (&mut stdout_task).await
             .map_err(|e| ToolError::ExecutionFailed(format!("Failed to read stdout: {}", e)))?;
-        let stderr_lines = stderr_task.await
+        (&mut stderr_task).await
             .map_err(|e| ToolError::ExecutionFailed(format!("Failed to read stderr: {}", e)))?;
-
-        Ok::<_, ToolError>((status, stdout_lines, stderr_lines))
+        Ok::<_, ToolError>(status)
+    };
+    let interrupted = tokio::select! {
+        status = finished => Ok(status?),
+        _ = timeout => Err(ToolError::Timeout),
+        _ = context.cancelled() => Err(ToolError::Cancelled { partial: Value::Null }),
     };
 
-    // Apply timeout if specified
-    let (status, stdout_lines, stderr_lines) = if let Some(duration) = timeout_duration {
-        match tokio::time::timeout(duration, result_future).await {
-            Ok(result) => result?,
-            Err(_) => {
-                // Timeout exceeded - child process is automatically killed due to kill_on_drop
-                return Err(ToolError::Timeout);
-            }
+    let status = match interrupted {
+        Ok(status) => status,
+        Err(error) => {
+            // Kill the whole process tree; grandchildren may hold the pipes open.
+            // The child may already be reaped, so signal the group by its saved id.
+            kill_group(process_group);
+            let _ = child.start_kill();
+            let _ = child.wait().await;
+            let _ = tokio::time::timeout(OUTPUT_DRAIN_TIMEOUT, async {
+                let _ = stdout_task.await;
+                let _ = stderr_task.await;
+            })
+            .await;
+
+            return Err(match error {
+                ToolError::Cancelled { .. } => ToolError::Cancelled {
+                    partial: json!({
+                        "command": command,
+                        "args": args,
+                        "stdout": join_lines(&stdout_lines),
+                        "stderr": join_lines(&stderr_lines),
+                    }),
+                },
+                error => error,
+            });
         }
-    } else {
-        result_future.await?
     };
 
-    let stdout_text = stdout_lines.join("\n");
-    let stderr_text = stderr_lines.join("\n");
+    let stdout_text = join_lines(&stdout_lines);
+    let stderr_text = join_lines(&stderr_lines);
 
     let result = json!({
         "command": command,
@@ -247,6 +255,44 @@ async fn execute_command_async(
     Ok(result)
 }
 
+/// Forward lines from a pipe to the context and collect them
+async fn read_lines<R: AsyncRead + Unpin>(
+    reader: BufReader<R>,
+    stream: OutputStream,
+    collected: Arc<Mutex<Vec<String>>>,
+    context: ToolContext,
+) {
+    let mut line_stream = reader.lines();
+    while let Ok(Some(line)) = line_stream.next_line().await {
+        context.emit(stream, &format!("{}\n", line));
+        collected.lock().unwrap().push(line);
+    }
+}
+
+/// Join collected output lines
+fn join_lines(lines: &Mutex<Vec<String>>) -> String {
+    lines.lock().unwrap().join("\n")
+}
+
+/// Kill a child process and every process in its group
+pub(super) fn kill_process_group(child: &mut Child) {
+    kill_group(child.id());
+    let _ = child.start_kill();
+}
+
+/// Kill every process in the group led by `pid`
+fn kill_group(pid: Option<u32>) {
+    #[cfg(unix)]
+    if let Some(pid) = pid {
+        // SAFETY: killpg only sends a signal; the group was created at spawn
+        unsafe {
+            libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -257,7 +303,7 @@ mod tests {
         let result = tool.execute(&json!({
             "command": "echo",
             "args": ["hello", "world"],
-        }));
+        }), &ToolContext::new()).await;
 
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -272,7 +318,7 @@ mod tests {
         let result = tool.execute(&json!({
             "command": "pwd",
             "working_dir": "/tmp",
-        }));
+        }), &ToolContext::new()).await;
 
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -285,7 +331,7 @@ mod tests {
         let tool = RunCommand;
         let result = tool.execute(&json!({
             "command": "false",
-        }));
+        }), &ToolContext::new()).await;
 
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -298,7 +344,7 @@ mod tests {
         let tool = RunCommand;
         let result = tool.execute(&json!({
             "command": "this-command-does-not-exist-12345",
-        }));
+        }), &ToolContext::new()).await;
 
         // The command fails to spawn, returning an error
         assert!(result.is_err());
@@ -314,7 +360,7 @@ mod tests {
             "environment": {
                 "TEST_VAR": "test_value"
             },
-        }));
+        }), &ToolContext::new()).await;
 
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -329,12 +375,119 @@ mod tests {
             "command": "sleep",
             "args": ["10"],
             "timeout_secs": 1,
-        }));
+        }), &ToolContext::new()).await;
 
         // Should timeout
         assert!(matches!(result, Err(ToolError::Timeout)));
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_command_streams_output() {
+        let chunks = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = chunks.clone();
+        let context = ToolContext::new().with_output(move |stream, chunk| {
+            sink.lock().unwrap().push((stream, chunk.to_string()));
+        });
+
+        let tool = RunCommand;
+        let result = tool
+            .execute(
+                &json!({
+                    "command": "sh",
+                    "args": ["-c", "echo one; echo two >&2"],
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result["stdout"], "one");
+        let chunks = chunks.lock().unwrap();
+        assert!(chunks.contains(&(OutputStream::Stdout, "one\n".to_string())));
+        assert!(chunks.contains(&(OutputStream::Stderr, "two\n".to_string())));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_command_cancel() {
+        let token = tokio_util::sync::CancellationToken::new();
+        let context = ToolContext::new().with_cancellation(token.clone());
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            token.cancel();
+        });
+
+        // The background sleep shares the pipes, so only a group kill ends it
+        let started = std::time::Instant::now();
+        let result = RunCommand
+            .execute(
+                &json!({
+                    "command": "sh",
+                    "args": ["-c", "echo started; sleep 30 & sleep 30"],
+                }),
+                &context,
+            )
+            .await;
+        canceller.join().unwrap();
+
+        match result {
+            Err(ToolError::Cancelled { partial }) => {
+                assert_eq!(partial["stdout"], "started");
+            }
+            other => panic!("expected cancellation, got {:?}", other),
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_command_timeout_after_exit() {
+        // The shell exits at once, but the background sleep keeps the pipes open
+        let started = std::time::Instant::now();
+        let result = RunCommand
+            .execute(
+                &json!({
+                    "command": "sh",
+                    "args": ["-c", "echo hi; sleep 8 &"],
+                    "timeout_secs": 1,
+                }),
+                &ToolContext::new(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(ToolError::Timeout)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_command_cancel_after_exit() {
+        let token = tokio_util::sync::CancellationToken::new();
+        let context = ToolContext::new().with_cancellation(token.clone());
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            token.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        let result = RunCommand
+            .execute(
+                &json!({
+                    "command": "sh",
+                    "args": ["-c", "echo hi; sleep 8 &"],
+                }),
+                &context,
+            )
+            .await;
+        canceller.join().unwrap();
+
+        match result {
+            Err(ToolError::Cancelled { partial }) => assert_eq!(partial["stdout"], "hi"),
+            other => panic!("expected cancellation, got {:?}", other),
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_run_command_no_timeout() {
         let tool = RunCommand;
@@ -342,7 +495,7 @@ mod tests {
             "command": "sleep",
             "args": ["0"],
             "timeout_secs": 0,
-        }));
+        }), &ToolContext::new()).await;
 
         assert!(result.is_ok());
         let result = result.unwrap();
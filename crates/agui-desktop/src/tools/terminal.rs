@@ -0,0 +1,755 @@
+//! Interactive terminal sessions
+//!
+//! PTY-backed processes that stay alive across tool calls, so agents can
+//! drive shells, REPLs, debuggers and interactive installers. Output is
+//! interpreted with a `TerminalScreen` for tool results and reported as
+//! `TerminalEvent`s so the UI can show each session live in the Stage.
+
+use super::sandbox::Sandbox;
+use super::{run_blocking, Tool, ToolContext, ToolError, ToolRegistry, ToolResult};
+use async_trait::async_trait;
+use crate::stage::ansi::{TerminalScreen, DEFAULT_COLS, DEFAULT_ROWS, SCROLLBACK_LINES};
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use serde_json::json;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default time `terminal_write` waits for output (milliseconds)
+const DEFAULT_WAIT_MS: u64 = 1000;
+/// Longest time `terminal_write` may wait for output (milliseconds)
+const MAX_WAIT_MS: u64 = 30_000;
+/// Output is considered settled after this long without new data
+const OUTPUT_IDLE: Duration = Duration::from_millis(200);
+/// Interval for polling session output while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+/// Polls for an exit status after a session's terminal closes
+const EXIT_POLL_ATTEMPTS: usize = 40;
+
+/// Terminal session lifecycle and output notifications
+#[derive(Debug, Clone, PartialEq)]
+pub enum TerminalEvent {
+    /// A session was started
+    Opened {
+        session_id: String,
+        title: String,
+        rows: u16,
+        cols: u16,
+    },
+    /// Raw output (including ANSI escape sequences) was received
+    Output { session_id: String, data: String },
+    /// A session's terminal size changed
+    Resized {
+        session_id: String,
+        rows: u16,
+        cols: u16,
+    },
+    /// A session's process exited or the session was closed
+    Exited {
+        session_id: String,
+        exit_code: Option<u32>,
+    },
+}
+
+/// Output state shared with a session's reader thread
+#[derive(Debug)]
+struct SessionOutput {
+    screen: TerminalScreen,
+    /// Output not yet returned by a tool call
+    unread: String,
+    /// When output last arrived
+    last_output: Instant,
+    exit_code: Option<u32>,
+    exited: bool,
+}
+
+struct TerminalSession {
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
+    output: Arc<Mutex<SessionOutput>>,
+}
+
+/// Owns the running terminal sessions
+///
+/// Sessions are killed when the manager is dropped.
+pub struct TerminalManager {
+    sandbox: Sandbox,
+    sessions: Mutex<HashMap<String, TerminalSession>>,
+    next_id: AtomicUsize,
+    events_tx: Sender<TerminalEvent>,
+    events: Mutex<Receiver<TerminalEvent>>,
+}
+
+impl TerminalManager {
+    /// Create a manager whose sessions start inside the sandbox
+    pub fn new(sandbox: Sandbox) -> Self {
+        let (events_tx, events) = mpsc::channel();
+        Self {
+            sandbox,
+            sessions: Mutex::new(HashMap::new()),
+            next_id: AtomicUsize::new(1),
+            events_tx,
+            events: Mutex::new(events),
+        }
+    }
+
+    /// Register the terminal tools backed by this manager
+    pub fn register_tools(self: &Arc<Self>, registry: &mut ToolRegistry) {
+        registry.register(Box::new(TerminalOpen::new(Arc::clone(self))));
+        registry.register(Box::new(TerminalWrite::new(Arc::clone(self))));
+        registry.register(Box::new(TerminalResize::new(Arc::clone(self))));
+        registry.register(Box::new(TerminalClose::new(Arc::clone(self))));
+    }
+
+    /// Start a session running `command`, or the user's shell if None
+    ///
+    /// Returns the new session ID.
+    pub fn open(
+        &self,
+        command: Option<&str>,
+        args: &[String],
+        working_dir: Option<&str>,
+        rows: u16,
+        cols: u16,
+    ) -> Result<String, ToolError> {
+        let cwd = match working_dir {
+            Some(dir) => Some(self.sandbox.resolve(dir)?),
+            None => self.sandbox.roots().first().cloned(),
+        };
+
+        let mut cmd = match command {
+            Some(command) => {
+                let mut cmd = CommandBuilder::new(command);
+                cmd.args(args);
+                cmd
+            }
+            None => CommandBuilder::new_default_prog(),
+        };
+        if let Some(cwd) = cwd {
+            cmd.cwd(cwd);
+        }
+        cmd.env("TERM", "xterm-256color");
+
+        let pair = native_pty_system()
+            .openpty(pty_size(rows, cols))
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to open terminal: {}", e)))?;
+        let child = pair
+            .slave
+            .spawn_command(cmd)
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to spawn command: {}", e)))?;
+        // The child holds its own handle; keeping ours would prevent EOF
+        drop(pair.slave);
+
+        let reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to read terminal: {}", e)))?;
+        let writer = pair
+            .master
+            .take_writer()
+            .map_err(|e| ToolError::ExecutionFailed(format!("Failed to write terminal: {}", e)))?;
+
+        let session_id = format!("term-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let title = command.map(str::to_string).unwrap_or_else(|| "shell".to_string());
+        let session = TerminalSession {
+            master: pair.master,
+            writer,
+            child: Arc::new(Mutex::new(child)),
+            output: Arc::new(Mutex::new(SessionOutput {
+                screen: TerminalScreen::new(rows as usize, cols as usize),
+                unread: String::new(),
+                last_output: Instant::now(),
+                exit_code: None,
+                exited: false,
+            })),
+        };
+
+        let _ = self.events_tx.send(TerminalEvent::Opened {
+            session_id: session_id.clone(),
+            title,
+            rows,
+            cols,
+        });
+        spawn_reader(
+            session_id.clone(),
+            reader,
+            Arc::clone(&session.output),
+            Arc::clone(&session.child),
+            self.events_tx.clone(),
+        );
+        self.sessions.lock().unwrap().insert(session_id.clone(), session);
+
+        Ok(session_id)
+    }
+
+    /// Send input to a session's stdin
+    pub fn write(&self, session_id: &str, input: &str) -> Result<(), ToolError> {
+        self.with_session(session_id, |session| {
+            session.writer.write_all(input.as_bytes())?;
+            session.writer.flush()?;
+            Ok(())
+        })
+    }
+
+    /// Change a session's terminal size
+    pub fn resize(&self, session_id: &str, rows: u16, cols: u16) -> Result<(), ToolError> {
+        self.with_session(session_id, |session| {
+            session
+                .master
+                .resize(pty_size(rows, cols))
+                .map_err(|e| ToolError::ExecutionFailed(format!("Failed to resize terminal: {}", e)))?;
+            session.output.lock().unwrap().screen.resize(rows as usize, cols as usize);
+            Ok(())
+        })?;
+
+        let _ = self.events_tx.send(TerminalEvent::Resized {
+            session_id: session_id.to_string(),
+            rows,
+            cols,
+        });
+        Ok(())
+    }
+
+    /// Kill a session's process and forget the session
+    pub fn close(&self, session_id: &str) -> Result<(), ToolError> {
+        let session = self
+            .sessions
+            .lock()
+            .unwrap()
+            .remove(session_id)
+            .ok_or_else(|| unknown_session(session_id))?;
+        let _ = session.child.lock().unwrap().kill();
+        Ok(())
+    }
+
+    /// Wait for output to settle, then report what the session printed
+    ///
+    /// Waits up to `wait` for output to stop arriving, returning early if
+    /// the context is cancelled or the process exits. Unread output is
+    /// consumed.
+    pub fn read(&self, session_id: &str, wait: Duration, context: &ToolContext) -> ToolResult {
+        let output = self.with_session(session_id, |session| Ok(Arc::clone(&session.output)))?;
+
+        let started = Instant::now();
+        while started.elapsed() < wait && !context.is_cancelled() {
+            {
+                let output = output.lock().unwrap();
+                let idle = output.last_output.elapsed() >= OUTPUT_IDLE;
+                if output.exited || (idle && !output.unread.is_empty()) {
+                    break;
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+
+        let mut output = output.lock().unwrap();
+        let unread = std::mem::take(&mut output.unread);
+        let (rows, cols) = (output.screen.rows(), output.screen.cols());
+        let text = output.screen.text();
+        let screen: Vec<&str> = text.lines().collect();
+        let screen = screen[screen.len().saturating_sub(rows)..].join("\n");
+
+        Ok(json!({
+            "session_id": session_id,
+            "output": TerminalScreen::from_output(&unread, SCROLLBACK_LINES, cols).text(),
+            "screen": screen,
+            "running": !output.exited,
+            "exit_code": output.exit_code,
+        }))
+    }
+
+    /// IDs of the open sessions
+    pub fn session_ids(&self) -> Vec<String> {
+        self.sessions.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// Take all pending session events
+    pub fn drain_events(&self) -> Vec<TerminalEvent> {
+        self.events.lock().unwrap().try_iter().collect()
+    }
+
+    fn with_session<T>(
+        &self,
+        session_id: &str,
+        f: impl FnOnce(&mut TerminalSession) -> Result<T, ToolError>,
+    ) -> Result<T, ToolError> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| unknown_session(session_id))?;
+        f(session)
+    }
+}
+
+impl Drop for TerminalManager {
+    fn drop(&mut self) {
+        for session in self.sessions.lock().unwrap().values() {
+            let _ = session.child.lock().unwrap().kill();
+        }
+    }
+}
+
+impl std::fmt::Debug for TerminalManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TerminalManager")
+            .field("sandbox", &self.sandbox)
+            .field("sessions", &self.session_ids())
+            .finish()
+    }
+}
+
+/// Read PTY output on a thread until the process closes the terminal
+fn spawn_reader(
+    session_id: String,
+    mut reader: Box<dyn Read + Send>,
+    output: Arc<Mutex<SessionOutput>>,
+    child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
+    events: Sender<TerminalEvent>,
+) {
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        // Bytes of a UTF-8 sequence split across reads
+        let mut pending = Vec::new();
+
+        while let Ok(n) = reader.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            pending.extend_from_slice(&buf[..n]);
+            let valid = match std::str::from_utf8(&pending) {
+                Ok(_) => pending.len(),
+                Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                // Invalid bytes: decode lossily rather than stall
+                Err(_) => pending.len(),
+            };
+            let data = String::from_utf8_lossy(&pending[..valid]).into_owned();
+            pending.drain(..valid);
+
+            {
+                let mut output = output.lock().unwrap();
+                output.screen.feed(&data);
+                output.unread.push_str(&data);
+                output.last_output = Instant::now();
+            }
+            let _ = events.send(TerminalEvent::Output {
+                session_id: session_id.clone(),
+                data,
+            });
+        }
+
+        // The process normally exits right after closing the terminal; poll
+        // briefly without holding the lock so close() is never blocked
+        let mut exit_code = None;
+        for _ in 0..EXIT_POLL_ATTEMPTS {
+            if let Ok(Some(status)) = child.lock().unwrap().try_wait() {
+                exit_code = Some(status.exit_code());
+                break;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        {
+            let mut output = output.lock().unwrap();
+            output.exited = true;
+            output.exit_code = exit_code;
+        }
+        let _ = events.send(TerminalEvent::Exited {
+            session_id,
+            exit_code,
+        });
+    });
+}
+
+fn pty_size(rows: u16, cols: u16) -> PtySize {
+    PtySize {
+        rows: rows.max(1),
+        cols: cols.max(1),
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+fn unknown_session(session_id: &str) -> ToolError {
+    ToolError::ExecutionFailed(format!("Unknown terminal session: {}", session_id))
+}
+
+/// Read a terminal dimension parameter
+fn dimension(params: &Value, name: &str, default: usize) -> u16 {
+    params
+        .get(name)
+        .and_then(|v| v.as_u64())
+        .unwrap_or(default as u64)
+        .clamp(1, u16::MAX as u64) as u16
+}
+
+fn session_id_param(params: &Value) -> Result<&str, ToolError> {
+    params
+        .get("session_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ToolError::ExecutionFailed("Missing 'session_id' parameter".to_string()))
+}
+
+fn session_id_schema() -> super::schema::ParameterSchema {
+    super::schema::ParameterSchema::string().with_description("Terminal session ID from terminal_open")
+}
+
+fn size_schemas(props: &mut HashMap<String, super::schema::ParameterSchema>) {
+    props.insert(
+        "rows".to_string(),
+        super::schema::ParameterSchema::integer()
+            .with_description("Terminal height in rows")
+            .with_minimum(1.0)
+            .with_default(json!(DEFAULT_ROWS)),
+    );
+    props.insert(
+        "cols".to_string(),
+        super::schema::ParameterSchema::integer()
+            .with_description("Terminal width in columns")
+            .with_minimum(1.0)
+            .with_default(json!(DEFAULT_COLS)),
+    );
+}
+
+/// Start an interactive terminal session
+#[derive(Debug, Clone)]
+pub struct TerminalOpen {
+    manager: Arc<TerminalManager>,
+}
+
+impl TerminalOpen {
+    /// Create the tool backed by a session manager
+    pub fn new(manager: Arc<TerminalManager>) -> Self {
+        Self { manager }
+    }
+}
+
+#[async_trait]
+impl Tool for TerminalOpen {
+    fn name(&self) -> &str {
+        "terminal_open"
+    }
+
+    fn description(&self) -> &str {
+        "Start an interactive terminal session (PTY) that stays alive across calls"
+    }
+
+    fn parameters_schema(&self) -> &super::schema::ParameterSchema {
+        static SCHEMA: std::sync::OnceLock<super::schema::ParameterSchema> = std::sync::OnceLock::new();
+        SCHEMA.get_or_init(|| {
+            let mut props = HashMap::new();
+            props.insert(
+                "command".to_string(),
+                super::schema::ParameterSchema::string()
+                    .with_description("Program to run (defaults to the user's shell)"),
+            );
+            props.insert(
+                "args".to_string(),
+                super::schema::ParameterSchema::array(super::schema::ParameterSchema::string())
+                    .with_description("Program arguments")
+                    .with_default(json!([])),
+            );
+            props.insert(
+                "working_dir".to_string(),
+                super::schema::ParameterSchema::string()
+                    .with_description("Working directory for the session"),
+            );
+            size_schemas(&mut props);
+            super::schema::ParameterSchema::object(props)
+        })
+    }
+
+    /// Starts the session, then waits briefly for its first output
+    async fn execute(&self, params: &Value, context: &ToolContext) -> ToolResult {
+        let context = context.clone();
+        run_blocking(self, params, move |tool, params| tool.run(params, &context)).await
+    }
+}
+
+impl TerminalOpen {
+    fn run(&self, params: &Value, context: &ToolContext) -> ToolResult {
+        let command = params.get("command").and_then(|v| v.as_str());
+        let args: Vec<String> = params
+            .get("args")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let working_dir = params.get("working_dir").and_then(|v| v.as_str());
+        let rows = dimension(params, "rows", DEFAULT_ROWS);
+        let cols = dimension(params, "cols", DEFAULT_COLS);
+
+        let session_id = self.manager.open(command, &args, working_dir, rows, cols)?;
+        self.manager
+            .read(&session_id, Duration::from_millis(DEFAULT_WAIT_MS), context)
+    }
+}
+
+/// Send input to a terminal session and return its output
+#[derive(Debug, Clone)]
+pub struct TerminalWrite {
+    manager: Arc<TerminalManager>,
+}
+
+impl TerminalWrite {
+    /// Create the tool backed by a session manager
+    pub fn new(manager: Arc<TerminalManager>) -> Self {
+        Self { manager }
+    }
+}
+
+#[async_trait]
+impl Tool for TerminalWrite {
+    fn name(&self) -> &str {
+        "terminal_write"
+    }
+
+    fn description(&self) -> &str {
+        "Write input to a terminal session and return the output it produces. \
+         Include \"\\n\" to press Enter and control characters such as \"\\u0003\" for Ctrl-C; \
+         empty input just reads new output"
+    }
+
+    fn parameters_schema(&self) -> &super::schema::ParameterSchema {
+        static SCHEMA: std::sync::OnceLock<super::schema::ParameterSchema> = std::sync::OnceLock::new();
+        SCHEMA.get_or_init(|| {
+            let mut props = HashMap::new();
+            props.insert("session_id".to_string(), session_id_schema());
+            props.insert(
+                "input".to_string(),
+                super::schema::ParameterSchema::string()
+                    .with_description("Text to send to the session's stdin")
+                    .with_default(json!("")),
+            );
+            props.insert(
+                "wait_ms".to_string(),
+                super::schema::ParameterSchema::integer()
+                    .with_description("Longest time to wait for output to settle, in milliseconds")
+                    .with_minimum(0.0)
+                    .with_maximum(MAX_WAIT_MS as f64)
+                    .with_default(json!(DEFAULT_WAIT_MS)),
+            );
+            super::schema::ParameterSchema::object(props)
+                .with_required(vec!["session_id".to_string()])
+        })
+    }
+
+    /// Writes the input, then waits for output until it settles or the call is cancelled
+    async fn execute(&self, params: &Value, context: &ToolContext) -> ToolResult {
+        let context = context.clone();
+        run_blocking(self, params, move |tool, params| tool.run(params, &context)).await
+    }
+}
+
+impl TerminalWrite {
+    fn run(&self, params: &Value, context: &ToolContext) -> ToolResult {
+        let session_id = session_id_param(params)?;
+        let input = params.get("input").and_then(|v| v.as_str()).unwrap_or_default();
+        let wait_ms = params
+            .get("wait_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_WAIT_MS)
+            .min(MAX_WAIT_MS);
+
+        if !input.is_empty() {
+            self.manager.write(session_id, input)?;
+        }
+        self.manager
+            .read(session_id, Duration::from_millis(wait_ms), context)
+    }
+}
+
+/// Resize a terminal session
+#[derive(Debug, Clone)]
+pub struct TerminalResize {
+    manager: Arc<TerminalManager>,
+}
+
+impl TerminalResize {
+    /// Create the tool backed by a session manager
+    pub fn new(manager: Arc<TerminalManager>) -> Self {
+        Self { manager }
+    }
+}
+
+#[async_trait]
+impl Tool for TerminalResize {
+    fn name(&self) -> &str {
+        "terminal_resize"
+    }
+
+    fn description(&self) -> &str {
+        "Change the size of a terminal session"
+    }
+
+    fn parameters_schema(&self) -> &super::schema::ParameterSchema {
+        static SCHEMA: std::sync::OnceLock<super::schema::ParameterSchema> = std::sync::OnceLock::new();
+        SCHEMA.get_or_init(|| {
+            let mut props = HashMap::new();
+            props.insert("session_id".to_string(), session_id_schema());
+            size_schemas(&mut props);
+            super::schema::ParameterSchema::object(props)
+                .with_required(vec!["session_id".to_string(), "rows".to_string(), "cols".to_string()])
+        })
+    }
+
+    async fn execute(&self, params: &Value, _context: &ToolContext) -> ToolResult {
+        run_blocking(self, params, Self::run).await
+    }
+}
+
+impl TerminalResize {
+    fn run(&self, params: &Value) -> ToolResult {
+        let session_id = session_id_param(params)?;
+        let rows = dimension(params, "rows", DEFAULT_ROWS);
+        let cols = dimension(params, "cols", DEFAULT_COLS);
+
+        self.manager.resize(session_id, rows, cols)?;
+        Ok(json!({
+            "session_id": session_id,
+            "rows": rows,
+            "cols": cols,
+        }))
+    }
+}
+
+/// End a terminal session
+#[derive(Debug, Clone)]
+pub struct TerminalClose {
+    manager: Arc<TerminalManager>,
+}
+
+impl TerminalClose {
+    /// Create the tool backed by a session manager
+    pub fn new(manager: Arc<TerminalManager>) -> Self {
+        Self { manager }
+    }
+}
+
+#[async_trait]
+impl Tool for TerminalClose {
+    fn name(&self) -> &str {
+        "terminal_close"
+    }
+
+    fn description(&self) -> &str {
+        "Kill a terminal session's process and close the session"
+    }
+
+    fn parameters_schema(&self) -> &super::schema::ParameterSchema {
+        static SCHEMA: std::sync::OnceLock<super::schema::ParameterSchema> = std::sync::OnceLock::new();
+        SCHEMA.get_or_init(|| {
+            let mut props = HashMap::new();
+            props.insert("session_id".to_string(), session_id_schema());
+            super::schema::ParameterSchema::object(props)
+                .with_required(vec!["session_id".to_string()])
+        })
+    }
+
+    async fn execute(&self, params: &Value, _context: &ToolContext) -> ToolResult {
+        run_blocking(self, params, Self::run).await
+    }
+}
+
+impl TerminalClose {
+    fn run(&self, params: &Value) -> ToolResult {
+        let session_id = session_id_param(params)?;
+        self.manager.close(session_id)?;
+        Ok(json!({
+            "session_id": session_id,
+            "closed": true,
+        }))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn registry() -> (Arc<TerminalManager>, ToolRegistry) {
+        let manager = Arc::new(TerminalManager::new(Sandbox::unrestricted()));
+        let mut registry = ToolRegistry::new();
+        manager.register_tools(&mut registry);
+        (manager, registry)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_interactive_session() {
+        let (manager, registry) = registry();
+
+        let opened = registry
+            .execute("terminal_open", &json!({"command": "sh", "rows": 10, "cols": 40}))
+            .await
+            .unwrap();
+        let session_id = opened["session_id"].as_str().unwrap().to_string();
+        assert_eq!(manager.session_ids(), vec![session_id.clone()]);
+
+        // State persists across calls
+        registry
+            .execute("terminal_write", &json!({"session_id": session_id, "input": "X=42\n"}))
+            .await
+            .unwrap();
+        let result = registry
+            .execute(
+                "terminal_write",
+                &json!({"session_id": session_id, "input": "printf '\\033[31m%s\\033[0m\\n' \"$X\"\n"}),
+            )
+            .await
+            .unwrap();
+        assert!(result["output"].as_str().unwrap().lines().any(|l| l == "42"));
+        assert_eq!(result["running"], true);
+
+        registry
+            .execute("terminal_resize", &json!({"session_id": session_id, "rows": 5, "cols": 20}))
+            .await
+            .unwrap();
+
+        let result = registry
+            .execute("terminal_write", &json!({"session_id": session_id, "input": "exit 3\n"}))
+            .await
+            .unwrap();
+        assert_eq!(result["running"], false);
+        assert_eq!(result["exit_code"], 3);
+
+        let events = manager.drain_events();
+        assert!(matches!(&events[0], TerminalEvent::Opened { rows: 10, cols: 40, .. }));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            TerminalEvent::Output { data, .. } if data.contains("\u{1b}[31m42")
+        )));
+        assert!(events.iter().any(|e| matches!(e, TerminalEvent::Resized { rows: 5, cols: 20, .. })));
+        assert!(matches!(
+            events.last(),
+            Some(TerminalEvent::Exited { exit_code: Some(3), .. })
+        ));
+
+        registry
+            .execute("terminal_close", &json!({"session_id": session_id}))
+            .await
+            .unwrap();
+        assert!(manager.session_ids().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_unknown_session() {
+        let (_manager, registry) = registry();
+        let result = registry
+            .execute("terminal_write", &json!({"session_id": "term-99", "input": "x"}))
+            .await;
+        assert!(matches!(result, Err(ToolError::ExecutionFailed(msg)) if msg.contains("term-99")));
+    }
+
+    #[test]
+    fn test_working_dir_sandboxed() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = TerminalManager::new(Sandbox::new([temp_dir.path()]).unwrap());
+        let result = manager.open(Some("sh"), &[], Some("/"), 24, 80);
+        assert!(matches!(result, Err(ToolError::PathViolation(_))));
+    }
+}
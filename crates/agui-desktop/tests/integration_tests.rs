@@ -17,6 +17,20 @@ fn test_config_default() {
     assert_eq!(config.metrics_port, 9090);
 }
 
+#[test]
+fn test_config_keeps_tool_settings_outside_workspace() {
+    let config = AppConfig::default();
+    let roots = config.workspace_roots();
+    // Without a home directory the settings fall back to the project root
+    if std::env::var_os("HOME").is_some() || std::env::var_os("APPDATA").is_some() {
+        for path in [config.tool_policy_path(), config.plugin_dir(), config.audit_log_path()] {
+            assert!(!roots.iter().any(|root| path.starts_with(root)), "{}", path.display());
+        }
+    }
+    assert!(config.protected_paths().contains(&config.plugin_dir()));
+    assert!(config.protected_paths().contains(&config.audit_log_path()));
+}
+
 #[test]
 fn test_version_display() {
     let version = VERSION.to_string();